@@ -1,46 +1,298 @@
-use tauri::State;
+use std::collections::HashMap;
+use std::process::Child;
 
-use crate::protocol::connection::{team_to_ip, DsCommand};
-use crate::protocol::types::Alliance;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::config::{
+    self, CustomDashboard, CustomLaunchType, DsConfig, GameDataPreset, NamedTarget, TargetAddress,
+};
+use crate::protocol::connection::{validate_team_number, DsCommand, DsEvent, TeamIpResolver};
+use crate::protocol::types::{Alliance, AllianceInfo};
 use crate::AppState;
 
-#[tauri::command]
-pub async fn set_team_number(state: State<'_, AppState>, team: u32) -> Result<(), String> {
-    // Update target IP via watch channel so TCP console reconnects
-    let ip = team_to_ip(team);
-    let _ = state.target_ip_tx.send(ip);
+/// Apply `update` to the persisted config and write it to disk
+pub(super) fn save_setting(app: &AppHandle, state: &AppState, update: impl FnOnce(&mut DsConfig)) {
+    let snapshot = {
+        let mut cfg = state.config.lock();
+        update(&mut cfg);
+        cfg.clone()
+    };
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    if let Err(e) = config::save_config(&config::config_path(&dir), &snapshot) {
+        tracing::warn!("Failed to save config: {e}");
+    }
+}
+
+/// Point the DS at a team's roboRIO and save it as the launch default
+async fn apply_team_number(app: &AppHandle, state: &AppState, team: u32) -> Result<(), String> {
+    validate_team_number(team)?;
+    // The protocol loop resolves the target IP (honoring simulation mode) and
+    // updates the watch channel so the TCP console reconnects
     state
         .cmd_tx
         .send(DsCommand::SetTeamNumber(team))
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    save_setting(app, state, |cfg| {
+        // A new team replaces any explicit IP chosen for the old one
+        if cfg.team_number != team {
+            cfg.target_ip = None;
+        }
+        cfg.team_number = team;
+    });
+    Ok(())
+}
+
+/// Point the DS at an explicit IP and save it as the launch default
+async fn apply_target_ip(app: &AppHandle, state: &AppState, ip: String) -> Result<(), String> {
+    // The protocol loop updates the watch channel (unless a USB roboRIO is
+    // preferred) so the TCP console reconnects
+    state
+        .cmd_tx
+        .send(DsCommand::SetTargetIp(ip.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    save_setting(app, state, |cfg| cfg.target_ip = Some(ip));
+    Ok(())
+}
+
+/// Record which saved target is in use and tell the frontend
+async fn set_active_target(app: &AppHandle, state: &AppState, label: Option<String>) {
+    if state.config.lock().active_target == label {
+        return;
+    }
+    save_setting(app, state, |cfg| cfg.active_target = label.clone());
+    let _ = state.event_tx.send(DsEvent::ActiveTarget { label }).await;
 }
 
 #[tauri::command]
-pub async fn set_alliance(state: State<'_, AppState>, alliance: String) -> Result<(), String> {
-    let a = match alliance.as_str() {
-        "Red1" => Alliance::Red1,
-        "Red2" => Alliance::Red2,
-        "Red3" => Alliance::Red3,
-        "Blue1" => Alliance::Blue1,
-        "Blue2" => Alliance::Blue2,
-        "Blue3" => Alliance::Blue3,
-        _ => return Err(format!("Unknown alliance: {alliance}")),
-    };
+pub async fn set_team_number(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    team: u32,
+) -> Result<(), String> {
+    apply_team_number(&app, &state, team).await?;
+    // Re-sending the active target's own team (e.g. at startup) keeps it active
+    let address = TargetAddress::Team(team);
+    if state.config.lock().active_address() != Some(&address) {
+        set_active_target(&app, &state, None).await;
+    }
+    Ok(())
+}
+
+fn parse_alliance(alliance: &str) -> Result<Alliance, String> {
+    match alliance {
+        "Red1" => Ok(Alliance::Red1),
+        "Red2" => Ok(Alliance::Red2),
+        "Red3" => Ok(Alliance::Red3),
+        "Blue1" => Ok(Alliance::Blue1),
+        "Blue2" => Ok(Alliance::Blue2),
+        "Blue3" => Ok(Alliance::Blue3),
+        _ => Err(format!("Unknown alliance: {alliance}")),
+    }
+}
+
+#[tauri::command]
+pub async fn set_alliance(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    alliance: String,
+) -> Result<(), String> {
+    let a = parse_alliance(&alliance)?;
     state
         .cmd_tx
         .send(DsCommand::SetAlliance(a))
         .await
+        .map_err(|e| e.to_string())?;
+    save_setting(&app, &state, |cfg| cfg.alliance = a);
+
+    // Symmetric-field practice: a preset bound to this station follows it
+    let preset = state.config.lock().game_data_for_alliance(a).map(str::to_string);
+    if let Some(data) = preset {
+        state
+            .cmd_tx
+            .send(DsCommand::SetGameData(data))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Alliance station currently sent to the robot, with its color and
+/// position, as last sent in `alliance-changed`
+#[tauri::command]
+pub fn get_alliance(state: State<'_, AppState>) -> AllianceInfo {
+    state.robot_snapshot.read().alliance.clone()
+}
+
+/// Temporarily override the alliance sent to the robot without changing the
+/// saved alliance. Pass `null` to revert.
+#[tauri::command]
+pub async fn set_alliance_override(
+    state: State<'_, AppState>,
+    alliance: Option<String>,
+) -> Result<(), String> {
+    let a = alliance.as_deref().map(parse_alliance).transpose()?;
+    state
+        .cmd_tx
+        .send(DsCommand::SetAllianceOverride(a))
+        .await
         .map_err(|e| e.to_string())
 }
 
+/// Check a target is an IP address, or a hostname that resolves
+async fn validate_target(target: &str) -> Result<(), String> {
+    if target.parse::<std::net::IpAddr>().is_ok() {
+        return Ok(());
+    }
+    if !config::is_valid_hostname(target) {
+        return Err(format!("Not a valid IP address or hostname: \"{target}\""));
+    }
+    let mut addrs = tokio::net::lookup_host((target, 1110))
+        .await
+        .map_err(|e| format!("Could not resolve {target}: {e}"))?;
+    match addrs.next() {
+        Some(_) => Ok(()),
+        None => Err(format!("{target} did not resolve to any address")),
+    }
+}
+
+/// Target a roboRIO by IP address or hostname. Hostnames must resolve.
+#[tauri::command]
+pub async fn set_target_ip(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    ip: String,
+) -> Result<(), String> {
+    let ip = ip.trim().to_string();
+    validate_target(&ip).await?;
+    let address = TargetAddress::Ip(ip.clone());
+    apply_target_ip(&app, &state, ip).await?;
+    if state.config.lock().active_address() != Some(&address) {
+        set_active_target(&app, &state, None).await;
+    }
+    Ok(())
+}
+
+/// Set the team → IP template for off-season events with nonstandard
+/// addressing, e.g. "10.{te}.{am}.2" (`{te}`, `{am}` and `{team}` are
+/// replaced). None restores the default. Retargets now unless an explicit IP
+/// is in use.
+#[tauri::command]
+pub async fn set_ip_template(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    template: Option<String>,
+) -> Result<(), String> {
+    let resolver = match template.as_deref() {
+        Some(t) => TeamIpResolver::new(t)?,
+        None => TeamIpResolver::default(),
+    };
+    let template = template.map(|_| resolver.template().to_string());
+    state
+        .cmd_tx
+        .send(DsCommand::SetIpResolver(resolver))
+        .await
+        .map_err(|e| e.to_string())?;
+    save_setting(&app, &state, |cfg| cfg.ip_template = template);
+
+    let (team, explicit_ip) = {
+        let cfg = state.config.lock();
+        (cfg.team_number, cfg.target_ip.is_some())
+    };
+    if !explicit_ip {
+        state
+            .cmd_tx
+            .send(DsCommand::SetTeamNumber(team))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Saved targets and the label of the one in use
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetList {
+    pub targets: Vec<NamedTarget>,
+    pub active: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_targets(state: State<'_, AppState>) -> Result<TargetList, String> {
+    let cfg = state.config.lock();
+    Ok(TargetList {
+        targets: cfg.targets.clone(),
+        active: cfg.active_target.clone(),
+    })
+}
+
+/// Save a named target; `team_or_ip` is a team number or an IP address.
+/// Re-adding a label replaces its address.
+#[tauri::command]
+pub fn add_target(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    label: String,
+    team_or_ip: String,
+) -> Result<(), String> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("Target label cannot be empty".into());
+    }
+    let address = TargetAddress::parse(&team_or_ip)?;
+    save_setting(&app, &state, |cfg| cfg.upsert_target(NamedTarget { label, address }));
+    Ok(())
+}
+
+/// Switch to a saved target, exactly as if its team number or IP had been
+/// entered manually
+#[tauri::command]
+pub async fn switch_target(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    label: String,
+) -> Result<(), String> {
+    let target = state
+        .config
+        .lock()
+        .find_target(&label)
+        .cloned()
+        .ok_or_else(|| format!("Unknown target: {label}"))?;
+    match target.address {
+        TargetAddress::Team(team) => apply_team_number(&app, &state, team).await?,
+        TargetAddress::Ip(ip) => apply_target_ip(&app, &state, ip).await?,
+    }
+    set_active_target(&app, &state, Some(target.label)).await;
+    Ok(())
+}
+
+/// Toggle simulation mode: target localhost regardless of team number. The
+/// configured team is kept and applies again when simulation is turned off.
 #[tauri::command]
-pub async fn set_target_ip(state: State<'_, AppState>, ip: String) -> Result<(), String> {
-    // Update watch channel so TCP console reconnects
-    let _ = state.target_ip_tx.send(ip.clone());
+pub async fn set_simulation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
     state
         .cmd_tx
-        .send(DsCommand::SetTargetIp(ip))
+        .send(DsCommand::SetSimulation(enabled))
+        .await
+        .map_err(|e| e.to_string())?;
+    save_setting(&app, &state, |cfg| cfg.simulation = enabled);
+    Ok(())
+}
+
+/// Toggle talking to a USB-tethered roboRIO (172.22.11.2) whenever one is
+/// detected, regardless of team number
+#[tauri::command]
+pub async fn set_prefer_usb(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetPreferUsb(enabled))
         .await
         .map_err(|e| e.to_string())
 }
@@ -54,11 +306,74 @@ pub async fn set_game_data(state: State<'_, AppState>, data: String) -> Result<(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_game_data_presets(state: State<'_, AppState>) -> Result<Vec<GameDataPreset>, String> {
+    Ok(state.config.lock().game_data_presets.clone())
+}
+
+/// Save a named game data preset, optionally bound to an alliance station
+/// so it is applied whenever that station is selected
+#[tauri::command]
+pub fn save_game_data_preset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    data: String,
+    alliance: Option<String>,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".into());
+    }
+    let alliance = alliance.as_deref().map(parse_alliance).transpose()?;
+    save_setting(&app, &state, |cfg| {
+        cfg.upsert_game_data_preset(GameDataPreset { name, data, alliance })
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_game_data_preset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    save_setting(&app, &state, |cfg| cfg.game_data_presets.retain(|p| p.name != name));
+    Ok(())
+}
+
+/// Send a preset's game data to the robot. Returns the data sent.
+#[tauri::command]
+pub async fn apply_game_data_preset(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<String, String> {
+    let data = state
+        .config
+        .lock()
+        .find_game_data_preset(&name)
+        .map(|p| p.data.clone())
+        .ok_or_else(|| format!("Unknown game data preset: {name}"))?;
+    state
+        .cmd_tx
+        .send(DsCommand::SetGameData(data.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
 // ---------------------------------------------------------------------------
 // Dashboard detection & launching (Shuffleboard, Elastic, AdvantageScope)
 // ---------------------------------------------------------------------------
 
-const ALL_DASHBOARDS: &[&str] = &["Shuffleboard", "Elastic", "AdvantageScope"];
+const ALL_DASHBOARDS: &[&str] = &[
+    "Shuffleboard",
+    "Elastic",
+    "AdvantageScope",
+    "Glass",
+    "SmartDashboard",
+    "PathPlanner",
+];
 
 fn home_dir() -> String {
     std::env::var("HOME")
@@ -66,59 +381,98 @@ fn home_dir() -> String {
         .unwrap_or_default()
 }
 
-/// Find the latest WPILib year directory under a base path (e.g. ~/wpilib/).
-/// Returns directories whose names are numeric, sorted descending so the
-/// newest year comes first.
-fn latest_wpilib_year(base: &std::path::Path) -> Option<std::path::PathBuf> {
-    let mut years: Vec<std::path::PathBuf> = std::fs::read_dir(base)
-        .ok()?
+/// WPILib year directories under a base path (e.g. ~/wpilib/): numeric
+/// directory names, newest year first.
+fn wpilib_years(base: &std::path::Path) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir(base) else {
+        return Vec::new();
+    };
+    let mut years: Vec<u32> = entries
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.file_type().map(|t| t.is_dir()).unwrap_or(false)
-                && e.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit())
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if name.chars().all(|c| c.is_ascii_digit()) {
+                name.parse().ok()
+            } else {
+                None
+            }
         })
-        .map(|e| e.path())
         .collect();
-    years.sort();
-    years.pop() // highest year
+    years.sort_unstable_by(|a, b| b.cmp(a));
+    years
 }
 
-/// Return all WPILib year directories to search (user home + Windows Public).
-fn wpilib_roots() -> Vec<std::path::PathBuf> {
-    let mut roots = Vec::new();
-    let home = home_dir();
-
-    // ~/wpilib/{year}  (macOS / Linux / Windows user-profile)
-    let home_base = std::path::PathBuf::from(format!("{home}/wpilib"));
-    if let Some(p) = latest_wpilib_year(&home_base) {
-        roots.push(p);
-    }
+/// The year directory to use under `base`: `year` if it is installed there,
+/// otherwise the latest one.
+fn wpilib_year_dir(base: &std::path::Path, year: Option<u32>) -> Option<std::path::PathBuf> {
+    let years = wpilib_years(base);
+    let chosen = year
+        .filter(|y| years.contains(y))
+        .or_else(|| years.first().copied())?;
+    Some(base.join(chosen.to_string()))
+}
 
-    // C:\Users\Public\wpilib\{year}  (Windows shared install)
-    let public_base = std::path::PathBuf::from("C:\\Users\\Public\\wpilib");
-    if let Some(p) = latest_wpilib_year(&public_base) {
-        roots.push(p);
-    }
+/// Base directories holding WPILib year installs (user home + Windows Public)
+fn wpilib_bases() -> Vec<std::path::PathBuf> {
+    let home = home_dir();
+    vec![
+        // ~/wpilib/{year}  (macOS / Linux / Windows user-profile)
+        std::path::PathBuf::from(format!("{home}/wpilib")),
+        // C:\Users\Public\wpilib\{year}  (Windows shared install)
+        std::path::PathBuf::from("C:\\Users\\Public\\wpilib"),
+    ]
+}
 
-    roots
+/// Return all WPILib year directories to search, preferring `year` and
+/// falling back to the latest install in each base.
+fn wpilib_roots(year: Option<u32>) -> Vec<std::path::PathBuf> {
+    wpilib_bases()
+        .iter()
+        .filter_map(|base| wpilib_year_dir(base, year))
+        .collect()
 }
 
-/// Check if a command is reachable on PATH.
-fn command_on_path(cmd: &str) -> bool {
-    let check = if cfg!(target_os = "windows") {
-        std::process::Command::new("where")
-            .arg(cmd)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status()
+/// Full path of a command on PATH, or None. On Windows, extensions from
+/// `PATHEXT` (default `.exe;.bat;.cmd`) are tried as well.
+fn find_on_path(cmd: &str) -> Option<std::path::PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    let pathext = if cfg!(target_os = "windows") {
+        Some(std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.BAT;.CMD".into()))
     } else {
-        std::process::Command::new("which")
-            .arg(cmd)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status()
+        None
     };
-    check.map(|s| s.success()).unwrap_or(false)
+    find_in_path_var(cmd, &path_var, pathext.as_deref())
+}
+
+/// Search the directories of `path_var` for `cmd`, also trying each
+/// `;`-separated extension in `pathext` when given
+fn find_in_path_var(
+    cmd: &str,
+    path_var: &std::ffi::OsStr,
+    pathext: Option<&str>,
+) -> Option<std::path::PathBuf> {
+    let extensions: Vec<&str> = pathext
+        .map(|exts| exts.split(';').filter(|e| !e.is_empty()).collect())
+        .unwrap_or_default();
+    std::env::split_paths(path_var)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .find_map(|dir| {
+            std::iter::once(dir.join(cmd))
+                .chain(extensions.iter().map(|ext| dir.join(format!("{cmd}{ext}"))))
+                .find(|candidate| is_executable(candidate))
+        })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
 }
 
 /// Scan a directory for the first entry matching a predicate.
@@ -139,16 +493,42 @@ enum Launch {
     MacOpen(std::path::PathBuf),      // open <path.app>
     #[allow(dead_code)]
     WinBatch(std::path::PathBuf),     // cmd /C <path.bat>
-    PathCmd(String),                  // command on PATH
+    PathCmd(std::path::PathBuf),      // command found on PATH (full path)
+}
+
+impl Launch {
+    /// The spawned process only hands off to the real app (`open`, `cmd /C`)
+    /// and exits on success, so only a nonzero exit means failure
+    fn is_wrapper(&self) -> bool {
+        matches!(self, Launch::MacOpen(_) | Launch::WinBatch(_))
+    }
+}
+
+/// Dashboard processes started by the DS, keyed by dashboard name
+pub type LaunchedDashboards = std::sync::Arc<parking_lot::Mutex<HashMap<String, Child>>>;
+
+/// How long a launched dashboard must stay up to count as started
+const DASHBOARD_HEALTH_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Whether a dashboard's process state after `DASHBOARD_HEALTH_DELAY` means
+/// it failed to start. `exit` is None while the process is still running.
+fn launch_failed(exit: Option<std::process::ExitStatus>, wrapper: bool) -> bool {
+    match exit {
+        None => false,
+        Some(status) => !wrapper || !status.success(),
+    }
 }
 
 /// Return the first launch candidate for a given dashboard, or None.
-fn find_dashboard(name: &str) -> Option<Launch> {
-    let roots = wpilib_roots();
+fn find_dashboard(name: &str, wpilib_year: Option<u32>) -> Option<Launch> {
+    find_dashboard_in(name, &wpilib_roots(wpilib_year))
+}
 
+/// `find_dashboard` over the given WPILib year directories
+fn find_dashboard_in(name: &str, roots: &[std::path::PathBuf]) -> Option<Launch> {
     match name {
         "Shuffleboard" => {
-            for root in &roots {
+            for root in roots {
                 let tools = root.join("tools");
                 // Native binary (WPILib 2026+): tools/Shuffleboard
                 let native = tools.join("Shuffleboard");
@@ -174,13 +554,13 @@ fn find_dashboard(name: &str) -> Option<Launch> {
                 }
             }
             // PATH fallback
-            if command_on_path("shuffleboard") {
-                return Some(Launch::PathCmd("shuffleboard".into()));
+            if let Some(path) = find_on_path("shuffleboard") {
+                return Some(Launch::PathCmd(path));
             }
         }
 
         "Elastic" => {
-            for root in &roots {
+            for root in roots {
                 let elastic_dir = root.join("elastic");
                 // WPILib-bundled .app (macOS): elastic/elastic_dashboard.app or similar
                 if let Some(app) = find_entry(&elastic_dir, |n| {
@@ -220,14 +600,14 @@ fn find_dashboard(name: &str) -> Option<Launch> {
             }
             // PATH fallback
             for cmd in &["elastic", "Elastic", "elastic_dashboard"] {
-                if command_on_path(cmd) {
-                    return Some(Launch::PathCmd(cmd.to_string()));
+                if let Some(path) = find_on_path(cmd) {
+                    return Some(Launch::PathCmd(path));
                 }
             }
         }
 
         "AdvantageScope" => {
-            for root in &roots {
+            for root in roots {
                 let as_dir = root.join("advantagescope");
                 // WPILib-bundled .app (macOS): may have parens/spaces in name
                 if let Some(app) = find_entry(&as_dir, |n| {
@@ -284,8 +664,81 @@ fn find_dashboard(name: &str) -> Option<Launch> {
             }
             // PATH fallback
             for cmd in &["advantagescope", "AdvantageScope"] {
-                if command_on_path(cmd) {
-                    return Some(Launch::PathCmd(cmd.to_string()));
+                if let Some(path) = find_on_path(cmd) {
+                    return Some(Launch::PathCmd(path));
+                }
+            }
+        }
+
+        "Glass" => {
+            for root in roots {
+                let tools = root.join("tools");
+                // macOS bundle: tools/Glass.app
+                if let Some(app) = find_entry(&tools, |n| n.eq_ignore_ascii_case("glass.app")) {
+                    return Some(Launch::MacOpen(app));
+                }
+                // Native binary: tools/Glass (Linux/macOS) or tools/Glass.exe (Windows)
+                for file in ["Glass", "Glass.exe"] {
+                    let native = tools.join(file);
+                    if native.exists() && !native.is_dir() {
+                        return Some(Launch::Direct(native));
+                    }
+                }
+            }
+            // PATH fallback
+            if let Some(path) = find_on_path("glass") {
+                return Some(Launch::PathCmd(path));
+            }
+        }
+
+        "SmartDashboard" => {
+            for root in roots {
+                let tools = root.join("tools");
+                // Native binary (WPILib 2026+): tools/SmartDashboard
+                let native = tools.join("SmartDashboard");
+                if native.exists() && !native.is_dir() {
+                    return Some(Launch::Direct(native));
+                }
+                // Windows batch: tools/SmartDashboard.bat
+                if let Some(bat) = find_entry(&tools, |n| {
+                    n.eq_ignore_ascii_case("smartdashboard.bat")
+                }) {
+                    return Some(Launch::WinBatch(bat));
+                }
+                // .jar
+                if let Some(jar) = find_entry(&tools, |n| {
+                    n.eq_ignore_ascii_case("smartdashboard.jar")
+                }) {
+                    return Some(Launch::JavaJar(jar));
+                }
+            }
+            // PATH fallback
+            if let Some(path) = find_on_path("smartdashboard") {
+                return Some(Launch::PathCmd(path));
+            }
+        }
+
+        // Not bundled with WPILib: standalone installs only
+        "PathPlanner" => {
+            let mac_app = std::path::PathBuf::from("/Applications/PathPlanner.app");
+            if mac_app.exists() {
+                return Some(Launch::MacOpen(mac_app));
+            }
+            // Windows standalone install
+            if let Ok(local) = std::env::var("LOCALAPPDATA") {
+                for sub in &["Programs\\PathPlanner", "PathPlanner"] {
+                    let exe = std::path::PathBuf::from(format!(
+                        "{local}\\{sub}\\PathPlanner.exe"
+                    ));
+                    if exe.exists() {
+                        return Some(Launch::Direct(exe));
+                    }
+                }
+            }
+            // PATH fallback
+            for cmd in &["pathplanner", "PathPlanner"] {
+                if let Some(path) = find_on_path(cmd) {
+                    return Some(Launch::PathCmd(path));
                 }
             }
         }
@@ -296,7 +749,31 @@ fn find_dashboard(name: &str) -> Option<Launch> {
     None
 }
 
-fn do_launch(launch: Launch) -> Result<(), String> {
+/// Launch candidate for a custom dashboard, or None if its file or command
+/// is missing
+fn custom_launch(dashboard: &CustomDashboard) -> Option<Launch> {
+    let path = std::path::PathBuf::from(&dashboard.path);
+    match dashboard.launch_type {
+        CustomLaunchType::PathCmd => {
+            find_on_path(&dashboard.path).map(Launch::PathCmd)
+        }
+        _ if !path.exists() => None,
+        CustomLaunchType::Direct => Some(Launch::Direct(path)),
+        CustomLaunchType::JavaJar => Some(Launch::JavaJar(path)),
+        CustomLaunchType::MacOpen => Some(Launch::MacOpen(path)),
+    }
+}
+
+/// Built-in dashboard by name, then the user's custom ones
+fn resolve_dashboard(state: &AppState, name: &str) -> Option<Launch> {
+    let config = state.config.lock();
+    if ALL_DASHBOARDS.contains(&name) {
+        return find_dashboard(name, config.wpilib_year);
+    }
+    config.find_custom_dashboard(name).and_then(custom_launch)
+}
+
+fn do_launch(launch: Launch) -> Result<Child, String> {
     use std::process::{Command, Stdio};
 
     // Detach child stdout/stderr so dashboard logs don't pollute DS console
@@ -320,22 +797,435 @@ fn do_launch(launch: Launch) -> Result<(), String> {
             .stdout(Stdio::null()).stderr(Stdio::null()).spawn(),
     };
 
-    result.map(|_| ()).map_err(|e| e.to_string())
+    result.map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-pub async fn get_installed_dashboards() -> Vec<String> {
+/// Forget dashboards whose process has exited (e.g. closed by the user)
+fn prune_exited(running: &mut HashMap<String, Child>) {
+    running.retain(|_, child| matches!(child.try_wait(), Ok(None)));
+}
+
+/// Kill a launched dashboard. Returns false if it had already exited.
+fn kill_dashboard(mut child: Child) -> bool {
+    if !matches!(child.try_wait(), Ok(None)) {
+        return false;
+    }
+    let _ = child.kill();
+    // Reap it so no zombie is left behind
+    let _ = child.wait();
+    true
+}
+
+/// Kill every dashboard the DS launched that is still running
+pub fn close_all_dashboards(dashboards: &LaunchedDashboards) {
+    for (name, child) in dashboards.lock().drain() {
+        if kill_dashboard(child) {
+            tracing::info!("Closed dashboard {name}");
+        }
+    }
+}
+
+/// After `DASHBOARD_HEALTH_DELAY`, report `name` as failed if its process
+/// already exited (e.g. crashed on startup) and forget it
+async fn check_dashboard_started(
+    dashboards: LaunchedDashboards,
+    event_tx: tokio::sync::mpsc::Sender<DsEvent>,
+    name: String,
+    wrapper: bool,
+) {
+    tokio::time::sleep(DASHBOARD_HEALTH_DELAY).await;
+    let failed = {
+        let mut running = dashboards.lock();
+        let Some(child) = running.get_mut(&name) else {
+            return;
+        };
+        let exit = child.try_wait().ok().flatten();
+        if exit.is_some() {
+            running.remove(&name);
+        }
+        launch_failed(exit, wrapper)
+    };
+    if failed {
+        tracing::warn!("Dashboard {name} exited right after launch");
+        let _ = event_tx.send(DsEvent::DashboardLaunchFailed { name }).await;
+    }
+}
+
+/// How long a dashboard scan is reused before the disk is searched again
+const DASHBOARD_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Last result of `get_installed_dashboards`, so repeated UI calls don't
+/// rescan the disk
+#[derive(Debug, Default)]
+pub struct DashboardCache {
+    scanned: Option<(std::time::Instant, Vec<String>)>,
+}
+
+impl DashboardCache {
+    /// The cached list, if still fresh at `now`
+    fn get(&self, now: std::time::Instant) -> Option<Vec<String>> {
+        self.scanned
+            .as_ref()
+            .filter(|(at, _)| now.duration_since(*at) < DASHBOARD_CACHE_TTL)
+            .map(|(_, names)| names.clone())
+    }
+
+    fn store(&mut self, now: std::time::Instant, names: Vec<String>) {
+        self.scanned = Some((now, names));
+    }
+
+    /// Force the next call to rescan, e.g. after the dashboard settings change
+    pub fn invalidate(&mut self) {
+        self.scanned = None;
+    }
+}
+
+/// Built-in dashboards found on this machine, then custom dashboards whose
+/// file or command exists. Blocking: searches the filesystem.
+fn scan_installed_dashboards(
+    custom: &[CustomDashboard],
+    wpilib_year: Option<u32>,
+) -> Vec<String> {
     ALL_DASHBOARDS
         .iter()
-        .filter(|name| find_dashboard(name).is_some())
+        .filter(|name| find_dashboard(name, wpilib_year).is_some())
         .map(|s| s.to_string())
+        .chain(
+            custom
+                .iter()
+                .filter(|d| custom_launch(d).is_some())
+                .map(|d| d.name.clone()),
+        )
         .collect()
 }
 
+/// Scan for dashboards off the command thread and cache the result
+async fn rescan_dashboards(state: &AppState) -> Result<Vec<String>, String> {
+    let (custom, year) = {
+        let config = state.config.lock();
+        (config.custom_dashboards.clone(), config.wpilib_year)
+    };
+    let names = tauri::async_runtime::spawn_blocking(move || {
+        scan_installed_dashboards(&custom, year)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    state.installed_dashboards.lock().store(std::time::Instant::now(), names.clone());
+    Ok(names)
+}
+
+/// Installed dashboards (built-in, then custom). Cached for a few seconds;
+/// see `refresh_dashboards` to force a rescan.
+#[tauri::command]
+pub async fn get_installed_dashboards(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let cached = state.installed_dashboards.lock().get(std::time::Instant::now());
+    match cached {
+        Some(names) => Ok(names),
+        None => rescan_dashboards(&state).await,
+    }
+}
+
+/// Rescan for dashboards now, e.g. after installing one
 #[tauri::command]
-pub async fn launch_dashboard(name: String) -> Result<(), String> {
-    match find_dashboard(&name) {
-        Some(launch) => do_launch(launch),
-        None => Err(format!("{name} is not installed")),
+pub async fn refresh_dashboards(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    rescan_dashboards(&state).await
+}
+
+/// Save a custom dashboard. `launch_type` is `direct`, `javajar`, `macopen`
+/// or `pathcmd`; the path (or command) must exist.
+#[tauri::command]
+pub fn add_custom_dashboard(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    path: String,
+    launch_type: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Dashboard name cannot be empty".into());
+    }
+    if ALL_DASHBOARDS.contains(&name.as_str()) {
+        return Err(format!("{name} is a built-in dashboard"));
+    }
+    let dashboard = CustomDashboard {
+        name,
+        path: path.trim().to_string(),
+        launch_type: CustomLaunchType::parse(&launch_type)?,
+    };
+    if custom_launch(&dashboard).is_none() {
+        return Err(format!("{} not found", dashboard.path));
+    }
+    save_setting(&app, &state, |cfg| cfg.upsert_custom_dashboard(dashboard));
+    state.installed_dashboards.lock().invalidate();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_custom_dashboard(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    save_setting(&app, &state, |cfg| cfg.custom_dashboards.retain(|d| d.name != name));
+    state.installed_dashboards.lock().invalidate();
+    Ok(())
+}
+
+/// Launch a dashboard. A dashboard the DS already started and that is still
+/// running is reused, not launched again.
+#[tauri::command]
+pub async fn launch_dashboard(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let Some(launch) = resolve_dashboard(&state, &name) else {
+        return Err(format!("{name} is not installed"));
+    };
+    let wrapper = launch.is_wrapper();
+    let launched = {
+        // Held across the spawn so two launches can't both start a process
+        let mut running = state.dashboards.lock();
+        prune_exited(&mut running);
+        if running.contains_key(&name) {
+            tracing::info!("Dashboard {name} is already running");
+            false
+        } else {
+            running.insert(name.clone(), do_launch(launch)?);
+            true
+        }
+    };
+    if launched {
+        tauri::async_runtime::spawn(check_dashboard_started(
+            state.dashboards.clone(),
+            state.event_tx.clone(),
+            name.clone(),
+            wrapper,
+        ));
+    }
+    Ok(())
+}
+
+/// Stop a dashboard started with `launch_dashboard`. Succeeds quietly if it
+/// was already closed by hand.
+#[tauri::command]
+pub fn close_dashboard(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let Some(child) = state.dashboards.lock().remove(&name) else {
+        return Err(format!("{name} was not launched by the driver station"));
+    };
+    if kill_dashboard(child) {
+        tracing::info!("Closed dashboard {name}");
+    }
+    Ok(())
+}
+
+/// Dashboards launched by the DS whose process is still running. Dashboards
+/// started through a launcher that exits (`open` on macOS, batch files) are
+/// not tracked past launch.
+#[tauri::command]
+pub fn get_running_dashboards(state: State<'_, AppState>) -> Vec<String> {
+    let mut running = state.dashboards.lock();
+    prune_exited(&mut running);
+    let mut names: Vec<String> = running.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Kill launched dashboards when the DS exits (off by default)
+#[tauri::command]
+pub fn set_close_dashboards_on_exit(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    save_setting(&app, &state, |cfg| cfg.close_dashboards_on_exit = enabled);
+    Ok(())
+}
+
+/// WPILib years installed on this machine, newest first
+#[tauri::command]
+pub fn get_wpilib_years() -> Vec<u32> {
+    let mut years: Vec<u32> = wpilib_bases().iter().flat_map(|b| wpilib_years(b)).collect();
+    years.sort_unstable_by(|a, b| b.cmp(a));
+    years.dedup();
+    years
+}
+
+/// Use dashboards from a specific WPILib year, e.g. mid-migration between
+/// seasons. `null` (or a year that isn't installed) uses the latest.
+#[tauri::command]
+pub fn set_wpilib_year(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    year: Option<u32>,
+) -> Result<(), String> {
+    save_setting(&app, &state, |cfg| cfg.wpilib_year = year);
+    state.installed_dashboards.lock().invalidate();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dashboard_exiting_on_startup_reports_failure() {
+        use std::process::Command;
+
+        let dashboards = LaunchedDashboards::default();
+        let mut crashed = Command::new("false").spawn().unwrap();
+        crashed.wait().unwrap();
+        let running = Command::new("sleep").arg("5").spawn().unwrap();
+        dashboards.lock().insert("Crashed".into(), crashed);
+        dashboards.lock().insert("Running".into(), running);
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(4);
+        tokio::join!(
+            check_dashboard_started(dashboards.clone(), event_tx.clone(), "Crashed".into(), false),
+            check_dashboard_started(dashboards.clone(), event_tx, "Running".into(), false),
+        );
+
+        let event = event_rx.try_recv().unwrap();
+        assert!(matches!(event, DsEvent::DashboardLaunchFailed { ref name } if name == "Crashed"));
+        assert!(event_rx.try_recv().is_err());
+        assert_eq!(dashboards.lock().keys().collect::<Vec<_>>(), ["Running"]);
+        close_all_dashboards(&dashboards);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wrapper_launchers_fail_only_on_a_nonzero_exit() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let ok = std::process::ExitStatus::from_raw(0);
+        let err = std::process::ExitStatus::from_raw(256);
+        assert!(!launch_failed(None, false));
+        assert!(launch_failed(Some(ok), false));
+        assert!(!launch_failed(Some(ok), true));
+        assert!(launch_failed(Some(err), true));
+    }
+
+    /// Empty scratch directory unique to this test process
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ds-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn launch_path(launch: Option<Launch>) -> Option<std::path::PathBuf> {
+        match launch? {
+            Launch::Direct(p) | Launch::JavaJar(p) | Launch::MacOpen(p) => Some(p),
+            Launch::WinBatch(p) | Launch::PathCmd(p) => Some(p),
+        }
+    }
+
+    #[test]
+    fn dashboards_are_found_in_the_tools_directory() {
+        let root = scratch_dir("tools");
+        let tools = root.join("tools");
+        std::fs::create_dir_all(tools.join("Glass.app")).unwrap();
+        std::fs::write(tools.join("SmartDashboard.jar"), b"").unwrap();
+        std::fs::write(tools.join("AdvantageScope"), b"").unwrap();
+        let roots = [root.clone()];
+
+        assert!(matches!(find_dashboard_in("Glass", &roots), Some(Launch::MacOpen(_))));
+        assert!(matches!(find_dashboard_in("SmartDashboard", &roots), Some(Launch::JavaJar(_))));
+        let found = |name| launch_path(find_dashboard_in(name, &roots));
+        assert_eq!(found("Glass"), Some(tools.join("Glass.app")));
+        assert_eq!(found("SmartDashboard"), Some(tools.join("SmartDashboard.jar")));
+        assert_eq!(found("AdvantageScope"), Some(tools.join("AdvantageScope")));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn custom_direct_dashboard_round_trips_through_config() {
+        let dir = scratch_dir("custom-dashboard");
+        let exe = dir.join("team-dash.sh");
+        std::fs::write(&exe, b"#!/bin/sh\n").unwrap();
+        let dashboard = CustomDashboard {
+            name: "Team Dash".into(),
+            path: exe.to_string_lossy().into_owned(),
+            launch_type: CustomLaunchType::Direct,
+        };
+        let mut saved = DsConfig::default();
+        saved.upsert_custom_dashboard(dashboard.clone());
+        let path = config::config_path(&dir);
+        config::save_config(&path, &saved).unwrap();
+        let loaded = config::load_config(&path);
+
+        assert_eq!(loaded.find_custom_dashboard("Team Dash"), Some(&dashboard));
+        let launch = loaded.find_custom_dashboard("Team Dash").and_then(custom_launch);
+        assert_eq!(launch_path(launch), Some(exe.clone()));
+        let names = scan_installed_dashboards(&loaded.custom_dashboards, None);
+        assert!(names.contains(&"Team Dash".to_string()));
+
+        // A dashboard whose file was removed is neither launched nor listed
+        std::fs::remove_file(&exe).unwrap();
+        assert!(custom_launch(&dashboard).is_none());
+        let names = scan_installed_dashboards(&loaded.custom_dashboards, None);
+        assert!(!names.contains(&dashboard.name));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn chosen_wpilib_year_wins_over_the_latest() {
+        let base = scratch_dir("wpilib-years");
+        for dir in ["2024", "2026", "2025", "tools", "2025-beta"] {
+            std::fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        // A plain file named like a year is not an install
+        std::fs::write(base.join("2027"), b"").unwrap();
+
+        assert_eq!(wpilib_years(&base), [2026, 2025, 2024]);
+        assert_eq!(wpilib_year_dir(&base, None), Some(base.join("2026")));
+        assert_eq!(wpilib_year_dir(&base, Some(2024)), Some(base.join("2024")));
+        // A year that isn't installed falls back to the latest
+        assert_eq!(wpilib_year_dir(&base, Some(2023)), Some(base.join("2026")));
+        assert_eq!(wpilib_year_dir(&base.join("missing"), None), None);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_scan_finds_the_first_executable_match() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = scratch_dir("path-scan");
+        let (first, second) = (root.join("a"), root.join("b"));
+        let make = |dir: &std::path::Path, file: &str, mode: u32| {
+            std::fs::create_dir_all(dir).unwrap();
+            let path = dir.join(file);
+            std::fs::write(&path, b"").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        make(&first, "elastic", 0o644);
+        make(&second, "elastic", 0o755);
+        make(&second, "glass.cmd", 0o755);
+        let path_var = std::env::join_paths([first.as_path(), second.as_path()]).unwrap();
+
+        // The non-executable file earlier on PATH is skipped
+        assert_eq!(find_in_path_var("elastic", &path_var, None), Some(second.join("elastic")));
+        assert_eq!(find_in_path_var("glass", &path_var, None), None);
+        assert_eq!(
+            find_in_path_var("glass", &path_var, Some(".exe;.cmd")),
+            Some(second.join("glass.cmd"))
+        );
+        assert_eq!(find_in_path_var("shuffleboard", &path_var, Some(".exe;.cmd")), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn dashboard_cache_is_reused_until_it_expires_or_is_invalidated() {
+        let start = std::time::Instant::now();
+        let mut cache = DashboardCache::default();
+        assert_eq!(cache.get(start), None);
+
+        cache.store(start, vec!["Elastic".into()]);
+        let hit = start + DASHBOARD_CACHE_TTL - std::time::Duration::from_millis(1);
+        assert_eq!(cache.get(hit), Some(vec!["Elastic".to_string()]));
+        assert_eq!(cache.get(start + DASHBOARD_CACHE_TTL), None);
+
+        cache.store(start, vec!["Elastic".into()]);
+        cache.invalidate();
+        assert_eq!(cache.get(start), None);
     }
 }
@@ -1,6 +1,14 @@
-use tauri::State;
+use std::sync::atomic::Ordering;
 
-use crate::protocol::connection::GamepadUpdate;
+use tauri::{AppHandle, Manager, State};
+
+use super::config::save_setting;
+use crate::config::DriverProfile;
+use crate::gamepad::input_map;
+use crate::gamepad::manager::{AxisCalibrations, MAX_UI_RATE_HZ};
+use crate::gamepad::mapping::{AxisCalibration, TriggerMode, TriggerModes, DEFAULT_SLEW_LIMIT};
+use crate::protocol::connection::{DsCommand, DsEvent, GamepadUpdate, MAX_BUTTON_COUNT};
+use crate::protocol::types::{JoystickLayout, MAX_JOYSTICK_AXES, MAX_JOYSTICK_POVS};
 use crate::AppState;
 
 #[tauri::command]
@@ -9,6 +17,26 @@ pub fn get_gamepads(state: State<'_, AppState>) -> Result<GamepadUpdate, String>
     Ok(mgr.get_gamepad_update())
 }
 
+/// False when the input subsystem failed to start; only virtual joysticks
+/// are available then
+#[tauri::command]
+pub fn gamepad_available(state: State<'_, AppState>) -> bool {
+    state.gamepad_manager.lock().gamepad_available()
+}
+
+/// Re-check which gamepads are connected, for when one is stuck absent
+/// (e.g. after sleep/resume), and push the result to the UI
+#[tauri::command]
+pub async fn rescan_gamepads(state: State<'_, AppState>) -> Result<GamepadUpdate, String> {
+    let update = state.gamepad_manager.lock().rescan();
+    state
+        .event_tx
+        .send(DsEvent::GamepadUpdate(update.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(update)
+}
+
 #[tauri::command]
 pub fn reorder_gamepads(
     state: State<'_, AppState>,
@@ -33,3 +61,305 @@ pub fn unlock_gamepad_slot(state: State<'_, AppState>, slot: usize) -> Result<()
     mgr.unlock_slot(slot);
     Ok(())
 }
+
+#[tauri::command]
+pub fn set_gamepad_changes_only(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state.gamepad_changes_only.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Rate of the periodic gamepad updates behind the live input display
+#[tauri::command]
+pub fn set_gamepad_ui_rate(state: State<'_, AppState>, hz: u32) -> Result<(), String> {
+    if !(1..=MAX_UI_RATE_HZ).contains(&hz) {
+        return Err(format!("Rate must be 1-{MAX_UI_RATE_HZ} Hz"));
+    }
+    state.gamepad_ui_interval_ms.store(1000 / hz as u64, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_min_button_count(state: State<'_, AppState>, count: u8) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetMinButtonCount(count))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Send physical button `from_index` of the controller in `slot` to output
+/// button `to_index`; the button previously on `to_index` takes `from_index`'s
+/// old output. Remaps follow the device by name.
+#[tauri::command]
+pub fn remap_button(
+    state: State<'_, AppState>,
+    slot: usize,
+    from_index: usize,
+    to_index: usize,
+) -> Result<(), String> {
+    let max = MAX_BUTTON_COUNT as usize;
+    if from_index >= max || to_index >= max {
+        return Err(format!("Button index must be below {max}"));
+    }
+    let mut mgr = state.gamepad_manager.lock();
+    if mgr.remap_button(slot, from_index, to_index) {
+        Ok(())
+    } else {
+        Err(format!("No gamepad in slot {slot}"))
+    }
+}
+
+#[tauri::command]
+pub fn clear_button_remap(state: State<'_, AppState>, slot: usize) -> Result<(), String> {
+    let mut mgr = state.gamepad_manager.lock();
+    mgr.clear_button_remap(slot);
+    Ok(())
+}
+
+/// Force the axis/button/POV counts robot code sees for `slot` (WPILib
+/// limits: 12 axes, 32 buttons, 4 POVs)
+#[tauri::command]
+pub fn set_joystick_layout(
+    state: State<'_, AppState>,
+    slot: usize,
+    axes: u8,
+    buttons: u8,
+    povs: u8,
+) -> Result<(), String> {
+    if slot >= 6 {
+        return Err(format!("Invalid joystick slot {slot}"));
+    }
+    if axes > MAX_JOYSTICK_AXES || buttons > MAX_BUTTON_COUNT || povs > MAX_JOYSTICK_POVS {
+        return Err(format!(
+            "Layout exceeds WPILib limits ({MAX_JOYSTICK_AXES} axes, {MAX_BUTTON_COUNT} buttons, {MAX_JOYSTICK_POVS} POVs)"
+        ));
+    }
+    let mut mgr = state.gamepad_manager.lock();
+    mgr.set_layout(slot, JoystickLayout { axes, buttons, povs });
+    Ok(())
+}
+
+/// Developer tool: time how long input published by the gamepad thread takes
+/// to appear in an outbound packet. Returns milliseconds.
+#[tauri::command]
+pub async fn measure_input_latency(state: State<'_, AppState>) -> Result<f64, String> {
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    state.latency_probe.lock().arm(reply_tx);
+    let latency = tokio::time::timeout(std::time::Duration::from_secs(1), reply_rx)
+        .await
+        .map_err(|_| "Timed out waiting for the next outbound packet".to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(latency.as_secs_f64() * 1000.0)
+}
+
+#[tauri::command]
+pub fn get_trigger_modes(state: State<'_, AppState>) -> Result<TriggerModes, String> {
+    Ok(state.gamepad_manager.lock().trigger_modes())
+}
+
+/// Choose whether analog triggers reach robot code as axes, buttons or both,
+/// for `device_type` `"xbox"` or `"generic"`. Xbox controllers default to
+/// axes only, matching WPILib's `XboxController`.
+#[tauri::command]
+pub fn set_trigger_mode(
+    state: State<'_, AppState>,
+    device_type: String,
+    mode: String,
+) -> Result<(), String> {
+    let is_xbox = match device_type.to_ascii_lowercase().as_str() {
+        "xbox" => true,
+        "generic" => false,
+        _ => return Err(format!("Unknown device type: {device_type}")),
+    };
+    let mode = TriggerMode::parse(&mode)?;
+    state.gamepad_manager.lock().set_trigger_mode(is_xbox, mode);
+    Ok(())
+}
+
+/// Add a keyboard-driven virtual gamepad, in `slot` or the first free one.
+/// Returns the slot it occupies.
+#[tauri::command]
+pub fn create_virtual_joystick(
+    state: State<'_, AppState>,
+    slot: Option<usize>,
+) -> Result<usize, String> {
+    state.gamepad_manager.lock().create_virtual(slot)
+}
+
+#[tauri::command]
+pub fn destroy_virtual_joystick(state: State<'_, AppState>, slot: usize) -> Result<(), String> {
+    if state.gamepad_manager.lock().destroy_virtual(slot) {
+        Ok(())
+    } else {
+        Err(format!("No virtual gamepad in slot {slot}"))
+    }
+}
+
+#[tauri::command]
+pub fn set_virtual_axis(
+    state: State<'_, AppState>,
+    slot: usize,
+    axis: usize,
+    value: f32,
+) -> Result<(), String> {
+    if axis >= MAX_JOYSTICK_AXES as usize {
+        return Err(format!("Axis index must be below {MAX_JOYSTICK_AXES}"));
+    }
+    state.gamepad_manager.lock().set_virtual_axis(slot, axis, value)
+}
+
+#[tauri::command]
+pub fn set_virtual_button(
+    state: State<'_, AppState>,
+    slot: usize,
+    button: usize,
+    pressed: bool,
+) -> Result<(), String> {
+    if button >= MAX_BUTTON_COUNT as usize {
+        return Err(format!("Button index must be below {MAX_BUTTON_COUNT}"));
+    }
+    state.gamepad_manager.lock().set_virtual_button(slot, button, pressed)
+}
+
+/// Limit how much any axis of the controller in `slot` may change per 20ms
+/// tick, so a slammed stick ramps instead of stepping. Omit `max_delta` to
+/// remove the limit. Limits follow the device by name.
+#[tauri::command]
+pub fn set_slew_limit(
+    state: State<'_, AppState>,
+    slot: usize,
+    max_delta: Option<f32>,
+) -> Result<(), String> {
+    if let Some(limit) = max_delta {
+        if !(limit > 0.0 && limit <= DEFAULT_SLEW_LIMIT) {
+            return Err(format!("Slew limit must be above 0 and at most {DEFAULT_SLEW_LIMIT}"));
+        }
+    }
+    let mut mgr = state.gamepad_manager.lock();
+    if mgr.set_slew_limit(slot, max_delta) {
+        Ok(())
+    } else {
+        Err(format!("No gamepad in slot {slot}"))
+    }
+}
+
+/// Calibrate one axis of a worn controller from its raw readings at full
+/// deflection (`min`, `max`) and at rest (`center`), so it maps to a clean
+/// -1..1 centered on 0. Calibrations follow the device by name.
+#[tauri::command]
+pub fn set_axis_calibration(
+    state: State<'_, AppState>,
+    slot: usize,
+    axis: usize,
+    min: f32,
+    center: f32,
+    max: f32,
+) -> Result<(), String> {
+    let calibration = AxisCalibration::new(min, center, max)?;
+    if state.gamepad_manager.lock().set_axis_calibration(slot, axis, Some(calibration)) {
+        Ok(())
+    } else {
+        Err(format!("No gamepad in slot {slot}"))
+    }
+}
+
+#[tauri::command]
+pub fn clear_axis_calibration(
+    state: State<'_, AppState>,
+    slot: usize,
+    axis: usize,
+) -> Result<(), String> {
+    if state.gamepad_manager.lock().set_axis_calibration(slot, axis, None) {
+        Ok(())
+    } else {
+        Err(format!("No gamepad in slot {slot}"))
+    }
+}
+
+/// Auto-calibrate the device in `slot`: the driver moves every stick through
+/// its full range and lets go within `seconds` (default 5). Axes that moved
+/// are calibrated from the extremes seen, with the final reading as center.
+#[tauri::command]
+pub async fn capture_axis_calibration(
+    state: State<'_, AppState>,
+    slot: usize,
+    seconds: Option<f32>,
+) -> Result<AxisCalibrations, String> {
+    let seconds = seconds.unwrap_or(5.0).clamp(1.0, 30.0);
+    state.gamepad_manager.lock().start_calibration_capture(slot)?;
+    tokio::time::sleep(std::time::Duration::from_secs_f32(seconds)).await;
+    state.gamepad_manager.lock().finish_calibration_capture()
+}
+
+/// Re-read `input_mappings.json` from the app data directory and apply it.
+/// Returns the number of devices it maps. On error the current table stays.
+#[tauri::command]
+pub fn reload_mappings(app: AppHandle, state: State<'_, AppState>) -> Result<usize, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mappings = input_map::load_mappings(&input_map::mappings_path(&dir))?;
+    let count = mappings.device_count();
+    state.gamepad_manager.lock().set_input_mappings(mappings);
+    Ok(count)
+}
+
+/// Import a mapping line from SDL_GameControllerDB (as used by Steam Input
+/// and other DS software) into the mapping table and save it to
+/// `input_mappings.json`
+#[tauri::command]
+pub fn import_sdl_mapping(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    mapping: String,
+) -> Result<input_map::SdlImport, String> {
+    let sdl = input_map::parse_sdl_mapping(&mapping)?;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut mgr = state.gamepad_manager.lock();
+    let imported = mgr.import_sdl_mapping(sdl);
+    input_map::save_mappings(&input_map::mappings_path(&dir), mgr.input_mappings())?;
+    Ok(imported)
+}
+
+/// Save the current remaps, layouts, slot locks, slew limits, trigger modes
+/// and axis calibrations as a named driver profile, replacing one with the
+/// same name
+#[tauri::command]
+pub fn save_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".into());
+    }
+    let gamepad = state.gamepad_manager.lock().settings();
+    save_setting(&app, &state, |cfg| cfg.upsert_driver_profile(DriverProfile { name, gamepad }));
+    Ok(())
+}
+
+/// Apply a saved driver profile. Locked devices that are connected move to
+/// their slots straight away.
+#[tauri::command]
+pub fn load_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let profile = state
+        .config
+        .lock()
+        .find_driver_profile(&name)
+        .cloned()
+        .ok_or_else(|| format!("No driver profile named {name}"))?;
+    state.gamepad_manager.lock().apply_settings(profile.gamepad);
+    tracing::info!("Loaded driver profile '{name}'");
+    Ok(())
+}
+
+/// Names of the saved driver profiles
+#[tauri::command]
+pub fn list_profiles(state: State<'_, AppState>) -> Vec<String> {
+    state
+        .config
+        .lock()
+        .driver_profiles
+        .iter()
+        .map(|p| p.name.clone())
+        .collect()
+}
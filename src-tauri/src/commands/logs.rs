@@ -0,0 +1,252 @@
+use std::sync::atomic::Ordering;
+
+use tauri::{AppHandle, State};
+use tokio::sync::oneshot;
+
+use super::config::save_setting;
+use crate::config::SavedConsoleFilter;
+use crate::log_writer::{self, LogControl};
+use crate::logging::{ConsoleLevel, ConsolePattern, SeverityPatterns};
+use crate::protocol::connection::{DsCommand, DsEvent};
+use crate::protocol::types::ConsoleMessage;
+use crate::replay;
+use crate::AppState;
+
+/// Close the current log file, start a new one, and tell the frontend to
+/// clear its console view. Returns the new log file path.
+#[tauri::command]
+pub async fn new_log_session(state: State<'_, AppState>) -> Result<String, String> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    state
+        .log_control_tx
+        .send(LogControl::NewSession(reply_tx))
+        .await
+        .map_err(|e| e.to_string())?;
+    let path = reply_rx
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Failed to open a new log file")?;
+    let _ = state.event_tx.send(DsEvent::ConsoleCleared).await;
+    Ok(path.display().to_string())
+}
+
+/// Export the current session's console log to `path` as `"csv"` or `"txt"`.
+/// Returns the number of messages exported.
+#[tauri::command]
+pub async fn export_console_log(
+    state: State<'_, AppState>,
+    path: String,
+    format: String,
+) -> Result<usize, String> {
+    let files = state.log_session.read().files.clone();
+    if files.is_empty() {
+        return Err("No console log has been written this session".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        log_writer::export_log(&files, std::path::Path::new(&path), &format)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Read a saved `ds-*.log` file back into console messages for display
+#[tauri::command]
+pub async fn load_log_file(path: String) -> Result<Vec<ConsoleMessage>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        log_writer::load_log_file(std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Search this session's console log for `query` (substring, or a regular
+/// expression with `regex`). Returns up to `MAX_SEARCH_MATCHES` lines.
+#[tauri::command]
+pub async fn search_log(
+    state: State<'_, AppState>,
+    query: String,
+    case_sensitive: bool,
+    regex: bool,
+) -> Result<log_writer::LogSearchResult, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".into());
+    }
+    let files = state.log_session.read().files.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        log_writer::search_log(&files, &query, case_sensitive, regex, log_writer::MAX_SEARCH_MATCHES)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Toggle collapsing of repeated console messages into "message (xN)"
+#[tauri::command]
+pub fn set_console_coalesce(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state.console_coalesce.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Show only console messages at or above `level` (`"none"`, `"warning"` or
+/// `"error"`). Filtered messages still reach the log file unless
+/// `apply_to_file` is set. Takes effect without reconnecting.
+#[tauri::command]
+pub fn set_console_level(
+    state: State<'_, AppState>,
+    level: String,
+    apply_to_file: Option<bool>,
+) -> Result<(), String> {
+    let level = ConsoleLevel::parse(&level)?;
+    let mut filter = state.console_filter.write();
+    filter.level = level;
+    filter.apply_to_file = apply_to_file.unwrap_or(false);
+    Ok(())
+}
+
+/// Save a named console filter (e.g. "Drivetrain"), matching `pattern` as a
+/// case-insensitive substring or, with `regex`, a regular expression
+#[tauri::command]
+pub fn save_console_filter(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    pattern: String,
+    regex: Option<bool>,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Filter name cannot be empty".into());
+    }
+    let regex = regex.unwrap_or(false);
+    // Reject bad patterns now rather than when the filter is applied
+    ConsolePattern::new(&name, &pattern, regex)?;
+    save_setting(&app, &state, |cfg| {
+        cfg.upsert_console_filter(SavedConsoleFilter { name, pattern, regex })
+    });
+    Ok(())
+}
+
+/// Delete a saved console filter, turning it off if it is active
+#[tauri::command]
+pub fn delete_console_filter(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    save_setting(&app, &state, |cfg| cfg.console_filters.retain(|f| f.name != name));
+    let mut filter = state.console_filter.write();
+    if filter.pattern.as_ref().is_some_and(|p| p.name == name) {
+        filter.pattern = None;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_console_filters(state: State<'_, AppState>) -> Vec<SavedConsoleFilter> {
+    state.config.lock().console_filters.clone()
+}
+
+/// Show only console messages matching the named saved filter (and the
+/// severity level). None shows everything again.
+#[tauri::command]
+pub fn apply_console_filter(
+    state: State<'_, AppState>,
+    name: Option<String>,
+) -> Result<(), String> {
+    let pattern = match name {
+        Some(name) => {
+            let saved = state
+                .config
+                .lock()
+                .find_console_filter(&name)
+                .cloned()
+                .ok_or_else(|| format!("No console filter named {name}"))?;
+            Some(ConsolePattern::new(&saved.name, &saved.pattern, saved.regex)?)
+        }
+        None => None,
+    };
+    state.console_filter.write().pattern = pattern;
+    Ok(())
+}
+
+/// Replace the standard output prefixes that mark a line as an error or
+/// warning (e.g. `"[ERROR]"`). Pass empty lists to turn promotion off.
+#[tauri::command]
+pub fn set_severity_patterns(
+    state: State<'_, AppState>,
+    error: Vec<String>,
+    warning: Vec<String>,
+) -> Result<(), String> {
+    *state.severity_patterns.write() = SeverityPatterns { error, warning };
+    Ok(())
+}
+
+/// Replay a recorded `session-*.ndjson` file to the frontend at its original
+/// timing. The robot is disabled and live events are held back until the
+/// replay ends or `stop_session_replay` is called. Returns the event count.
+#[tauri::command]
+pub async fn start_session_replay(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<usize, String> {
+    if state.replaying.load(Ordering::Relaxed) {
+        return Err("A session replay is already running".into());
+    }
+
+    let records = tauri::async_runtime::spawn_blocking(move || {
+        let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+        replay::parse_recording(std::io::BufReader::new(file))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if records.is_empty() {
+        return Err("Recording contains no events".into());
+    }
+
+    state
+        .cmd_tx
+        .send(DsCommand::Disable)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let count = records.len();
+    let (stop_tx, stop_rx) = oneshot::channel();
+    *state.replay_stop_tx.lock() = Some(stop_tx);
+    state.replaying.store(true, Ordering::Relaxed);
+    tauri::async_runtime::spawn(replay::replay_session(
+        app,
+        records,
+        stop_rx,
+        state.replaying.clone(),
+    ));
+    Ok(count)
+}
+
+/// Toggle recording frontend events to `logs/session-*.ndjson` for replay.
+/// Off by default; the newest recordings are kept.
+#[tauri::command]
+pub fn set_session_recording(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state.session_recording.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Stop the running session replay and return to live events
+#[tauri::command]
+pub fn stop_session_replay(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(stop_tx) = state.replay_stop_tx.lock().take() {
+        let _ = stop_tx.send(());
+    }
+    Ok(())
+}
+
+/// Toggle writing the DS's own control actions (enable/disable, mode,
+/// E-Stop, reboot/restart requests) to the log file as `[DS]` lines. On by
+/// default.
+#[tauri::command]
+pub async fn set_control_logging(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetControlLogging(enabled))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,3 +1,5 @@
 pub mod config;
 pub mod gamepad;
+pub mod logs;
 pub mod robot;
+pub mod system;
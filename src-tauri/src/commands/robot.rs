@@ -1,29 +1,71 @@
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
-use crate::protocol::connection::DsCommand;
-use crate::protocol::types::Mode;
+use crate::config::TargetAddress;
+use crate::discovery::DiscoveredRoborio;
+use crate::protocol::battery::BatteryThresholds;
+use crate::protocol::connection::{DsCommand, OutboundPacket};
+use crate::protocol::metrics::render_metrics;
+use crate::protocol::timeline::TimelineEntry;
+use crate::protocol::types::{DiagnosticData, Mode, RobotState, SocketStatus};
 use crate::AppState;
 
+/// Address a control command to `robot_id` (`"primary"` or a monitored
+/// robot's id); None leaves it for the active robot
+fn for_robot(robot_id: Option<String>, command: DsCommand) -> DsCommand {
+    match robot_id {
+        Some(robot_id) => DsCommand::ForRobot { robot_id, command: Box::new(command) },
+        None => command,
+    }
+}
+
+#[tauri::command]
+pub async fn enable_robot(
+    state: State<'_, AppState>,
+    robot_id: Option<String>,
+) -> Result<(), String> {
+    if state.replaying.load(std::sync::atomic::Ordering::Relaxed) {
+        return Err("Cannot enable during a session replay".into());
+    }
+    state
+        .cmd_tx
+        .send(for_robot(robot_id, DsCommand::Enable))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-pub async fn enable_robot(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn disable_robot(
+    state: State<'_, AppState>,
+    robot_id: Option<String>,
+) -> Result<(), String> {
     state
         .cmd_tx
-        .send(DsCommand::Enable)
+        .send(for_robot(robot_id, DsCommand::Disable))
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn disable_robot(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn estop_robot(
+    state: State<'_, AppState>,
+    robot_id: Option<String>,
+) -> Result<(), String> {
     state
         .cmd_tx
-        .send(DsCommand::Disable)
+        .send(for_robot(robot_id, DsCommand::EStop))
         .await
         .map_err(|e| e.to_string())
 }
 
+/// E-Stop from the keyboard shortcut (Space) while the window has focus.
+///
+/// Key repeats need no debouncing here: E-Stop latches, so repeated commands
+/// leave the state unchanged and only the first is recorded. Shortcuts that
+/// work while the window is unfocused would need an OS-level hook (e.g.
+/// `tauri-plugin-global-shortcut`) calling this same command.
 #[tauri::command]
-pub async fn estop_robot(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn key_estop(state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!("E-Stop from keyboard");
     state
         .cmd_tx
         .send(DsCommand::EStop)
@@ -31,8 +73,86 @@ pub async fn estop_robot(state: State<'_, AppState>) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+/// Liveness ping from the frontend; feeds the enable watchdog
+#[tauri::command]
+pub async fn heartbeat(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::Heartbeat)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set how long the robot may stay enabled without a frontend command
+#[tauri::command]
+pub async fn set_watchdog_timeout(state: State<'_, AppState>, ms: u64) -> Result<(), String> {
+    let ms = ms.clamp(250, 10_000);
+    state
+        .cmd_tx
+        .send(DsCommand::SetWatchdogTimeout(std::time::Duration::from_millis(ms)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Toggle disabling the robot when the main window loses focus. Off by
+/// default; regaining focus never re-enables.
+#[tauri::command]
+pub fn set_disable_on_blur(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .disable_on_blur
+        .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+/// Toggle rate-limiting of high-rate frontend events: `robot-state` and
+/// `diagnostics` at most 10Hz, `packet-capture` at most 20Hz. On by default;
+/// the latest update is always delivered.
+#[tauri::command]
+pub fn set_event_throttling(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .event_throttling
+        .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+/// Set how long the robot may go without replying before it is marked
+/// disconnected (default 3s). Raise it on high-latency links that flap. The
+/// value in use is reported by `get_socket_status`.
+#[tauri::command]
+pub async fn set_disconnect_timeout(state: State<'_, AppState>, ms: u64) -> Result<(), String> {
+    let ms = ms.clamp(200, 5_000);
+    state
+        .cmd_tx
+        .send(DsCommand::SetDisconnectTimeout(std::time::Duration::from_millis(ms)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set the low-battery warning and critical voltages (defaults 7.5V / 6.8V)
+#[tauri::command]
+pub async fn set_battery_thresholds(
+    state: State<'_, AppState>,
+    warn: f32,
+    critical: f32,
+) -> Result<(), String> {
+    if !(critical > 0.0 && critical < warn && warn <= 15.0) {
+        return Err(format!(
+            "Invalid thresholds: need 0 < critical ({critical}V) < warn ({warn}V) <= 15V"
+        ));
+    }
+    state
+        .cmd_tx
+        .send(DsCommand::SetBatteryThresholds(BatteryThresholds { warn, critical }))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-pub async fn set_mode(state: State<'_, AppState>, mode: String) -> Result<(), String> {
+pub async fn set_mode(
+    state: State<'_, AppState>,
+    mode: String,
+    robot_id: Option<String>,
+) -> Result<(), String> {
     let m = match mode.as_str() {
         "Teleoperated" => Mode::Teleoperated,
         "Autonomous" => Mode::Autonomous,
@@ -41,25 +161,212 @@ pub async fn set_mode(state: State<'_, AppState>, mode: String) -> Result<(), St
     };
     state
         .cmd_tx
-        .send(DsCommand::SetMode(m))
+        .send(for_robot(robot_id, DsCommand::SetMode(m)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reboot_rio(
+    state: State<'_, AppState>,
+    robot_id: Option<String>,
+) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(for_robot(robot_id, DsCommand::RebootRio))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restart_code(
+    state: State<'_, AppState>,
+    robot_id: Option<String>,
+) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(for_robot(robot_id, DsCommand::RestartCode))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set how many control packets carry each reboot/restart request (default 5)
+#[tauri::command]
+pub async fn set_request_repeat(state: State<'_, AppState>, packets: u8) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetRequestRepeat(packets.clamp(1, 50)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Protocol debugging: change the comm version byte sent to the robot
+/// (default 0x01). The value in use is reported by `get_socket_status`.
+#[tauri::command]
+pub async fn set_comm_version(state: State<'_, AppState>, version: u8) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetCommVersion(version))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_session_timeline(state: State<'_, AppState>) -> Vec<TimelineEntry> {
+    state.timeline.read().entries()
+}
+
+#[tauri::command]
+pub fn get_socket_status(state: State<'_, AppState>) -> SocketStatus {
+    state.socket_status.read().clone()
+}
+
+/// Latest robot state, as last sent in `robot-state`
+#[tauri::command]
+pub fn get_robot_state(state: State<'_, AppState>) -> RobotState {
+    state.robot_snapshot.read().robot_state.clone()
+}
+
+/// The last control packet sent to the robot, as hex and decoded, for
+/// checking what the DS sends without a packet sniffer
+#[tauri::command]
+pub fn get_last_outbound_packet(state: State<'_, AppState>) -> Result<OutboundPacket, String> {
+    OutboundPacket::decode(&state.last_outbound.read())
+        .ok_or_else(|| "No packet has been sent yet".to_string())
+}
+
+/// Latest diagnostics, as last sent in `diagnostics`
+#[tauri::command]
+pub fn get_diagnostics(state: State<'_, AppState>) -> DiagnosticData {
+    state.robot_snapshot.read().diagnostics.clone()
+}
+
+/// Robot, diagnostic and link metrics in Prometheus text format (see
+/// `render_metrics` for the metric names)
+#[tauri::command]
+pub fn get_metrics_text(state: State<'_, AppState>) -> String {
+    render_metrics(&state.robot_snapshot.read())
+}
+
+/// Connect to a second robot by team number or IP. It stays disabled until
+/// control commands are addressed to `id` or it is made the active robot;
+/// its state, diagnostics and battery warnings arrive as `robot-event` events
+/// tagged `id`.
+#[tauri::command]
+pub async fn add_monitored_robot(
+    state: State<'_, AppState>,
+    id: String,
+    team_or_ip: String,
+) -> Result<(), String> {
+    let ip = match TargetAddress::parse(&team_or_ip)? {
+        TargetAddress::Team(team) => state.config.lock().ip_resolver().team_to_ip(team),
+        TargetAddress::Ip(ip) => ip,
+    };
+    let ip = ip.parse().map_err(|e| format!("Invalid IP {ip}: {e}"))?;
+    state
+        .cmd_tx
+        .send(DsCommand::AddMonitoredRobot { id, ip })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_monitored_robot(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::RemoveMonitoredRobot(id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Send control commands without a robot id, and the joysticks, to
+/// `robot_id` ("primary" for the main connection). Disables every robot.
+#[tauri::command]
+pub async fn set_active_robot(state: State<'_, AppState>, robot_id: String) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetActiveRobot(robot_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Toggle throttled raw packet capture (`packet-capture` events) for
+/// protocol debugging. Off by default.
+#[tauri::command]
+pub async fn set_packet_capture(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetPacketCapture(enabled))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Toggle automatic telemetry snapshots on brownout. Snapshots are written to
+/// `snapshots/brownout-{millis}.json` in the app data directory.
+#[tauri::command]
+pub async fn set_brownout_snapshots(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let dir = if enabled {
+        let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        Some(data_dir.join("snapshots"))
+    } else {
+        None
+    };
+    state
+        .cmd_tx
+        .send(DsCommand::SetBrownoutSnapshots(dir))
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Safety toggle for pit work: when disabled, every joystick is sent to the
+/// robot at rest regardless of input. The gamepad view still shows live
+/// values; `joysticks-enabled` reports the state.
 #[tauri::command]
-pub async fn reboot_rio(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn set_joysticks_enabled(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
     state
         .cmd_tx
-        .send(DsCommand::RebootRio)
+        .send(DsCommand::SetJoysticksEnabled(enabled))
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Zero CAN error, brownout and minimum-voltage counters and the power
+/// disable/fault counts for a fresh test. Only the DS view is reset; counts
+/// the robot reports come back with its next update.
 #[tauri::command]
-pub async fn restart_code(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn reset_diagnostics(state: State<'_, AppState>) -> Result<(), String> {
     state
         .cmd_tx
-        .send(DsCommand::RestartCode)
+        .send(DsCommand::ResetDiagnostics)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Toggle the built-in robot simulator, which fakes a connected robot (state,
+/// diagnostics, console) for UI work and demos. Nothing is sent to real
+/// robots while it runs; its `robot-state` carries `simulated: true` and its
+/// console lines start with `[SIM]`.
+#[tauri::command]
+pub async fn set_simulated_robot(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state
+        .cmd_tx
+        .send(DsCommand::SetSimulatedRobot(enabled))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List every roboRIO answering mDNS on the network, sorted by team number
+#[tauri::command]
+pub async fn scan_roborios(timeout_ms: Option<u64>) -> Result<Vec<DiscoveredRoborio>, String> {
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(2000).clamp(500, 10_000));
+    crate::discovery::discover_all_roborios(timeout)
         .await
         .map_err(|e| e.to_string())
 }
@@ -0,0 +1,60 @@
+use tauri::State;
+
+use crate::event_server;
+use crate::protocol::selftest::{self, SelfTestReport};
+use crate::system_info::SystemInfoData;
+use crate::AppState;
+
+/// Last minute of host PC samples (1Hz), oldest first
+#[tauri::command]
+pub fn get_system_info_history(state: State<'_, AppState>) -> Vec<SystemInfoData> {
+    state.system_info_history.read().iter().cloned().collect()
+}
+
+/// Set the PC CPU temperature (°C) above which a `PcOverheat` alert fires
+#[tauri::command]
+pub fn set_cpu_temp_threshold(state: State<'_, AppState>, celsius: f32) -> Result<(), String> {
+    if !(40.0..=120.0).contains(&celsius) {
+        return Err(format!("Temperature threshold must be 40-120°C, got {celsius}"));
+    }
+    *state.cpu_temp_threshold.write() = celsius;
+    Ok(())
+}
+
+/// Event payload schema version, for frontends that missed `app-ready`
+#[tauri::command]
+pub fn get_schema_version() -> u32 {
+    crate::events::SCHEMA_VERSION
+}
+
+/// "Does my DS work" check: round-trips control packets through a loopback
+/// robot and opens a local TCP connection. Needs no robot and leaves the live
+/// connection alone.
+#[tauri::command]
+pub async fn run_self_test(state: State<'_, AppState>) -> Result<SelfTestReport, String> {
+    let gamepad_count = state.gamepad_manager.lock().gamepad_count();
+    Ok(selftest::run_self_test(gamepad_count).await)
+}
+
+/// Stream every DS event as JSON lines to local TCP clients on `port`
+/// (0 = pick a free port), replacing a running server. Returns the port.
+#[tauri::command]
+pub async fn start_event_server(state: State<'_, AppState>, port: u16) -> Result<u16, String> {
+    // Free the port first in case the same one is requested again
+    let running = state.event_server.lock().take();
+    if let Some(running) = running {
+        running.stop().await;
+    }
+    let server = event_server::start_event_server(port, state.event_fanout.clone()).await?;
+    let port = server.port;
+    *state.event_server.lock() = Some(server);
+    Ok(port)
+}
+
+/// Stop the event server and disconnect its clients
+#[tauri::command]
+pub async fn stop_event_server(state: State<'_, AppState>) -> Result<(), String> {
+    let running = state.event_server.lock().take();
+    running.ok_or("Event server is not running")?.stop().await;
+    Ok(())
+}
@@ -0,0 +1,424 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gamepad::manager::GamepadSettings;
+use crate::protocol::connection::{validate_team_number, TeamIpResolver};
+use crate::protocol::types::Alliance;
+
+/// Where a saved target points: a team number (10.TE.AM.2) or a fixed IP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum TargetAddress {
+    Team(u32),
+    Ip(String),
+}
+
+impl TargetAddress {
+    /// Parse user input: all digits is a team number, otherwise an IP address
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if let Ok(team) = input.parse::<u32>() {
+            validate_team_number(team)?;
+            return Ok(TargetAddress::Team(team));
+        }
+        input
+            .parse::<std::net::IpAddr>()
+            .map(|ip| TargetAddress::Ip(ip.to_string()))
+            .map_err(|_| format!("Not a team number or IP address: {input}"))
+    }
+}
+
+/// Whether `host` is a syntactically valid DNS hostname (RFC 1123). Dotted
+/// all-numeric names are rejected as malformed IP addresses.
+pub fn is_valid_hostname(host: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    if host.is_empty() || host.len() > 253 {
+        return false;
+    }
+    let labels_ok = host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    labels_ok && !host.split('.').all(|label| label.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A robot saved for quick switching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedTarget {
+    pub label: String,
+    pub address: TargetAddress,
+}
+
+/// Named game data string for practice scenarios
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameDataPreset {
+    pub name: String,
+    /// Sent as-is; an empty string clears the robot's game data
+    pub data: String,
+    /// Apply automatically when this alliance station is selected
+    pub alliance: Option<Alliance>,
+}
+
+/// How a custom dashboard is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustomLaunchType {
+    /// Run the file directly (an executable or a script with a shebang)
+    Direct,
+    /// `java -jar <path>`
+    JavaJar,
+    /// `open <path>` (a macOS .app bundle)
+    MacOpen,
+    /// A command on PATH
+    PathCmd,
+}
+
+impl CustomLaunchType {
+    pub fn parse(kind: &str) -> Result<Self, String> {
+        match kind.to_ascii_lowercase().as_str() {
+            "direct" => Ok(CustomLaunchType::Direct),
+            "javajar" | "jar" => Ok(CustomLaunchType::JavaJar),
+            "macopen" | "app" => Ok(CustomLaunchType::MacOpen),
+            "pathcmd" | "command" => Ok(CustomLaunchType::PathCmd),
+            _ => Err(format!("Unknown launch type: {kind}")),
+        }
+    }
+}
+
+/// A team's own dashboard, launched alongside the built-in ones
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomDashboard {
+    pub name: String,
+    /// File to run, or the command name for `PathCmd`
+    pub path: String,
+    pub launch_type: CustomLaunchType,
+}
+
+/// Named console filter for quickly showing one subsystem's output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedConsoleFilter {
+    pub name: String,
+    /// Substring, or a regular expression if `regex` is set
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+/// Named bundle of gamepad settings, so each driver can load their own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriverProfile {
+    pub name: String,
+    pub gamepad: GamepadSettings,
+}
+
+/// Settings restored on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DsConfig {
+    pub team_number: u32,
+    pub alliance: Alliance,
+    /// Explicit target IP; None means derive it from the team number
+    pub target_ip: Option<String>,
+    /// Target localhost while keeping `team_number` for display and game flows
+    pub simulation: bool,
+    /// Saved robots for multi-robot benches
+    pub targets: Vec<NamedTarget>,
+    /// Label of the saved target currently in use, if any
+    pub active_target: Option<String>,
+    pub game_data_presets: Vec<GameDataPreset>,
+    /// Kill dashboards the DS launched when the DS exits
+    pub close_dashboards_on_exit: bool,
+    pub custom_dashboards: Vec<CustomDashboard>,
+    /// WPILib year whose tools are used for dashboards; None = latest installed
+    pub wpilib_year: Option<u32>,
+    /// Team IP template such as "10.{te}.{am}.2"; None = the FRC default
+    pub ip_template: Option<String>,
+    pub console_filters: Vec<SavedConsoleFilter>,
+    pub driver_profiles: Vec<DriverProfile>,
+}
+
+impl DsConfig {
+    /// Team number → IP mapping, falling back to the default for an
+    /// invalid saved template
+    pub fn ip_resolver(&self) -> TeamIpResolver {
+        self.ip_template
+            .as_deref()
+            .and_then(|t| TeamIpResolver::new(t).ok())
+            .unwrap_or_default()
+    }
+
+    /// Add a target, replacing any existing one with the same label
+    pub fn upsert_target(&mut self, target: NamedTarget) {
+        match self.targets.iter_mut().find(|t| t.label == target.label) {
+            Some(existing) => *existing = target,
+            None => self.targets.push(target),
+        }
+    }
+
+    pub fn find_target(&self, label: &str) -> Option<&NamedTarget> {
+        self.targets.iter().find(|t| t.label == label)
+    }
+
+    /// Add a game data preset, replacing any existing one with the same name.
+    /// An alliance can be bound to only one preset.
+    pub fn upsert_game_data_preset(&mut self, preset: GameDataPreset) {
+        if let Some(alliance) = preset.alliance {
+            for p in &mut self.game_data_presets {
+                if p.alliance == Some(alliance) {
+                    p.alliance = None;
+                }
+            }
+        }
+        match self.game_data_presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.game_data_presets.push(preset),
+        }
+    }
+
+    pub fn find_game_data_preset(&self, name: &str) -> Option<&GameDataPreset> {
+        self.game_data_presets.iter().find(|p| p.name == name)
+    }
+
+    /// Game data to apply automatically when `alliance` is selected
+    pub fn game_data_for_alliance(&self, alliance: Alliance) -> Option<&str> {
+        self.game_data_presets
+            .iter()
+            .find(|p| p.alliance == Some(alliance))
+            .map(|p| p.data.as_str())
+    }
+
+    /// Add a custom dashboard, replacing any existing one with the same name
+    pub fn upsert_custom_dashboard(&mut self, dashboard: CustomDashboard) {
+        match self.custom_dashboards.iter_mut().find(|d| d.name == dashboard.name) {
+            Some(existing) => *existing = dashboard,
+            None => self.custom_dashboards.push(dashboard),
+        }
+    }
+
+    pub fn find_custom_dashboard(&self, name: &str) -> Option<&CustomDashboard> {
+        self.custom_dashboards.iter().find(|d| d.name == name)
+    }
+
+    /// Add a console filter, replacing any existing one with the same name
+    pub fn upsert_console_filter(&mut self, filter: SavedConsoleFilter) {
+        match self.console_filters.iter_mut().find(|f| f.name == filter.name) {
+            Some(existing) => *existing = filter,
+            None => self.console_filters.push(filter),
+        }
+    }
+
+    pub fn find_console_filter(&self, name: &str) -> Option<&SavedConsoleFilter> {
+        self.console_filters.iter().find(|f| f.name == name)
+    }
+
+    /// Add a driver profile, replacing any existing one with the same name
+    pub fn upsert_driver_profile(&mut self, profile: DriverProfile) {
+        match self.driver_profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.driver_profiles.push(profile),
+        }
+    }
+
+    pub fn find_driver_profile(&self, name: &str) -> Option<&DriverProfile> {
+        self.driver_profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Target the protocol loop settles on once the restored settings are
+    /// applied: localhost in simulation, else the explicit IP, else the team IP
+    pub fn restored_target(&self) -> String {
+        if self.simulation {
+            return "127.0.0.1".to_string();
+        }
+        match &self.target_ip {
+            Some(ip) => ip.clone(),
+            None => self.ip_resolver().team_to_ip(self.team_number),
+        }
+    }
+
+    /// Address of the saved target in use, if any
+    pub fn active_address(&self) -> Option<&TargetAddress> {
+        let label = self.active_target.as_deref()?;
+        self.find_target(label).map(|t| &t.address)
+    }
+}
+
+impl Default for DsConfig {
+    fn default() -> Self {
+        Self {
+            team_number: 0,
+            alliance: Alliance::Red1,
+            target_ip: None,
+            simulation: false,
+            targets: Vec::new(),
+            active_target: None,
+            game_data_presets: Vec::new(),
+            close_dashboards_on_exit: false,
+            custom_dashboards: Vec::new(),
+            wpilib_year: None,
+            ip_template: None,
+            console_filters: Vec::new(),
+            driver_profiles: Vec::new(),
+        }
+    }
+}
+
+pub fn config_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("config.json")
+}
+
+/// Load the saved config, falling back to defaults if it is missing or unreadable
+pub fn load_config(path: &Path) -> DsConfig {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return DsConfig::default(),
+        Err(e) => {
+            tracing::warn!("Failed to read config {}: {e}", path.display());
+            return DsConfig::default();
+        }
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid config {}: {e}", path.display());
+        DsConfig::default()
+    })
+}
+
+/// Write the config, replacing the old file only once the new one is complete
+pub fn save_config(path: &Path, config: &DsConfig) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_team_targets_the_team_ip() {
+        let config = DsConfig { team_number: 1234, ..DsConfig::default() };
+        assert_eq!(config.restored_target(), "10.12.34.2");
+
+        let config = DsConfig { simulation: true, ..config };
+        assert_eq!(config.restored_target(), "127.0.0.1");
+
+        let config = DsConfig {
+            simulation: false,
+            target_ip: Some("192.168.1.50".into()),
+            ..config
+        };
+        assert_eq!(config.restored_target(), "192.168.1.50");
+    }
+
+    #[test]
+    fn config_round_trips_through_the_file() {
+        let dir = std::env::temp_dir().join(format!("ds-config-test-{}", std::process::id()));
+        let path = config_path(&dir);
+        assert_eq!(load_config(&path), DsConfig::default());
+
+        let config = DsConfig {
+            team_number: 1234,
+            alliance: Alliance::Blue2,
+            target_ip: Some("10.12.34.2".into()),
+            ..DsConfig::default()
+        };
+        save_config(&path, &config).unwrap();
+        let loaded = load_config(&path);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let config: DsConfig = serde_json::from_str(r#"{ "team_number": 254 }"#).unwrap();
+        assert_eq!(config, DsConfig { team_number: 254, ..DsConfig::default() });
+    }
+
+    fn preset(name: &str, data: &str, alliance: Option<Alliance>) -> GameDataPreset {
+        GameDataPreset { name: name.into(), data: data.into(), alliance }
+    }
+
+    #[test]
+    fn game_data_presets_are_replaced_by_name_and_bound_once() {
+        let mut config = DsConfig::default();
+        config.upsert_game_data_preset(preset("Left", "LRL", Some(Alliance::Red1)));
+        config.upsert_game_data_preset(preset("Clear", "", None));
+        config.upsert_game_data_preset(preset("Left", "LLL", Some(Alliance::Red1)));
+        assert_eq!(config.game_data_presets.len(), 2);
+        assert_eq!(config.find_game_data_preset("Left").unwrap().data, "LLL");
+        assert_eq!(config.find_game_data_preset("Clear").unwrap().data, "");
+
+        // Binding the station to another preset unbinds the first
+        config.upsert_game_data_preset(preset("Right", "RRR", Some(Alliance::Red1)));
+        assert_eq!(config.game_data_for_alliance(Alliance::Red1), Some("RRR"));
+        assert_eq!(config.find_game_data_preset("Left").unwrap().alliance, None);
+        assert_eq!(config.game_data_for_alliance(Alliance::Blue1), None);
+    }
+
+    #[test]
+    fn simulation_targets_localhost_and_keeps_the_team() {
+        let mut config = DsConfig { team_number: 1234, simulation: true, ..DsConfig::default() };
+        assert_eq!(config.restored_target(), "127.0.0.1");
+        let saved: DsConfig = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!((saved.team_number, saved.simulation), (1234, true));
+
+        config.simulation = false;
+        assert_eq!(config.restored_target(), "10.12.34.2");
+    }
+
+    #[test]
+    fn target_input_parses_teams_ips_and_hostnames() {
+        assert_eq!(TargetAddress::parse(" 1234 "), Ok(TargetAddress::Team(1234)));
+        assert_eq!(
+            TargetAddress::parse("10.12.34.2"),
+            Ok(TargetAddress::Ip("10.12.34.2".into()))
+        );
+        assert!(TargetAddress::parse("10.12.34.256").is_err());
+        assert!(TargetAddress::parse("99999").is_err());
+
+        assert!(is_valid_hostname("roboRIO-1234-FRC.local"));
+        assert!(is_valid_hostname("robot.lan."));
+        assert!(!is_valid_hostname("10.12.34.256"));
+        assert!(!is_valid_hostname("-robot.local"));
+        assert!(!is_valid_hostname("robot..local"));
+        assert!(!is_valid_hostname("robot_1"));
+        assert!(!is_valid_hostname(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn saved_targets_are_listed_and_applied_by_label() {
+        let target = |label: &str, input: &str| NamedTarget {
+            label: label.into(),
+            address: TargetAddress::parse(input).unwrap(),
+        };
+        let mut config = DsConfig::default();
+        config.upsert_target(target("Comp bot", "1234"));
+        config.upsert_target(target("Practice bot", "10.12.34.50"));
+        config.upsert_target(target("Comp bot", "254"));
+        let labels: Vec<&str> = config.targets.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, ["Comp bot", "Practice bot"]);
+
+        let dir = std::env::temp_dir().join(format!("ds-targets-test-{}", std::process::id()));
+        let path = config_path(&dir);
+        config.active_target = Some("Practice bot".into());
+        save_config(&path, &config).unwrap();
+        let mut loaded = load_config(&path);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(loaded.targets, config.targets);
+
+        // Applying a target sets what the loop connects to
+        match loaded.active_address().cloned() {
+            Some(TargetAddress::Ip(ip)) => loaded.target_ip = Some(ip),
+            other => panic!("unexpected active target {other:?}"),
+        }
+        assert_eq!(loaded.restored_target(), "10.12.34.50");
+        assert_eq!(loaded.find_target("Comp bot").unwrap().address, TargetAddress::Team(254));
+        assert!(loaded.find_target("Missing").is_none());
+    }
+}
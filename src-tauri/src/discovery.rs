@@ -1,11 +1,23 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
 use std::time::Duration;
+
+use serde::Serialize;
+use tokio::net::UdpSocket;
 use tokio::sync::mpsc;
 use tracing;
 
-/// Discover the roboRIO via mDNS hostname resolution, then static IP fallback
-pub async fn discover_roborio(team: u32, result_tx: mpsc::Sender<String>) {
+use crate::protocol::connection::TeamIpResolver;
+
+/// Discover the roboRIO via mDNS hostname resolution, then fall back to the
+/// team IP from `resolver` (the same template the protocol loop uses)
+pub async fn discover_roborio(
+    team: u32,
+    resolver: TeamIpResolver,
+    result_tx: mpsc::Sender<String>,
+) {
     if team == 0 {
-        let _ = result_tx.send("127.0.0.1".to_string()).await;
+        let _ = result_tx.send(resolver.team_to_ip(0)).await;
         return;
     }
 
@@ -37,10 +49,185 @@ pub async fn discover_roborio(team: u32, result_tx: mpsc::Sender<String>) {
         }
     }
 
-    // Fallback to static IP
-    let te = team / 100;
-    let am = team % 100;
-    let ip = format!("10.{te}.{am}.2");
+    // Fallback to the static team IP
+    let ip = resolver.team_to_ip(team);
     tracing::info!("Using static IP fallback: {ip}");
     let _ = result_tx.send(ip).await;
 }
+
+/// A roboRIO found by `discover_all_roborios`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiscoveredRoborio {
+    pub team: u32,
+    pub ip: String,
+    /// e.g. `roboRIO-1234-FRC.local`
+    pub hostname: String,
+}
+
+/// Team number from a roboRIO hostname (`roboRIO-1234-FRC`, optionally with
+/// `.local` and a trailing dot), case-insensitive
+pub fn team_from_hostname(hostname: &str) -> Option<u32> {
+    let host = hostname.trim_end_matches('.').to_ascii_lowercase();
+    let host = host.strip_suffix(".local").unwrap_or(&host);
+    let team = host.strip_prefix("roborio-")?.strip_suffix("-frc")?;
+    if team.is_empty() || !team.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    team.parse().ok()
+}
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const DNS_TYPE_A: u16 = 1;
+const DNS_TYPE_PTR: u16 = 12;
+
+/// One-shot mDNS query for the NI service (`_ni._tcp.local`, PTR) that every
+/// roboRIO advertises
+fn build_ni_service_query() -> Vec<u8> {
+    // Header: id 0, flags 0, one question
+    let mut pkt = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in ["_ni", "_tcp", "local"] {
+        pkt.push(label.len() as u8);
+        pkt.extend_from_slice(label.as_bytes());
+    }
+    pkt.push(0);
+    pkt.extend_from_slice(&DNS_TYPE_PTR.to_be_bytes());
+    pkt.extend_from_slice(&1u16.to_be_bytes()); // class IN
+    pkt
+}
+
+/// Read a (possibly compressed) DNS name at `offset`. Returns the dotted
+/// name and the offset just past it in the record.
+fn read_dns_name(data: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bound pointer chains so a malicious packet can't loop forever
+    for _ in 0..32 {
+        let len = *data.get(offset)? as usize;
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(offset + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let ptr = ((len & 0x3F) << 8) | *data.get(offset + 1)? as usize;
+            end.get_or_insert(offset + 2);
+            offset = ptr;
+            continue;
+        }
+        let label = data.get(offset + 1..offset + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + len;
+    }
+    None
+}
+
+/// Extract (hostname, IPv4) pairs from every A record in an mDNS response
+fn parse_a_records(data: &[u8]) -> Vec<(String, Ipv4Addr)> {
+    let mut records = Vec::new();
+    if data.len() < 12 {
+        return records;
+    }
+    let count = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]) as usize;
+    let questions = count(4);
+    let answers = count(6) + count(8) + count(10);
+
+    let mut offset = 12;
+    for _ in 0..questions {
+        let Some((_, next)) = read_dns_name(data, offset) else {
+            return records;
+        };
+        offset = next + 4;
+    }
+    for _ in 0..answers {
+        let Some((name, next)) = read_dns_name(data, offset) else {
+            break;
+        };
+        let Some(fixed) = data.get(next..next + 10) else {
+            break;
+        };
+        let rtype = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let rdlen = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let rdata_start = next + 10;
+        let Some(rdata) = data.get(rdata_start..rdata_start + rdlen) else {
+            break;
+        };
+        if rtype == DNS_TYPE_A && rdlen == 4 {
+            records.push((name, Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3])));
+        }
+        offset = rdata_start + rdlen;
+    }
+    records
+}
+
+/// Browse `_ni._tcp.local.` and collect every roboRIO that answers within
+/// `timeout`, deduplicated by hostname and sorted by team number. Used to
+/// pick a robot on a shared bench; normal connection still goes through
+/// `discover_roborio`.
+pub async fn discover_all_roborios(timeout: Duration) -> std::io::Result<Vec<DiscoveredRoborio>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket
+        .send_to(&build_ni_service_query(), SocketAddr::from((MDNS_ADDR, MDNS_PORT)))
+        .await?;
+
+    let mut found: HashMap<String, DiscoveredRoborio> = HashMap::new();
+    let mut buf = vec![0u8; 9000];
+    let deadline = tokio::time::Instant::now() + timeout;
+    while let Ok(result) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let Ok((len, _)) = result else {
+            continue;
+        };
+        for (hostname, ip) in parse_a_records(&buf[..len]) {
+            if let Some(team) = team_from_hostname(&hostname) {
+                found
+                    .entry(hostname.to_ascii_lowercase())
+                    .or_insert_with(|| DiscoveredRoborio { team, ip: ip.to_string(), hostname });
+            }
+        }
+    }
+
+    let mut robots: Vec<_> = found.into_values().collect();
+    robots.sort_by(|a, b| a.team.cmp(&b.team).then_with(|| a.hostname.cmp(&b.hostname)));
+    tracing::info!("mDNS scan found {} roboRIO(s)", robots.len());
+    Ok(robots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn team_from_roborio_hostnames() {
+        assert_eq!(team_from_hostname("roboRIO-1234-FRC"), Some(1234));
+        assert_eq!(team_from_hostname("roborio-254-frc.local"), Some(254));
+        assert_eq!(team_from_hostname("ROBORIO-9999-FRC.local."), Some(9999));
+        assert_eq!(team_from_hostname("roboRIO--FRC"), None);
+        assert_eq!(team_from_hostname("roboRIO-12a4-FRC"), None);
+        assert_eq!(team_from_hostname("raspberrypi.local"), None);
+    }
+
+    #[test]
+    fn a_record_with_compressed_name() {
+        // One question (the NI service query), one A answer whose name is
+        // written out once and then referenced by pointer in a second answer
+        let mut pkt = build_ni_service_query();
+        pkt[7] = 2;
+        let name_at = pkt.len();
+        for label in ["roboRIO-1234-FRC", "local"] {
+            pkt.push(label.len() as u8);
+            pkt.extend_from_slice(label.as_bytes());
+        }
+        pkt.push(0);
+        pkt.extend_from_slice(&[0, 1, 0, 1, 0, 0, 0, 120, 0, 4, 10, 12, 34, 2]);
+        pkt.extend_from_slice(&[0xC0, name_at as u8]);
+        pkt.extend_from_slice(&[0, 1, 0, 1, 0, 0, 0, 120, 0, 4, 172, 22, 11, 2]);
+
+        let records = parse_a_records(&pkt);
+        assert_eq!(
+            records,
+            vec![
+                ("roboRIO-1234-FRC.local".to_string(), Ipv4Addr::new(10, 12, 34, 2)),
+                ("roboRIO-1234-FRC.local".to_string(), Ipv4Addr::new(172, 22, 11, 2)),
+            ]
+        );
+        assert_eq!(team_from_hostname(&records[0].0), Some(1234));
+    }
+}
@@ -0,0 +1,145 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use crate::protocol::connection::DsEvent;
+
+/// Events buffered per client before a slow one starts missing them
+pub const FANOUT_CAPACITY: usize = 256;
+
+/// Copy of the event stream for the event server, fed by the event bridge
+pub type EventFanout = broadcast::Sender<DsEvent>;
+
+/// A running event server
+#[derive(Debug)]
+pub struct EventServer {
+    pub port: u16,
+    shutdown: watch::Sender<()>,
+    accept_task: JoinHandle<()>,
+}
+
+impl EventServer {
+    /// Disconnect every client and close the listener, returning once the
+    /// port is free again
+    pub async fn stop(self) {
+        drop(self.shutdown);
+        let _ = self.accept_task.await;
+    }
+}
+
+/// Listen on 127.0.0.1:`port` (0 = any free port) and stream every `DsEvent`
+/// to each connected client as one JSON object per line, in the same
+/// `{"type": ...}` form the events serialize to. Only local tools can
+/// connect.
+pub async fn start_event_server(port: u16, fanout: EventFanout) -> Result<EventServer, String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .await
+        .map_err(|e| format!("Could not listen on port {port}: {e}"))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let (shutdown_tx, shutdown_rx) = watch::channel(());
+    let accept_task = tokio::spawn(accept_clients(listener, fanout, shutdown_rx));
+    tracing::info!("Event server listening on 127.0.0.1:{port}");
+    Ok(EventServer { port, shutdown: shutdown_tx, accept_task })
+}
+
+async fn accept_clients(
+    listener: TcpListener,
+    fanout: EventFanout,
+    mut shutdown_rx: watch::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            result = listener.accept() => match result {
+                Ok((stream, addr)) => {
+                    tracing::info!("Event client connected from {addr}");
+                    tokio::spawn(serve_client(
+                        stream,
+                        addr,
+                        fanout.subscribe(),
+                        shutdown_rx.clone(),
+                    ));
+                }
+                Err(e) => tracing::warn!("Event server accept failed: {e}"),
+            },
+            _ = shutdown_rx.changed() => break,
+        }
+    }
+    tracing::info!("Event server stopped");
+}
+
+/// Write events to one client until it disconnects or the server stops
+async fn serve_client(
+    mut stream: TcpStream,
+    addr: SocketAddr,
+    mut events: broadcast::Receiver<DsEvent>,
+    mut shutdown_rx: watch::Receiver<()>,
+) {
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = shutdown_rx.changed() => break,
+        };
+        let event = match event {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                tracing::warn!("Event client {addr} fell behind, {missed} events dropped");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let mut line = match serde_json::to_string(&event) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("Event not sent to {addr}: {e}");
+                continue;
+            }
+        };
+        line.push('\n');
+        if stream.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown().await;
+    tracing::info!("Event client {addr} disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn streams_events_as_json_lines_until_stopped() {
+        let (fanout, _) = broadcast::channel(FANOUT_CAPACITY);
+        let server = start_event_server(0, fanout.clone()).await.unwrap();
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        // Events sent before the client subscribes aren't replayed
+        while fanout.receiver_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        fanout.send(DsEvent::ConsoleCleared).unwrap();
+        fanout.send(DsEvent::RobotCodeStarted { timestamp: 12.5 }).unwrap();
+        let first: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["type"], "ConsoleCleared");
+        let second: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(second["type"], "RobotCodeStarted");
+
+        // Stopping disconnects the client and frees the port
+        let port = server.port;
+        server.stop().await;
+        let closed = tokio::time::timeout(Duration::from_secs(2), lines.next_line()).await;
+        assert!(matches!(closed, Ok(Ok(None))));
+        let restarted = start_event_server(port, fanout).await.unwrap();
+        restarted.stop().await;
+    }
+}
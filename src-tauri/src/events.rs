@@ -1,37 +1,268 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::mpsc;
 
+use crate::event_server::EventFanout;
 use crate::protocol::connection::DsEvent;
+use crate::replay::{ReplayRecord, RECORDED_EVENTS};
 
-/// Bridges protocol events to Tauri frontend events
-pub async fn event_bridge(app: AppHandle, mut event_rx: mpsc::Receiver<DsEvent>) {
-    while let Some(event) = event_rx.recv().await {
-        match &event {
-            DsEvent::RobotState(state) => {
-                let _ = app.emit("robot-state", state);
-            }
-            DsEvent::Diagnostics(diag) => {
-                let _ = app.emit("diagnostics", diag);
-            }
-            DsEvent::Console(msg) => {
-                tracing::info!("Console: {}", msg.message);
-                let _ = app.emit("console-message", msg);
-            }
-            DsEvent::GamepadUpdate(update) => {
-                let _ = app.emit("gamepad-update", update);
-            }
-            DsEvent::SystemInfo(info) => {
-                let _ = app.emit("system-info", info);
+/// Version of the event payload shapes sent to the frontend. Bump whenever a
+/// payload struct changes so an out-of-date frontend can detect the mismatch.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// Tauri event name and payload for a protocol event. Object payloads carry
+/// a `schema_version` field.
+fn frontend_event(event: &DsEvent) -> (&'static str, serde_json::Value) {
+    let (name, mut payload) = event_payload(event);
+    if let serde_json::Value::Object(fields) = &mut payload {
+        fields.insert("schema_version".into(), SCHEMA_VERSION.into());
+    }
+    (name, payload)
+}
+
+fn event_payload(event: &DsEvent) -> (&'static str, serde_json::Value) {
+    fn json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap_or_default()
+    }
+
+    match event {
+        DsEvent::RobotState(state) => ("robot-state", json(state)),
+        DsEvent::Diagnostics(diag) => ("diagnostics", json(diag)),
+        DsEvent::Console(msg) => ("console-message", json(msg)),
+        DsEvent::GamepadUpdate(update) => ("gamepad-update", json(update)),
+        DsEvent::SystemInfo(info) => ("system-info", json(info)),
+        DsEvent::ConnectionStatus(status) => ("connection-status", json(status)),
+        DsEvent::PowerData(data) => ("power-data", json(data)),
+        DsEvent::VersionInfo(info) => ("version-info", json(info)),
+        DsEvent::RobotCodeStarted { timestamp } => ("robot-code-started", json(timestamp)),
+        DsEvent::RobotCodeStopped { timestamp } => ("robot-code-stopped", json(timestamp)),
+        DsEvent::ConsoleCleared => ("console-cleared", serde_json::Value::Null),
+        DsEvent::Alert(alert) => ("alert", json(alert)),
+        DsEvent::RadioEvent(event) => ("radio-event", json(event)),
+        DsEvent::ActiveTarget { label } => ("active-target", json(label)),
+        DsEvent::ForRobot { robot_id, event } => {
+            let (name, payload) = event_payload(event);
+            (
+                "robot-event",
+                serde_json::json!({ "robot_id": robot_id, "event": name, "payload": payload }),
+            )
+        }
+        DsEvent::ActiveRobotChanged { robot_id } => ("active-robot-changed", json(robot_id)),
+        DsEvent::PacketCapture(capture) => ("packet-capture", json(capture)),
+        DsEvent::RobotRequest { request, acknowledged } => (
+            "robot-request",
+            serde_json::json!({ "request": request, "acknowledged": acknowledged }),
+        ),
+        DsEvent::AllianceChanged(info) => ("alliance-changed", json(info)),
+        DsEvent::LinkQuality(link) => ("link-quality", json(link)),
+        DsEvent::DashboardLaunchFailed { name } => ("dashboard-launch-failed", json(name)),
+        DsEvent::EStopConfirmed => ("estop-confirmed", serde_json::Value::Null),
+        DsEvent::EStopUnconfirmed => ("estop-unconfirmed", serde_json::Value::Null),
+        DsEvent::JoysticksEnabled { enabled } => ("joysticks-enabled", json(enabled)),
+        DsEvent::Resumed { slept_ms } => {
+            ("system-resumed", serde_json::json!({ "slept_ms": slept_ms }))
+        }
+        DsEvent::BatteryWarning { level, voltage } => (
+            "battery-warning",
+            serde_json::json!({ "level": level, "voltage": voltage }),
+        ),
+    }
+}
+
+/// Minimum spacing between emits of high-rate events. Everything else,
+/// including E-Stop and code-stopped events, is forwarded immediately.
+fn min_emit_interval(name: &str) -> Option<Duration> {
+    match name {
+        "robot-state" | "diagnostics" => Some(Duration::from_millis(100)),
+        "packet-capture" => Some(Duration::from_millis(50)),
+        _ => None,
+    }
+}
+
+/// Rate-limits high-rate events per event type. Updates arriving too soon
+/// after the last emit replace any pending one, which is sent once its
+/// interval has passed, so the frontend always ends up with the latest.
+#[derive(Default)]
+struct EventThrottle {
+    last_emit: HashMap<&'static str, Instant>,
+    pending: HashMap<&'static str, serde_json::Value>,
+}
+
+impl EventThrottle {
+    /// The payload if it should be emitted now; otherwise it is held back
+    fn offer(
+        &mut self,
+        name: &'static str,
+        payload: serde_json::Value,
+        now: Instant,
+    ) -> Option<serde_json::Value> {
+        let Some(interval) = min_emit_interval(name) else {
+            return Some(payload);
+        };
+        match self.last_emit.get(name) {
+            Some(last) if now.duration_since(*last) < interval => {
+                self.pending.insert(name, payload);
+                None
             }
-            DsEvent::ConnectionStatus(status) => {
-                let _ = app.emit("connection-status", status);
+            _ => {
+                self.pending.remove(name);
+                self.last_emit.insert(name, now);
+                Some(payload)
             }
-            DsEvent::PowerData(data) => {
-                let _ = app.emit("power-data", data);
+        }
+    }
+
+    /// When the earliest held-back update becomes due
+    fn next_due(&self) -> Option<Instant> {
+        self.pending
+            .keys()
+            .filter_map(|name| Some(*self.last_emit.get(name)? + min_emit_interval(name)?))
+            .min()
+    }
+
+    /// Held-back updates whose interval has passed
+    fn take_due(&mut self, now: Instant) -> Vec<(&'static str, serde_json::Value)> {
+        let due: Vec<&'static str> = self
+            .pending
+            .keys()
+            .copied()
+            .filter(|name| {
+                match (self.last_emit.get(name), min_emit_interval(name)) {
+                    (Some(last), Some(interval)) => now.duration_since(*last) >= interval,
+                    _ => true,
+                }
+            })
+            .collect();
+        due.into_iter()
+            .filter_map(|name| {
+                let payload = self.pending.remove(name)?;
+                self.last_emit.insert(name, now);
+                Some((name, payload))
+            })
+            .collect()
+    }
+
+    /// Everything held back, for when throttling is switched off
+    fn take_all(&mut self) -> Vec<(&'static str, serde_json::Value)> {
+        self.pending.drain().collect()
+    }
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Bridges protocol events to Tauri frontend events and, while `recording` is
+/// set, the session recording. While a session replay is running, live events
+/// are neither shown nor recorded.
+/// With `throttling` set, high-rate events reach the frontend at a bounded
+/// rate; recordings always get every event.
+pub async fn event_bridge(
+    app: AppHandle,
+    mut event_rx: mpsc::Receiver<DsEvent>,
+    record_tx: mpsc::Sender<ReplayRecord>,
+    recording: Arc<AtomicBool>,
+    replaying: Arc<AtomicBool>,
+    throttling: Arc<AtomicBool>,
+    fanout: EventFanout,
+) {
+    let _ = app.emit(
+        "app-ready",
+        serde_json::json!({ "schema_version": SCHEMA_VERSION }),
+    );
+
+    let mut throttle = EventThrottle::default();
+    loop {
+        let due = throttle.next_due();
+        let event = tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = tokio::time::sleep_until(
+                due.map(tokio::time::Instant::from_std).unwrap_or_else(tokio::time::Instant::now)
+            ), if due.is_some() => {
+                for (name, payload) in throttle.take_due(Instant::now()) {
+                    let _ = app.emit(name, payload);
+                }
+                continue;
             }
-            DsEvent::VersionInfo(info) => {
-                let _ = app.emit("version-info", info);
+        };
+
+        match &event {
+            DsEvent::Console(msg) => tracing::info!("Console: {}", msg.message),
+            DsEvent::RobotCodeStopped { .. } => tracing::warn!("Robot code stopped"),
+            _ => {}
+        }
+
+        // Live events go to external tools even while a replay is shown
+        if fanout.receiver_count() > 0 {
+            let _ = fanout.send(event.clone());
+        }
+
+        if replaying.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let (name, payload) = frontend_event(&event);
+        if recording.load(Ordering::Relaxed) && RECORDED_EVENTS.contains(&name) {
+            // Never stall the bridge on a slow disk
+            let _ = record_tx.try_send(ReplayRecord {
+                timestamp: unix_now(),
+                event: name.to_string(),
+                payload: payload.clone(),
+            });
+        }
+        if !throttling.load(Ordering::Relaxed) {
+            for (name, payload) in throttle.take_all() {
+                let _ = app.emit(name, payload);
             }
+            let _ = app.emit(name, payload);
+        } else if let Some(payload) = throttle.offer(name, payload, Instant::now()) {
+            let _ = app.emit(name, payload);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_collapses_to_the_first_and_latest_update() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut throttle = EventThrottle::default();
+
+        let sent: Vec<_> = (0..10)
+            .filter_map(|i| throttle.offer("robot-state", serde_json::json!(i), ms(i * 5)))
+            .collect();
+        assert_eq!(sent, [serde_json::json!(0)]);
+        assert_eq!(throttle.next_due(), Some(ms(100)));
+        assert!(throttle.take_due(ms(99)).is_empty());
+        assert_eq!(throttle.take_due(ms(100)), [("robot-state", serde_json::json!(9))]);
+        assert_eq!(throttle.next_due(), None);
+
+        // Unthrottled events always pass straight through
+        for _ in 0..3 {
+            assert!(throttle.offer("estop-confirmed", serde_json::json!(null), ms(101)).is_some());
+        }
+    }
+
+    #[test]
+    fn object_payloads_carry_the_schema_version() {
+        let state = crate::protocol::types::RobotState::default();
+        let (name, payload) = frontend_event(&DsEvent::RobotState(state));
+        assert_eq!(name, "robot-state");
+        assert_eq!(payload["schema_version"], SCHEMA_VERSION);
+        assert!(payload.get("battery_voltage").is_some());
+    }
+}
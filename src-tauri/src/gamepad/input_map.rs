@@ -0,0 +1,311 @@
+// User-supplied gilrs axis/button → WPILib index tables, per device name
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use gilrs::{Axis, Button};
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::connection::MAX_BUTTON_COUNT;
+use crate::protocol::types::MAX_JOYSTICK_AXES;
+
+/// Axes a mapping file can name. The D-pad axes always drive the POV.
+const AXES: [Axis; 7] = [
+    Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ,
+    Axis::RightStickX, Axis::RightStickY, Axis::RightZ, Axis::Unknown,
+];
+/// Buttons a mapping file can name. The D-pad buttons always drive the POV.
+const BUTTONS: [Button; 16] = [
+    Button::South, Button::East, Button::North, Button::West, Button::C, Button::Z,
+    Button::LeftTrigger, Button::LeftTrigger2, Button::RightTrigger, Button::RightTrigger2,
+    Button::Select, Button::Start, Button::Mode, Button::LeftThumb, Button::RightThumb,
+    Button::Unknown,
+];
+
+/// Index overrides for one device. Controls left out use the built-in mapping.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceMapping {
+    pub axes: HashMap<Axis, usize>,
+    pub buttons: HashMap<Button, usize>,
+}
+
+/// Mapping file entry for one device, with gilrs control names as keys
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DeviceMappingFile {
+    axes: BTreeMap<String, usize>,
+    buttons: BTreeMap<String, usize>,
+}
+
+impl From<&DeviceMapping> for DeviceMappingFile {
+    fn from(mapping: &DeviceMapping) -> Self {
+        Self {
+            axes: mapping.axes.iter().map(|(a, &i)| (format!("{a:?}"), i)).collect(),
+            buttons: mapping.buttons.iter().map(|(b, &i)| (format!("{b:?}"), i)).collect(),
+        }
+    }
+}
+
+/// gilrs control whose `Debug` name is `name`
+fn by_name<T: Copy + std::fmt::Debug>(controls: &[T], name: &str) -> Option<T> {
+    controls.iter().copied().find(|c| format!("{c:?}") == name)
+}
+
+impl DeviceMappingFile {
+    fn resolve(self, device: &str) -> Result<DeviceMapping, String> {
+        let mut mapping = DeviceMapping::default();
+        for (name, index) in self.axes {
+            let axis = by_name(&AXES, &name)
+                .ok_or_else(|| format!("{device}: unknown axis '{name}'"))?;
+            if index >= MAX_JOYSTICK_AXES as usize {
+                return Err(format!("{device}: axis index {index} out of range"));
+            }
+            mapping.axes.insert(axis, index);
+        }
+        for (name, index) in self.buttons {
+            let button = by_name(&BUTTONS, &name)
+                .ok_or_else(|| format!("{device}: unknown button '{name}'"))?;
+            if index >= MAX_BUTTON_COUNT as usize {
+                return Err(format!("{device}: button index {index} out of range"));
+            }
+            mapping.buttons.insert(button, index);
+        }
+        Ok(mapping)
+    }
+}
+
+/// Loaded mapping table, keyed by the device name gilrs reports
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputMappings {
+    devices: HashMap<String, DeviceMapping>,
+}
+
+impl InputMappings {
+    /// Parse a mapping file:
+    /// `{"<device name>": {"axes": {"LeftStickX": 0}, "buttons": {"South": 1}}}`
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file: HashMap<String, DeviceMappingFile> =
+            serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let devices = file
+            .into_iter()
+            .map(|(device, entry)| entry.resolve(&device).map(|m| (device, m)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { devices })
+    }
+
+    pub fn device_count(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Add or replace the mapping for one device
+    pub fn insert(&mut self, device: String, mapping: DeviceMapping) {
+        self.devices.insert(device, mapping);
+    }
+
+    /// The table in mapping file form
+    pub fn to_json(&self) -> Result<String, String> {
+        let file: BTreeMap<&str, DeviceMappingFile> = self
+            .devices
+            .iter()
+            .map(|(device, mapping)| (device.as_str(), mapping.into()))
+            .collect();
+        serde_json::to_string_pretty(&file).map_err(|e| e.to_string())
+    }
+
+    /// Overridden index for `axis` on `device`, if the table has one
+    pub fn axis(&self, device: &str, axis: Axis) -> Option<usize> {
+        self.devices.get(device)?.axes.get(&axis).copied()
+    }
+
+    /// Overridden index for `button` on `device`, if the table has one
+    pub fn button(&self, device: &str, button: Button) -> Option<usize> {
+        self.devices.get(device)?.buttons.get(&button).copied()
+    }
+}
+
+pub fn mappings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("input_mappings.json")
+}
+
+/// Write the mapping file, replacing the old one only once the new one is
+/// complete
+pub fn save_mappings(path: &Path, mappings: &InputMappings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, mappings.to_json()?).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Load the mapping file. A missing file is an empty table.
+pub fn load_mappings(path: &Path) -> Result<InputMappings, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => InputMappings::parse(&text)
+            .map_err(|e| format!("Invalid mapping file {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(InputMappings::default()),
+        Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Raw device element an SDL mapping binds a control to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SdlElement {
+    Button(usize),
+    Axis(usize),
+    /// Hat index and direction bitmask
+    Hat(usize, u8),
+}
+
+impl SdlElement {
+    /// Parse `b3`, `a2`, `+a2`, `-a2`, `a2~` or `h0.4`. Half-axis and
+    /// inversion markers are dropped; the whole axis is mapped.
+    fn parse(element: &str) -> Option<Self> {
+        let element = element.trim_start_matches(['+', '-']).trim_end_matches('~');
+        if let Some(index) = element.strip_prefix('b') {
+            index.parse().ok().map(SdlElement::Button)
+        } else if let Some(index) = element.strip_prefix('a') {
+            index.parse().ok().map(SdlElement::Axis)
+        } else {
+            let (hat, mask) = element.strip_prefix('h')?.split_once('.')?;
+            Some(SdlElement::Hat(hat.parse().ok()?, mask.parse().ok()?))
+        }
+    }
+}
+
+/// gilrs button for an SDL GameController button name
+fn sdl_button(control: &str) -> Option<Button> {
+    Some(match control {
+        "a" => Button::South,
+        "b" => Button::East,
+        "x" => Button::West,
+        "y" => Button::North,
+        "back" => Button::Select,
+        "start" => Button::Start,
+        "guide" => Button::Mode,
+        "leftshoulder" => Button::LeftTrigger,
+        "rightshoulder" => Button::RightTrigger,
+        "leftstick" => Button::LeftThumb,
+        "rightstick" => Button::RightThumb,
+        "lefttrigger" => Button::LeftTrigger2,
+        "righttrigger" => Button::RightTrigger2,
+        _ => return None,
+    })
+}
+
+/// gilrs axis for an SDL GameController axis name
+fn sdl_axis(control: &str) -> Option<Axis> {
+    Some(match control {
+        "leftx" => Axis::LeftStickX,
+        "lefty" => Axis::LeftStickY,
+        "rightx" => Axis::RightStickX,
+        "righty" => Axis::RightStickY,
+        "lefttrigger" => Axis::LeftZ,
+        "righttrigger" => Axis::RightZ,
+        _ => return None,
+    })
+}
+
+/// SDL mapping fields that describe the entry rather than bind a control
+const SDL_META_KEYS: [&str; 3] = ["platform", "crc", "hint"];
+
+/// An SDL_GameControllerDB line translated to this crate's index table
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdlMapping {
+    /// SDL GUID, which gilrs reports as the device UUID
+    pub guid: [u8; 16],
+    pub name: String,
+    /// Each control lands on the raw device index SDL bound it to, so robot
+    /// code sees the same indices as with the device unmapped
+    pub mapping: DeviceMapping,
+    /// Entries with no equivalent here, as written in the mapping
+    pub skipped: Vec<String>,
+}
+
+/// Result of importing an SDL mapping, for display
+#[derive(Debug, Clone, Serialize)]
+pub struct SdlImport {
+    /// Device name the mapping was stored under
+    pub device: String,
+    pub mapped: usize,
+    pub skipped: Vec<String>,
+}
+
+/// Parse an SDL GameController mapping (`GUID,name,a:b0,leftx:a0,...`).
+/// D-pad entries are checked but skipped, since the D-pad always drives the
+/// POV; so are controls this crate has no index for (paddles, touchpad).
+pub fn parse_sdl_mapping(line: &str) -> Result<SdlMapping, String> {
+    let mut fields = line.trim().trim_end_matches(',').split(',');
+    let guid_hex = fields.next().unwrap_or_default().trim();
+    let name = fields.next().map(str::trim).filter(|n| !n.is_empty());
+    let name = name.ok_or("SDL mapping has no device name")?.to_string();
+    if guid_hex.len() != 32 {
+        return Err(format!("Invalid SDL GUID '{guid_hex}'"));
+    }
+    let mut guid = [0u8; 16];
+    for (i, byte) in guid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&guid_hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("Invalid SDL GUID '{guid_hex}'"))?;
+    }
+
+    let mut mapping = DeviceMapping::default();
+    let mut skipped = Vec::new();
+    for field in fields.map(str::trim).filter(|f| !f.is_empty()) {
+        let (control, element) = field
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed SDL mapping entry '{field}'"))?;
+        if SDL_META_KEYS.contains(&control) || control.starts_with("sdk") {
+            continue;
+        }
+        // A half-axis output (`+leftx`) still lands on the whole axis
+        let control = control.trim_start_matches(['+', '-']);
+        let parsed = SdlElement::parse(element)
+            .ok_or_else(|| format!("Malformed SDL element '{field}'"))?;
+        match (parsed, sdl_button(control), sdl_axis(control)) {
+            (SdlElement::Button(index), Some(button), _) => {
+                if index >= MAX_BUTTON_COUNT as usize {
+                    return Err(format!("{field}: button index out of range"));
+                }
+                mapping.buttons.insert(button, index);
+            }
+            (SdlElement::Axis(index), _, Some(axis)) => {
+                if index >= MAX_JOYSTICK_AXES as usize {
+                    return Err(format!("{field}: axis index out of range"));
+                }
+                mapping.axes.insert(axis, index);
+            }
+            _ => skipped.push(field.to_string()),
+        }
+    }
+    Ok(SdlMapping { guid, name, mapping, skipped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sdl_controller_line() {
+        let line = "030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,b:b1,x:b2,y:b3,\
+            back:b6,guide:b8,start:b7,leftstick:b9,rightstick:b10,leftshoulder:b4,\
+            rightshoulder:b5,dpup:h0.1,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,leftx:a0,\
+            lefty:a1,rightx:a3,righty:a4,lefttrigger:a2,righttrigger:a5,misc1:b11,\
+            platform:Linux,";
+        let sdl = parse_sdl_mapping(line).unwrap();
+
+        assert_eq!(sdl.name, "Xbox 360 Controller");
+        assert_eq!(sdl.guid[..4], [0x03, 0x00, 0x00, 0x00]);
+        assert_eq!(sdl.guid[4..6], [0x5e, 0x04]);
+        assert_eq!(sdl.mapping.buttons.get(&Button::West), Some(&2));
+        assert_eq!(sdl.mapping.buttons.get(&Button::Mode), Some(&8));
+        assert_eq!(sdl.mapping.buttons.len(), 11);
+        assert_eq!(sdl.mapping.axes.get(&Axis::RightStickX), Some(&3));
+        assert_eq!(sdl.mapping.axes.get(&Axis::RightZ), Some(&5));
+        assert_eq!(sdl.mapping.axes.len(), 6);
+        // D-pad hats and controls with no index here are reported, not mapped
+        assert_eq!(sdl.skipped.len(), 5);
+        assert!(sdl.skipped.contains(&"misc1:b11".to_string()));
+
+        assert!(parse_sdl_mapping("0300,Pad,a:b0").is_err());
+        assert!(parse_sdl_mapping("030000005e0400008e02000010010000,Pad,a:q0").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+/// Measures how long input written by the gamepad thread takes to reach an
+/// outbound packet. The gamepad thread stamps the probe while publishing
+/// joystick state; the protocol loop takes the stamp while reading that
+/// state for the next packet.
+#[derive(Debug, Default)]
+pub struct LatencyProbe {
+    reply: Option<oneshot::Sender<Duration>>,
+    injected_at: Option<Instant>,
+}
+
+pub type SharedLatencyProbe = Arc<Mutex<LatencyProbe>>;
+
+impl LatencyProbe {
+    /// Start a measurement, replacing any that is still pending
+    pub fn arm(&mut self, reply: oneshot::Sender<Duration>) {
+        self.reply = Some(reply);
+        self.injected_at = None;
+    }
+
+    /// A measurement is waiting for the gamepad thread to publish state
+    pub fn awaiting_injection(&self) -> bool {
+        self.reply.is_some() && self.injected_at.is_none()
+    }
+
+    /// Gamepad thread: mark the input published at `now` as the probe
+    pub fn inject(&mut self, now: Instant) {
+        if self.reply.is_some() && self.injected_at.is_none() {
+            self.injected_at = Some(now);
+        }
+    }
+
+    /// Protocol loop: take an injected probe so the packet being built can
+    /// report it
+    pub fn take_injection(&mut self) -> Option<(Instant, oneshot::Sender<Duration>)> {
+        let injected_at = self.injected_at.take()?;
+        self.reply.take().map(|reply| (injected_at, reply))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gamepad publish and packet send period
+    const TICK: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn probe_is_stamped_once_and_taken_once() {
+        let mut probe = LatencyProbe::default();
+        probe.inject(Instant::now());
+        assert!(probe.take_injection().is_none(), "unarmed probe was stamped");
+
+        let (reply, _rx) = oneshot::channel();
+        probe.arm(reply);
+        assert!(probe.awaiting_injection());
+        let first = Instant::now();
+        probe.inject(first);
+        probe.inject(first + TICK);
+        assert!(!probe.awaiting_injection());
+        assert_eq!(probe.take_injection().map(|(at, _)| at), Some(first));
+        assert!(probe.take_injection().is_none());
+    }
+
+    #[tokio::test]
+    async fn measured_latency_is_within_one_send_tick() {
+        let probe: SharedLatencyProbe = Arc::default();
+        let (reply, rx) = oneshot::channel();
+        probe.lock().arm(reply);
+
+        let gamepad = {
+            let probe = probe.clone();
+            std::thread::spawn(move || {
+                for _ in 0..10 {
+                    probe.lock().inject(Instant::now());
+                    std::thread::sleep(TICK);
+                }
+            })
+        };
+        let mut send = tokio::time::interval(TICK);
+        let latency = loop {
+            send.tick().await;
+            let taken = probe.lock().take_injection();
+            if let Some((injected_at, reply)) = taken {
+                let _ = reply.send(injected_at.elapsed());
+                break rx.await.unwrap();
+            }
+        };
+        gamepad.join().unwrap();
+        // One send tick at most, plus scheduling slack
+        assert!(latency <= TICK + Duration::from_millis(80), "{latency:?}");
+    }
+}
@@ -1,10 +1,20 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use gilrs::{Gilrs, Event as GilrsEvent, EventType, Axis, Button};
+use gilrs::{Gilrs, Event as GilrsEvent, EventType, Axis, Button, PowerInfo};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 
-use crate::protocol::types::JoystickState;
-use crate::protocol::connection::{GamepadInfo, GamepadUpdate};
+use super::input_map::{InputMappings, SdlImport, SdlMapping};
+use super::latency::SharedLatencyProbe;
+use super::mapping::{
+    apply_slew, AxisCalibration, AxisCapture, ButtonRemap, TriggerMode, TriggerModes,
+};
+use crate::protocol::types::{
+    JoystickDescriptor, JoystickLayout, JoystickState, HID_TYPE_HID_GAMEPAD,
+    HID_TYPE_XINPUT_GAMEPAD,
+};
+use crate::protocol::connection::{GamepadBattery, GamepadInfo, GamepadUpdate};
 
 /// Maps gilrs axis to our axis index (matching WPILib convention)
 /// Supports gamepads (6 axes) and flight sticks (X, Y, Twist, Throttle)
@@ -20,6 +30,47 @@ fn axis_index(axis: Axis) -> Option<usize> {
     }
 }
 
+/// Axis index on an Xbox controller, following WPILib's `XboxController`
+/// (left X/Y, left trigger, right trigger, right X/Y)
+fn xbox_axis_index(axis: Axis) -> Option<usize> {
+    match axis {
+        Axis::LeftStickX => Some(0),
+        Axis::LeftStickY => Some(1),
+        Axis::LeftZ => Some(2),
+        Axis::RightZ => Some(3),
+        Axis::RightStickX => Some(4),
+        Axis::RightStickY => Some(5),
+        _ => None,
+    }
+}
+
+fn device_axis_index(axis: Axis, is_xbox: bool) -> Option<usize> {
+    if is_xbox { xbox_axis_index(axis) } else { axis_index(axis) }
+}
+
+/// Axis index from the device's loaded mapping, else the built-in one
+fn mapped_axis_index(
+    mappings: &InputMappings,
+    device: &str,
+    axis: Axis,
+    is_xbox: bool,
+) -> Option<usize> {
+    mappings.axis(device, axis).or_else(|| device_axis_index(axis, is_xbox))
+}
+
+/// Button index from the device's loaded mapping, else the built-in one
+fn mapped_button_index(mappings: &InputMappings, device: &str, button: Button) -> Option<usize> {
+    mappings.button(device, button).or_else(|| button_index(button))
+}
+
+fn is_trigger_axis(axis: Axis) -> bool {
+    matches!(axis, Axis::LeftZ | Axis::RightZ)
+}
+
+fn is_trigger_button(button: Button) -> bool {
+    matches!(button, Button::LeftTrigger2 | Button::RightTrigger2)
+}
+
 /// Maps gilrs button to our button index (matching WPILib convention)
 /// Supports gamepads (A/B/X/Y + shoulders) and flight sticks (numbered buttons)
 fn button_index(button: Button) -> Option<usize> {
@@ -60,36 +111,317 @@ fn dpad_to_pov(up: bool, right: bool, down: bool, left: bool) -> i16 {
     }
 }
 
+fn battery_from_power(power: PowerInfo) -> Option<GamepadBattery> {
+    match power {
+        PowerInfo::Unknown => None,
+        PowerInfo::Wired => Some(GamepadBattery::Wired),
+        PowerInfo::Charging(percent) => Some(GamepadBattery::Charging(percent)),
+        PowerInfo::Discharging(percent) => Some(GamepadBattery::Discharging(percent)),
+        PowerInfo::Charged => Some(GamepadBattery::Charged),
+    }
+}
+
+/// How often controller power state is re-read (it changes slowly)
+const BATTERY_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Infer a device's layout from the axes/buttons gilrs can map for it, so
+/// robot code sees the controller's real counts. None if nothing is mapped.
+fn detect_layout(gamepad: &gilrs::Gamepad) -> Option<JoystickLayout> {
+    const AXES: [Axis; 6] = [
+        Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ,
+        Axis::RightStickX, Axis::RightStickY, Axis::RightZ,
+    ];
+    const BUTTONS: [Button; 15] = [
+        Button::South, Button::East, Button::West, Button::North,
+        Button::LeftTrigger, Button::RightTrigger, Button::Select, Button::Start,
+        Button::LeftThumb, Button::RightThumb, Button::LeftTrigger2, Button::RightTrigger2,
+        Button::C, Button::Z, Button::Mode,
+    ];
+    const HAT_BUTTONS: [Button; 4] = [
+        Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight,
+    ];
+
+    let is_xbox = is_xbox_device(gamepad);
+    let axes = AXES.iter()
+        .filter(|&&a| gamepad.axis_code(a).is_some())
+        .filter_map(|&a| device_axis_index(a, is_xbox))
+        .max()
+        .map_or(0, |i| i + 1);
+    let buttons = BUTTONS.iter()
+        .filter(|&&b| gamepad.button_code(b).is_some())
+        .filter_map(|&b| button_index(b))
+        .max()
+        .map_or(0, |i| i + 1);
+    if axes == 0 && buttons == 0 {
+        return None;
+    }
+    let has_hat = HAT_BUTTONS.iter().any(|&b| gamepad.button_code(b).is_some())
+        || gamepad.axis_code(Axis::DPadX).is_some();
+    Some(JoystickLayout {
+        axes: axes as u8,
+        buttons: buttons as u8,
+        povs: u8::from(has_hat),
+    })
+}
+
+/// Initial state for a newly connected device
+fn initial_state(gamepad: &gilrs::Gamepad) -> JoystickState {
+    let mut state = JoystickState::default();
+    if let Some(layout) = detect_layout(gamepad) {
+        layout.apply(&mut state);
+    }
+    state
+}
+
+/// Microsoft's USB vendor ID
+const VENDOR_MICROSOFT: u16 = 0x045E;
+
+/// Whether robot code should see this device as an Xbox controller: a
+/// Microsoft device, or one whose name or SDL mapping says Xbox/XInput
+fn is_xbox_device(gamepad: &gilrs::Gamepad) -> bool {
+    if gamepad.vendor_id() == Some(VENDOR_MICROSOFT) {
+        return true;
+    }
+    [Some(gamepad.name()), gamepad.map_name()]
+        .into_iter()
+        .flatten()
+        .any(|name| {
+            let lower = name.to_lowercase();
+            lower.contains("xbox") || lower.contains("x-box") || lower.contains("xinput")
+        })
+}
+
+/// Hat axis deflection needed to count as pressed in that direction
+const HAT_THRESHOLD: f32 = 0.5;
+
+/// Converts a hat switch reported as an axis pair (`Axis::DPadX`/`DPadY`,
+/// Y positive = up like gilrs sticks) to a POV angle
+fn hat_to_pov(x: f32, y: f32) -> i16 {
+    dpad_to_pov(
+        y > HAT_THRESHOLD,
+        x > HAT_THRESHOLD,
+        y < -HAT_THRESHOLD,
+        x < -HAT_THRESHOLD,
+    )
+}
+
+/// Wall-clock time beyond the expected loop interval that means the system
+/// was asleep rather than just busy
+const RESUME_GAP: Duration = Duration::from_secs(5);
+
+/// Time the system spent asleep between two loop iterations, if any. Uses
+/// wall-clock time because `Instant` stops during suspend on some platforms.
+pub fn detect_resume(
+    last: std::time::SystemTime,
+    now: std::time::SystemTime,
+    expected: Duration,
+) -> Option<Duration> {
+    let gap = now.duration_since(last).ok()?;
+    (gap > expected + RESUME_GAP).then_some(gap)
+}
+
+/// Devices to add (connected but untracked) and to drop (tracked but no
+/// longer connected)
+fn reconcile_devices<T: Copy + PartialEq>(tracked: &[T], connected: &[T]) -> (Vec<T>, Vec<T>) {
+    let added = connected.iter().copied().filter(|id| !tracked.contains(id)).collect();
+    let removed = tracked.iter().copied().filter(|id| !connected.contains(id)).collect();
+    (added, removed)
+}
+
+/// Maximum time between periodic UI updates when nothing has changed
+pub const UPDATE_HEARTBEAT: Duration = Duration::from_secs(2);
+
+/// Default and allowed rates for periodic UI updates
+pub const DEFAULT_UI_RATE_HZ: u32 = 10;
+pub const MAX_UI_RATE_HZ: u32 = 50;
+
+/// Axis movement smaller than this doesn't count as a change for the UI,
+/// so sensor noise on a resting stick doesn't keep updates flowing
+const AXIS_CHANGE_EPSILON: f32 = 0.005;
+
+/// Whether two snapshots differ visibly: any axis by more than the epsilon,
+/// or any button, POV, device or slot setting at all
+fn updates_differ(a: &GamepadUpdate, b: &GamepadUpdate) -> bool {
+    a.gamepads.len() != b.gamepads.len()
+        || a.gamepads.iter().zip(&b.gamepads).any(|(a, b)| {
+            a.axes.len() != b.axes.len()
+                || a.axes.iter().zip(&b.axes).any(|(x, y)| (x - y).abs() > AXIS_CHANGE_EPSILON)
+                || a.buttons != b.buttons
+                || a.povs != b.povs
+                || a.id != b.id
+                || a.name != b.name
+                || a.slot != b.slot
+                || a.locked != b.locked
+                || a.battery != b.battery
+                || a.button_remap != b.button_remap
+        })
+}
+
+/// Decide whether a periodic gamepad update should be sent to the UI.
+/// Unchanged snapshots are skipped until the heartbeat interval elapses.
+pub fn should_emit_update(
+    last_sent: Option<&GamepadUpdate>,
+    update: &GamepadUpdate,
+    since_last_emit: Duration,
+) -> bool {
+    since_last_emit >= UPDATE_HEARTBEAT
+        || last_sent.is_none_or(|last| updates_differ(last, update))
+}
+
+/// Stable per-device identifier from gilrs. None when the backend can't
+/// provide one (gilrs reports all zeros).
+fn device_uuid(gamepad: &gilrs::Gamepad) -> Option<[u8; 16]> {
+    let uuid = gamepad.uuid();
+    (uuid != [0; 16]).then_some(uuid)
+}
+
+/// A slot reserved for one device. Keyed on UUID so identical controllers
+/// (same name) each return to their own slot; the name is kept for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotLock {
+    name: String,
+    /// None for a lock taken by name only (a device without a UUID, or a
+    /// lock from before UUIDs were used). It adopts the UUID of the first
+    /// matching device to reclaim it.
+    uuid: Option<[u8; 16]>,
+}
+
+/// Locked slot a connecting device should return to, if any. A UUID match
+/// wins; otherwise a name-only lock with the same name is used. Slots
+/// already occupied are skipped so two identical devices can't share one.
+fn find_locked_slot(
+    locks: &std::collections::HashMap<usize, SlotLock>,
+    occupied: &std::collections::HashSet<usize>,
+    uuid: Option<[u8; 16]>,
+    name: &str,
+) -> Option<usize> {
+    let free = |slot: &usize| !occupied.contains(slot);
+    let mut slots: Vec<usize> = locks.keys().copied().filter(free).collect();
+    slots.sort_unstable();
+    uuid.and_then(|uuid| slots.iter().copied().find(|s| locks[s].uuid == Some(uuid)))
+        .or_else(|| {
+            slots.iter().copied().find(|s| locks[s].uuid.is_none() && locks[s].name == name)
+        })
+}
+
+/// Every per-device gamepad setting, saved and restored as a driver profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    /// Device name → button remap
+    pub button_remaps: std::collections::HashMap<String, ButtonRemap>,
+    /// Slot → forced layout
+    pub layout_overrides: std::collections::HashMap<usize, JoystickLayout>,
+    /// Slot → reserved device
+    pub slot_locks: std::collections::HashMap<usize, SlotLock>,
+    /// Device name → max axis change per poll
+    pub slew_limits: std::collections::HashMap<String, f32>,
+    pub trigger_modes: TriggerModes,
+    /// Device name → axis index → calibration
+    pub calibrations: std::collections::HashMap<String, AxisCalibrations>,
+}
+
+/// Calibrated axes of one device, by axis index
+pub type AxisCalibrations = std::collections::BTreeMap<usize, AxisCalibration>;
+
+/// Name shown for keyboard-driven virtual devices
+pub const VIRTUAL_GAMEPAD_NAME: &str = "Virtual";
+
 /// Internal tracking of a connected gamepad
 struct TrackedGamepad {
-    gilrs_id: gilrs::GamepadId,
+    /// None for the virtual (keyboard-driven) device
+    gilrs_id: Option<gilrs::GamepadId>,
     name: String,
+    uuid: Option<[u8; 16]>,
     slot: usize,
     state: JoystickState,
     dpad_up: bool,
     dpad_right: bool,
     dpad_down: bool,
     dpad_left: bool,
+    /// Hat position for devices that report it as axes instead of D-pad buttons
+    hat_x: f32,
+    hat_y: f32,
+    battery: Option<GamepadBattery>,
+    is_xbox: bool,
+    /// Axes after slew limiting; sent instead of `state.axes` when the
+    /// device has a slew limit
+    slewed_axes: Vec<f32>,
+}
+
+impl TrackedGamepad {
+    /// Recompute POV 0 from whichever hat source is active. gilrs exposes a
+    /// single hat per device, so `povs` keeps its default length of one.
+    fn update_pov(&mut self) {
+        if self.state.povs.is_empty() {
+            return;
+        }
+        let buttons = dpad_to_pov(self.dpad_up, self.dpad_right, self.dpad_down, self.dpad_left);
+        self.state.povs[0] = if buttons >= 0 {
+            buttons
+        } else {
+            hat_to_pov(self.hat_x, self.hat_y)
+        };
+    }
 }
 
 /// Manages gamepad enumeration and input polling
 pub struct GamepadManager {
-    gilrs: Gilrs,
+    /// None when the platform has no usable input subsystem; only virtual
+    /// joysticks work then
+    gilrs: Option<Gilrs>,
     gamepads: Vec<TrackedGamepad>,
     joystick_state: Arc<RwLock<Vec<JoystickState>>>,
-    /// Maps slot index → device name for locked slots
-    locked_slots: std::collections::HashMap<usize, String>,
+    /// Maps slot index → the device reserved for it
+    locked_slots: std::collections::HashMap<usize, SlotLock>,
+    latency_probe: SharedLatencyProbe,
+    last_battery_poll: std::time::Instant,
+    /// Maps device name → button remap, so a remap follows the controller
+    /// to whichever slot it lands in
+    button_remaps: std::collections::HashMap<String, ButtonRemap>,
+    /// Maps slot index → layout forced by the user, overriding detection
+    layout_overrides: std::collections::HashMap<usize, JoystickLayout>,
+    /// Descriptor per slot (0-5), sent to the roboRIO over TCP
+    descriptors: Arc<RwLock<Vec<JoystickDescriptor>>>,
+    trigger_modes: TriggerModes,
+    /// Maps device name → max axis change per 20ms poll
+    slew_limits: std::collections::HashMap<String, f32>,
+    /// Maps device name → per-axis range calibration, applied before slew
+    calibrations: std::collections::HashMap<String, AxisCalibrations>,
+    /// Auto-calibration in progress
+    capture: Option<AxisCapture>,
+    /// State the UI shows changed since `take_ui_dirty`
+    ui_dirty: bool,
+    /// Per-device index overrides from the mapping file
+    input_mappings: InputMappings,
 }
 
 impl GamepadManager {
-    pub fn new(joystick_state: Arc<RwLock<Vec<JoystickState>>>) -> Self {
-        let gilrs = Gilrs::new().expect("Failed to initialize gilrs");
+    pub fn new(
+        joystick_state: Arc<RwLock<Vec<JoystickState>>>,
+        latency_probe: SharedLatencyProbe,
+        descriptors: Arc<RwLock<Vec<JoystickDescriptor>>>,
+    ) -> Self {
+        let gilrs = Gilrs::new()
+            .map_err(|e| tracing::warn!("Gamepad support unavailable: {e}"))
+            .ok();
 
         let mut manager = Self {
             gilrs,
             gamepads: Vec::new(),
             joystick_state,
             locked_slots: std::collections::HashMap::new(),
+            latency_probe,
+            last_battery_poll: std::time::Instant::now(),
+            button_remaps: std::collections::HashMap::new(),
+            layout_overrides: std::collections::HashMap::new(),
+            descriptors,
+            trigger_modes: TriggerModes::default(),
+            slew_limits: std::collections::HashMap::new(),
+            calibrations: std::collections::HashMap::new(),
+            capture: None,
+            ui_dirty: true,
+            input_mappings: InputMappings::default(),
         };
 
         // Enumerate already-connected gamepads
@@ -106,107 +438,169 @@ impl GamepadManager {
             .unwrap_or(self.gamepads.len())
     }
 
-    /// Find the locked slot for a device by name, if any
-    fn find_locked_slot(&self, name: &str) -> Option<usize> {
-        self.locked_slots.iter()
-            .find(|(_, locked_name)| locked_name.as_str() == name)
-            .map(|(&slot, _)| slot)
+    /// Find the locked slot for a connecting device, adopting its UUID if
+    /// the lock was name-only
+    fn claim_locked_slot(&mut self, uuid: Option<[u8; 16]>, name: &str) -> Option<usize> {
+        let occupied: std::collections::HashSet<usize> =
+            self.gamepads.iter().map(|g| g.slot).collect();
+        let slot = find_locked_slot(&self.locked_slots, &occupied, uuid, name)?;
+        let lock = self.locked_slots.get_mut(&slot)?;
+        if lock.uuid.is_none() && uuid.is_some() {
+            tracing::info!("Slot {slot} lock for '{name}' now keyed by device UUID");
+            lock.uuid = uuid;
+        }
+        Some(slot)
     }
 
     fn enumerate_gamepads(&mut self) {
         self.gamepads.clear();
-        for (id, gamepad) in self.gilrs.gamepads() {
+        for (id, gamepad) in self.gilrs.iter().flat_map(Gilrs::gamepads) {
             if gamepad.is_connected() {
                 let slot = self.first_available_slot();
                 self.gamepads.push(TrackedGamepad {
-                    gilrs_id: id,
+                    gilrs_id: Some(id),
                     name: gamepad.name().to_string(),
+                    uuid: device_uuid(&gamepad),
                     slot,
-                    state: JoystickState::default(),
+                    state: initial_state(&gamepad),
                     dpad_up: false,
                     dpad_right: false,
                     dpad_down: false,
                     dpad_left: false,
+                    hat_x: 0.0,
+                    hat_y: 0.0,
+                    battery: battery_from_power(gamepad.power_info()),
+                    is_xbox: is_xbox_device(&gamepad),
+                    slewed_axes: Vec::new(),
                 });
             }
         }
         self.sync_joystick_state();
     }
 
-    /// Poll for gamepad events and update state. Call at ~50Hz.
+    /// Start tracking a connected device, in its locked slot if it has one.
+    /// Returns false if it is already tracked or gilrs is unavailable.
+    fn track_connected(&mut self, id: gilrs::GamepadId) -> bool {
+        if self.gamepads.iter().any(|g| g.gilrs_id == Some(id)) {
+            return false;
+        }
+        let Some(gamepad) = self.gilrs.as_ref().map(|g| g.gamepad(id)) else {
+            return false;
+        };
+        let name = gamepad.name().to_string();
+        let battery = battery_from_power(gamepad.power_info());
+        let state = initial_state(&gamepad);
+        let is_xbox = is_xbox_device(&gamepad);
+        let uuid = device_uuid(&gamepad);
+        // Check if this device has a locked slot
+        let slot = if let Some(locked) = self.claim_locked_slot(uuid, &name) {
+            locked
+        } else {
+            self.first_available_slot()
+        };
+        self.gamepads.push(TrackedGamepad {
+            gilrs_id: Some(id),
+            name: name.clone(),
+            uuid,
+            slot,
+            state,
+            dpad_up: false,
+            dpad_right: false,
+            dpad_down: false,
+            dpad_left: false,
+            hat_x: 0.0,
+            hat_y: 0.0,
+            battery,
+            is_xbox,
+            slewed_axes: Vec::new(),
+        });
+        tracing::info!("Gamepad connected: {} (slot {})", name, slot);
+        true
+    }
+
+    /// Reconcile tracked devices with the ones gilrs reports connected, for
+    /// when a connection event was missed (seen after sleep/resume). Devices
+    /// already tracked keep their slots; new ones get their locked slot or
+    /// the first free one. Virtual devices are left alone.
+    pub fn rescan(&mut self) -> GamepadUpdate {
+        let connected: Vec<gilrs::GamepadId> = self
+            .gilrs
+            .iter()
+            .flat_map(Gilrs::gamepads)
+            .filter(|(_, gamepad)| gamepad.is_connected())
+            .map(|(id, _)| id)
+            .collect();
+        let tracked: Vec<gilrs::GamepadId> =
+            self.gamepads.iter().filter_map(|g| g.gilrs_id).collect();
+        let (added, removed) = reconcile_devices(&tracked, &connected);
+
+        self.gamepads.retain(|g| g.gilrs_id.is_none_or(|id| !removed.contains(&id)));
+        for &id in &added {
+            self.track_connected(id);
+        }
+        tracing::info!("Gamepad rescan: {} added, {} removed", added.len(), removed.len());
+        self.sync_joystick_state();
+        self.get_gamepad_update()
+    }
+
+    /// Poll for gamepad events and update state. Call at ~50Hz. Shared
+    /// joystick state is only republished when something changed.
     pub fn poll(&mut self) -> Option<GamepadUpdate> {
         let mut changed = false;
+        let mut input_changed = false;
 
         // Process all pending events
-        while let Some(GilrsEvent { id, event, .. }) = self.gilrs.next_event() {
+        while let Some(GilrsEvent { id, event, .. }) =
+            self.gilrs.as_mut().and_then(Gilrs::next_event)
+        {
             match event {
                 EventType::Connected => {
-                    let gamepad = self.gilrs.gamepad(id);
-                    let name = gamepad.name().to_string();
-                    // Check if this device has a locked slot
-                    let slot = if let Some(locked) = self.find_locked_slot(&name) {
-                        locked
-                    } else {
-                        self.first_available_slot()
-                    };
-                    self.gamepads.push(TrackedGamepad {
-                        gilrs_id: id,
-                        name: name.clone(),
-                        slot,
-                        state: JoystickState::default(),
-                        dpad_up: false,
-                        dpad_right: false,
-                        dpad_down: false,
-                        dpad_left: false,
-                    });
-                    changed = true;
-                    tracing::info!("Gamepad connected: {} (slot {})", name, slot);
+                    changed |= self.track_connected(id);
                 }
                 EventType::Disconnected => {
                     // If slot is locked, keep the reservation but remove the gamepad
-                    self.gamepads.retain(|g| g.gilrs_id != id);
+                    self.gamepads.retain(|g| g.gilrs_id != Some(id));
                     changed = true;
                     tracing::info!("Gamepad disconnected");
                 }
                 EventType::AxisChanged(axis, value, _) => {
-                    if let Some(gp) = self.gamepads.iter_mut().find(|g| g.gilrs_id == id) {
-                        if let Some(idx) = axis_index(axis) {
-                            if idx < gp.state.axes.len() {
-                                gp.state.axes[idx] = value;
-                            }
-                        }
+                    if let Some(index) = self.gamepads.iter().position(|g| g.gilrs_id == Some(id)) {
+                        input_changed = true;
+                        self.handle_axis(index, axis, value);
                     }
                 }
                 EventType::ButtonChanged(button, value, _) => {
-                    if let Some(gp) = self.gamepads.iter_mut().find(|g| g.gilrs_id == id) {
-                        let pressed = value > 0.5;
-                        // Handle D-pad buttons → POV
-                        match button {
-                            Button::DPadUp => gp.dpad_up = pressed,
-                            Button::DPadRight => gp.dpad_right = pressed,
-                            Button::DPadDown => gp.dpad_down = pressed,
-                            Button::DPadLeft => gp.dpad_left = pressed,
-                            _ => {
-                                if let Some(idx) = button_index(button) {
-                                    if idx < gp.state.buttons.len() {
-                                        gp.state.buttons[idx] = pressed;
-                                    }
-                                }
-                            }
-                        }
-                        // Update POV from D-pad state
-                        if !gp.state.povs.is_empty() {
-                            gp.state.povs[0] = dpad_to_pov(
-                                gp.dpad_up, gp.dpad_right, gp.dpad_down, gp.dpad_left,
-                            );
-                        }
+                    if let Some(index) = self.gamepads.iter().position(|g| g.gilrs_id == Some(id)) {
+                        input_changed = true;
+                        self.handle_button(index, button, value);
                     }
                 }
                 _ => {}
             }
         }
 
-        self.sync_joystick_state();
+        if self.last_battery_poll.elapsed() >= BATTERY_POLL_INTERVAL {
+            self.last_battery_poll = std::time::Instant::now();
+            for gp in &mut self.gamepads {
+                if let (Some(gilrs), Some(id)) = (&self.gilrs, gp.gilrs_id) {
+                    let battery = battery_from_power(gilrs.gamepad(id).power_info());
+                    if battery != gp.battery {
+                        gp.battery = battery;
+                        self.ui_dirty = true;
+                    }
+                }
+            }
+        }
+
+        let slewing = self.step_slew();
+        // Republish on change, or to give an armed latency probe its stamp
+        if changed
+            || input_changed
+            || slewing
+            || self.latency_probe.lock().awaiting_injection()
+        {
+            self.sync_joystick_state();
+        }
 
         if changed {
             Some(self.get_gamepad_update())
@@ -215,8 +609,98 @@ impl GamepadManager {
         }
     }
 
+    /// Apply an axis reading to tracked device `index`
+    fn handle_axis(&mut self, index: usize, axis: Axis, value: f32) {
+        let gp = &mut self.gamepads[index];
+        let triggers = self.trigger_modes.for_device(gp.is_xbox);
+        match axis {
+            Axis::DPadX => {
+                gp.hat_x = value;
+                gp.update_pov();
+            }
+            Axis::DPadY => {
+                gp.hat_y = value;
+                gp.update_pov();
+            }
+            _ if is_trigger_axis(axis) && !triggers.sends_axis() => {}
+            _ => {
+                let Some(idx) = mapped_axis_index(&self.input_mappings, &gp.name, axis, gp.is_xbox)
+                else {
+                    return;
+                };
+                if let Some(capture) = self.capture.as_mut() {
+                    if capture.device == gp.name {
+                        capture.record(idx, value);
+                    }
+                }
+                if idx >= gp.state.axes.len() {
+                    gp.state.axes.resize(idx + 1, 0.0);
+                }
+                gp.state.axes[idx] = self
+                    .calibrations
+                    .get(&gp.name)
+                    .and_then(|axes| axes.get(&idx))
+                    .map_or(value, |cal| cal.apply(value));
+            }
+        }
+    }
+
+    /// Apply a button reading to tracked device `index`
+    fn handle_button(&mut self, index: usize, button: Button, value: f32) {
+        let gp = &mut self.gamepads[index];
+        let pressed = value > 0.5;
+        let triggers = self.trigger_modes.for_device(gp.is_xbox);
+        // Handle D-pad buttons → POV
+        match button {
+            Button::DPadUp => gp.dpad_up = pressed,
+            Button::DPadRight => gp.dpad_right = pressed,
+            Button::DPadDown => gp.dpad_down = pressed,
+            Button::DPadLeft => gp.dpad_left = pressed,
+            // The analog axis already carries this trigger
+            _ if is_trigger_button(button) && !triggers.sends_button() => {}
+            _ => {
+                if let Some(idx) = mapped_button_index(&self.input_mappings, &gp.name, button) {
+                    let idx = self
+                        .button_remaps
+                        .get(&gp.name)
+                        .map_or(idx, |remap| remap.apply(idx));
+                    if idx >= gp.state.buttons.len() {
+                        gp.state.buttons.resize(idx + 1, false);
+                    }
+                    gp.state.buttons[idx] = pressed;
+                }
+            }
+        }
+        // Update POV from D-pad state
+        gp.update_pov();
+    }
+
+    /// Advance slew-limited axes one poll tick toward the raw stick values.
+    /// Returns whether any axis is still moving.
+    fn step_slew(&mut self) -> bool {
+        let mut moved = false;
+        for gp in &mut self.gamepads {
+            let Some(&limit) = self.slew_limits.get(&gp.name) else {
+                continue;
+            };
+            gp.slewed_axes.resize(gp.state.axes.len(), 0.0);
+            for (out, &target) in gp.slewed_axes.iter_mut().zip(&gp.state.axes) {
+                let next = apply_slew(*out, target, limit);
+                moved |= next != *out;
+                *out = next;
+            }
+        }
+        moved
+    }
+
+    /// Whether the UI snapshot may have changed since the last call
+    pub fn take_ui_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.ui_dirty)
+    }
+
     /// Sync internal gamepad state to the shared joystick state for the protocol loop
-    fn sync_joystick_state(&self) {
+    fn sync_joystick_state(&mut self) {
+        self.ui_dirty = true;
         let mut js = self.joystick_state.write();
         // Find max slot to size the vector
         let max_slot = self.gamepads.iter().map(|g| g.slot).max().unwrap_or(0);
@@ -225,8 +709,37 @@ impl GamepadManager {
         for gp in &self.gamepads {
             if gp.slot < js.len() {
                 js[gp.slot] = gp.state.clone();
+                if self.slew_limits.contains_key(&gp.name) {
+                    js[gp.slot].axes.clone_from(&gp.slewed_axes);
+                }
+                if let Some(layout) = self.layout_overrides.get(&gp.slot) {
+                    layout.apply(&mut js[gp.slot]);
+                }
             }
         }
+        // Stamp while the write lock is held so the protocol loop sees the
+        // stamp together with this state
+        self.latency_probe.lock().inject(std::time::Instant::now());
+
+        let mut descriptors = vec![JoystickDescriptor::default(); 6];
+        for gp in &self.gamepads {
+            if let (Some(desc), Some(state)) = (descriptors.get_mut(gp.slot), js.get(gp.slot)) {
+                *desc = JoystickDescriptor {
+                    name: gp.name.clone(),
+                    is_xbox: gp.is_xbox,
+                    hid_type: if gp.is_xbox { HID_TYPE_XINPUT_GAMEPAD } else { HID_TYPE_HID_GAMEPAD },
+                    layout: JoystickLayout {
+                        axes: state.axes.len() as u8,
+                        buttons: state.buttons.len() as u8,
+                        povs: state.pov_count() as u8,
+                    },
+                };
+            }
+        }
+        let mut shared = self.descriptors.write();
+        if *shared != descriptors {
+            *shared = descriptors;
+        }
     }
 
     /// Move gamepad from one slot to another. If target slot is occupied, swap.
@@ -254,6 +767,16 @@ impl GamepadManager {
         self.sync_joystick_state();
     }
 
+    /// POVs as `sync_joystick_state` sends them for `gp`: resized to the
+    /// slot's layout override, if any
+    fn sent_povs(&self, gp: &TrackedGamepad) -> Vec<i16> {
+        let mut povs = gp.state.povs.clone();
+        if let Some(layout) = self.layout_overrides.get(&gp.slot) {
+            povs.resize(layout.povs as usize, -1);
+        }
+        povs
+    }
+
     pub fn get_gamepad_update(&self) -> GamepadUpdate {
         GamepadUpdate {
             gamepads: self
@@ -265,18 +788,20 @@ impl GamepadManager {
                     slot: gp.slot,
                     axes: gp.state.axes.clone(),
                     buttons: gp.state.buttons.clone(),
-                    povs: gp.state.povs.clone(),
+                    povs: self.sent_povs(gp),
                     locked: self.locked_slots.contains_key(&gp.slot),
+                    battery: gp.battery,
+                    button_remap: self.button_remaps.get(&gp.name).cloned().unwrap_or_default(),
                 })
                 .collect(),
         }
     }
 
-    /// Lock a slot to its current device name
+    /// Lock a slot to its current device
     pub fn lock_slot(&mut self, slot: usize) {
         if let Some(gp) = self.gamepads.iter().find(|g| g.slot == slot) {
             tracing::info!("Locking slot {} to '{}'", slot, gp.name);
-            self.locked_slots.insert(slot, gp.name.clone());
+            self.locked_slots.insert(slot, SlotLock { name: gp.name.clone(), uuid: gp.uuid });
         }
     }
 
@@ -287,12 +812,652 @@ impl GamepadManager {
         }
     }
 
+    /// Remap a button on the device in `slot`. Returns false if the slot is empty.
+    pub fn remap_button(&mut self, slot: usize, from: usize, to: usize) -> bool {
+        let Some(gp) = self.gamepads.iter_mut().find(|g| g.slot == slot) else {
+            return false;
+        };
+        tracing::info!("Remapping button {from} → {to} on '{}'", gp.name);
+        self.button_remaps.entry(gp.name.clone()).or_default().set(from, to);
+        // Release everything so no press is left stranded at its old index
+        gp.state.buttons.iter_mut().for_each(|b| *b = false);
+        self.sync_joystick_state();
+        true
+    }
+
+    /// Force the axis/button/POV counts sent for `slot`, regardless of device
+    pub fn set_layout(&mut self, slot: usize, layout: JoystickLayout) {
+        tracing::info!("Slot {slot} layout: {layout:?}");
+        self.layout_overrides.insert(slot, layout);
+        self.sync_joystick_state();
+    }
+
+    /// Restore the default button mapping for the device in `slot`
+    pub fn clear_button_remap(&mut self, slot: usize) {
+        if let Some(gp) = self.gamepads.iter_mut().find(|g| g.slot == slot) {
+            if self.button_remaps.remove(&gp.name).is_some() {
+                tracing::info!("Cleared button remap on '{}'", gp.name);
+                gp.state.buttons.iter_mut().for_each(|b| *b = false);
+                self.sync_joystick_state();
+            }
+        }
+    }
+
+    pub fn trigger_modes(&self) -> TriggerModes {
+        self.trigger_modes
+    }
+
+    /// Choose how triggers are sent for Xbox (`is_xbox`) or other devices.
+    /// Affected controllers have their trigger inputs released so nothing
+    /// is left stuck on the channel that stops updating.
+    pub fn set_trigger_mode(&mut self, is_xbox: bool, mode: TriggerMode) {
+        let current = if is_xbox {
+            &mut self.trigger_modes.xbox
+        } else {
+            &mut self.trigger_modes.generic
+        };
+        if *current == mode {
+            return;
+        }
+        *current = mode;
+        tracing::info!(
+            "{} trigger mode: {mode:?}",
+            if is_xbox { "Xbox" } else { "Generic" }
+        );
+        for gp in self.gamepads.iter_mut().filter(|g| g.is_xbox == is_xbox) {
+            for axis in [Axis::LeftZ, Axis::RightZ] {
+                if let Some(value) =
+                    mapped_axis_index(&self.input_mappings, &gp.name, axis, gp.is_xbox)
+                        .and_then(|idx| gp.state.axes.get_mut(idx))
+                {
+                    *value = 0.0;
+                }
+            }
+            // Only the trigger buttons, at their remapped outputs; held face
+            // buttons stay pressed
+            for button in [Button::LeftTrigger2, Button::RightTrigger2] {
+                let Some(idx) = mapped_button_index(&self.input_mappings, &gp.name, button) else {
+                    continue;
+                };
+                let idx = self.button_remaps.get(&gp.name).map_or(idx, |remap| remap.apply(idx));
+                if let Some(pressed) = gp.state.buttons.get_mut(idx) {
+                    *pressed = false;
+                }
+            }
+        }
+        self.sync_joystick_state();
+    }
+
+    /// Add a virtual device driven by `set_virtual_axis`/`set_virtual_button`,
+    /// in `slot` if given (must be free) or the first available one.
+    /// Returns the slot it landed in.
+    pub fn create_virtual(&mut self, slot: Option<usize>) -> Result<usize, String> {
+        let slot = match slot {
+            Some(slot) if slot >= 6 => return Err(format!("Invalid joystick slot {slot}")),
+            Some(slot) if self.gamepads.iter().any(|g| g.slot == slot) => {
+                return Err(format!("Slot {slot} is already in use"));
+            }
+            Some(slot) => slot,
+            None => self
+                .claim_locked_slot(None, VIRTUAL_GAMEPAD_NAME)
+                .unwrap_or_else(|| self.first_available_slot()),
+        };
+        if slot >= 6 {
+            return Err("All joystick slots are in use".to_string());
+        }
+        self.gamepads.push(TrackedGamepad {
+            gilrs_id: None,
+            name: VIRTUAL_GAMEPAD_NAME.to_string(),
+            uuid: None,
+            slot,
+            state: JoystickState::default(),
+            dpad_up: false,
+            dpad_right: false,
+            dpad_down: false,
+            dpad_left: false,
+            hat_x: 0.0,
+            hat_y: 0.0,
+            battery: None,
+            is_xbox: false,
+            slewed_axes: Vec::new(),
+        });
+        tracing::info!("Virtual gamepad created (slot {slot})");
+        self.sync_joystick_state();
+        Ok(slot)
+    }
+
+    /// Remove the virtual device in `slot`. Returns false if there is none.
+    pub fn destroy_virtual(&mut self, slot: usize) -> bool {
+        let before = self.gamepads.len();
+        self.gamepads.retain(|g| !(g.gilrs_id.is_none() && g.slot == slot));
+        if self.gamepads.len() == before {
+            return false;
+        }
+        tracing::info!("Virtual gamepad removed (slot {slot})");
+        self.sync_joystick_state();
+        true
+    }
+
+    fn virtual_in_slot(&mut self, slot: usize) -> Result<&mut TrackedGamepad, String> {
+        self.gamepads
+            .iter_mut()
+            .find(|g| g.gilrs_id.is_none() && g.slot == slot)
+            .ok_or_else(|| format!("No virtual gamepad in slot {slot}"))
+    }
+
+    /// Set an axis (clamped to -1..1) on the virtual device in `slot`
+    pub fn set_virtual_axis(&mut self, slot: usize, axis: usize, value: f32) -> Result<(), String> {
+        let gp = self.virtual_in_slot(slot)?;
+        if axis >= gp.state.axes.len() {
+            gp.state.axes.resize(axis + 1, 0.0);
+        }
+        gp.state.axes[axis] = value.clamp(-1.0, 1.0);
+        self.sync_joystick_state();
+        Ok(())
+    }
+
+    /// Press or release a button on the virtual device in `slot`
+    pub fn set_virtual_button(
+        &mut self,
+        slot: usize,
+        button: usize,
+        pressed: bool,
+    ) -> Result<(), String> {
+        let gp = self.virtual_in_slot(slot)?;
+        if button >= gp.state.buttons.len() {
+            gp.state.buttons.resize(button + 1, false);
+        }
+        gp.state.buttons[button] = pressed;
+        self.sync_joystick_state();
+        Ok(())
+    }
+
+    /// Limit how far any axis of the device in `slot` may move per 20ms
+    /// poll; None removes the limit. Follows the device by name. Returns
+    /// false if the slot is empty.
+    pub fn set_slew_limit(&mut self, slot: usize, max_delta: Option<f32>) -> bool {
+        let Some(gp) = self.gamepads.iter_mut().find(|g| g.slot == slot) else {
+            return false;
+        };
+        match max_delta {
+            Some(limit) => {
+                tracing::info!("Slew limit on '{}': {limit} per tick", gp.name);
+                if !self.slew_limits.contains_key(&gp.name) {
+                    // Start from the current stick position, not from rest
+                    gp.slewed_axes.clone_from(&gp.state.axes);
+                }
+                self.slew_limits.insert(gp.name.clone(), limit);
+            }
+            None => {
+                if self.slew_limits.remove(&gp.name).is_some() {
+                    tracing::info!("Cleared slew limit on '{}'", gp.name);
+                }
+            }
+        }
+        self.sync_joystick_state();
+        true
+    }
+
     /// Get locked slots info for the frontend (slot → device name)
-    pub fn get_locked_slots(&self) -> &std::collections::HashMap<usize, String> {
-        &self.locked_slots
+    pub fn get_locked_slots(&self) -> std::collections::HashMap<usize, String> {
+        self.locked_slots
+            .iter()
+            .map(|(&slot, lock)| (slot, lock.name.clone()))
+            .collect()
+    }
+
+    /// Whether physical gamepads can be used on this system
+    pub fn gamepad_available(&self) -> bool {
+        self.gilrs.is_some()
     }
 
     pub fn gamepad_count(&self) -> usize {
         self.gamepads.len()
     }
+
+    /// Replace the per-device index overrides. Takes effect from the next
+    /// input event; values already held stay at their old indices until then.
+    pub fn set_input_mappings(&mut self, mappings: InputMappings) {
+        self.input_mappings = mappings;
+    }
+
+    pub fn input_mappings(&self) -> &InputMappings {
+        &self.input_mappings
+    }
+
+    /// Add an imported SDL mapping to the table. It is stored under the name
+    /// of a connected device with the mapping's GUID, since that is the name
+    /// its input arrives under, else under the name in the mapping.
+    pub fn import_sdl_mapping(&mut self, sdl: SdlMapping) -> SdlImport {
+        let device = self
+            .gamepads
+            .iter()
+            .find(|g| g.uuid == Some(sdl.guid))
+            .map_or(sdl.name, |g| g.name.clone());
+        tracing::info!("Imported SDL mapping for '{device}'");
+        let mapped = sdl.mapping.axes.len() + sdl.mapping.buttons.len();
+        self.input_mappings.insert(device.clone(), sdl.mapping);
+        SdlImport { device, mapped, skipped: sdl.skipped }
+    }
+
+    /// Set or clear (None) the range calibration of one axis of the device in
+    /// `slot`. Calibrations follow the device by name. Returns false if the
+    /// slot is empty.
+    pub fn set_axis_calibration(
+        &mut self,
+        slot: usize,
+        axis: usize,
+        calibration: Option<AxisCalibration>,
+    ) -> bool {
+        let Some(gp) = self.gamepads.iter().find(|g| g.slot == slot) else {
+            return false;
+        };
+        match calibration {
+            Some(cal) => {
+                self.calibrations.entry(gp.name.clone()).or_default().insert(axis, cal);
+            }
+            None => {
+                if let Some(axes) = self.calibrations.get_mut(&gp.name) {
+                    axes.remove(&axis);
+                    if axes.is_empty() {
+                        self.calibrations.remove(&gp.name);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Start recording the raw range of every axis of the device in `slot`
+    pub fn start_calibration_capture(&mut self, slot: usize) -> Result<(), String> {
+        let gp = self
+            .gamepads
+            .iter()
+            .find(|g| g.slot == slot && g.gilrs_id.is_some())
+            .ok_or_else(|| format!("No physical gamepad in slot {slot}"))?;
+        tracing::info!("Capturing axis calibration for '{}'", gp.name);
+        self.capture = Some(AxisCapture::new(gp.name.clone()));
+        Ok(())
+    }
+
+    /// End the capture and apply calibrations for the axes that moved.
+    /// Returns them by axis index.
+    pub fn finish_calibration_capture(&mut self) -> Result<AxisCalibrations, String> {
+        let capture = self.capture.take().ok_or("No calibration capture running")?;
+        let captured = capture.finish();
+        if captured.is_empty() {
+            return Err("No axis moved far enough to calibrate".into());
+        }
+        self.calibrations
+            .entry(capture.device)
+            .or_default()
+            .extend(captured.iter().map(|(&axis, &cal)| (axis, cal)));
+        Ok(captured)
+    }
+
+    /// Current per-device settings, for saving as a driver profile
+    pub fn settings(&self) -> GamepadSettings {
+        GamepadSettings {
+            button_remaps: self.button_remaps.clone(),
+            layout_overrides: self.layout_overrides.clone(),
+            slot_locks: self.locked_slots.clone(),
+            slew_limits: self.slew_limits.clone(),
+            trigger_modes: self.trigger_modes,
+            calibrations: self.calibrations.clone(),
+        }
+    }
+
+    /// Replace all per-device settings (loading a driver profile) and move
+    /// connected devices into the slots the profile locks for them, matched
+    /// by UUID then name. Unlocked devices keep their slot where possible.
+    pub fn apply_settings(&mut self, settings: GamepadSettings) {
+        self.button_remaps = settings.button_remaps;
+        self.layout_overrides = settings.layout_overrides;
+        self.locked_slots = settings.slot_locks;
+        self.slew_limits = settings.slew_limits;
+        self.trigger_modes = settings.trigger_modes;
+        self.calibrations = settings.calibrations;
+
+        let mut occupied = std::collections::HashSet::new();
+        let mut unplaced = Vec::new();
+        for i in 0..self.gamepads.len() {
+            let (uuid, name) = (self.gamepads[i].uuid, self.gamepads[i].name.clone());
+            match find_locked_slot(&self.locked_slots, &occupied, uuid, &name) {
+                Some(slot) => {
+                    if let Some(lock) = self.locked_slots.get_mut(&slot) {
+                        if lock.uuid.is_none() {
+                            lock.uuid = uuid;
+                        }
+                    }
+                    self.gamepads[i].slot = slot;
+                    occupied.insert(slot);
+                }
+                None => unplaced.push(i),
+            }
+        }
+        for i in unplaced {
+            let current = self.gamepads[i].slot;
+            let free = |s: &usize| !occupied.contains(s) && !self.locked_slots.contains_key(s);
+            let slot = if free(&current) {
+                current
+            } else {
+                // Fall back to a reserved slot rather than sharing one
+                (0..6)
+                    .find(free)
+                    .or_else(|| (0..6).find(|s| !occupied.contains(s)))
+                    .unwrap_or(current)
+            };
+            self.gamepads[i].slot = slot;
+            occupied.insert(slot);
+        }
+        self.sync_joystick_state();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Manager with no physical devices, whatever the host has plugged in
+    fn manager() -> GamepadManager {
+        let mut manager = GamepadManager::new(Arc::default(), Arc::default(), Arc::default());
+        manager.gilrs = None;
+        manager.gamepads.clear();
+        manager
+    }
+
+    fn tracked(name: &str, slot: usize, is_xbox: bool) -> TrackedGamepad {
+        TrackedGamepad {
+            gilrs_id: None,
+            name: name.to_string(),
+            uuid: None,
+            slot,
+            state: JoystickState::default(),
+            dpad_up: false,
+            dpad_right: false,
+            dpad_down: false,
+            dpad_left: false,
+            hat_x: 0.0,
+            hat_y: 0.0,
+            battery: None,
+            is_xbox,
+            slewed_axes: Vec::new(),
+        }
+    }
+
+    fn update(axis: f32, pressed: bool) -> GamepadUpdate {
+        GamepadUpdate {
+            gamepads: vec![GamepadInfo {
+                id: 0,
+                name: "Xbox Controller".into(),
+                slot: 0,
+                axes: vec![axis, 0.0],
+                buttons: vec![pressed, false],
+                povs: vec![-1],
+                locked: false,
+                battery: None,
+                button_remap: ButtonRemap::default(),
+            }],
+        }
+    }
+
+    #[test]
+    fn idle_updates_wait_for_the_heartbeat() {
+        let idle = update(0.0, false);
+        let soon = Duration::from_millis(100);
+        assert!(should_emit_update(None, &idle, soon));
+        assert!(!should_emit_update(Some(&idle), &update(0.0, false), soon));
+        assert!(should_emit_update(Some(&idle), &update(0.0, false), UPDATE_HEARTBEAT));
+        assert!(should_emit_update(Some(&idle), &update(0.0, true), soon));
+        assert!(should_emit_update(Some(&idle), &update(0.5, false), soon));
+    }
+
+    #[test]
+    fn hat_axes_map_to_eight_pov_angles() {
+        let cases = [
+            ((0.0, 1.0), 0),
+            ((1.0, 1.0), 45),
+            ((1.0, 0.0), 90),
+            ((1.0, -1.0), 135),
+            ((0.0, -1.0), 180),
+            ((-1.0, -1.0), 225),
+            ((-1.0, 0.0), 270),
+            ((-1.0, 1.0), 315),
+            ((0.0, 0.0), -1),
+            // Below the threshold counts as centered
+            ((0.3, -0.3), -1),
+        ];
+        for ((x, y), angle) in cases {
+            assert_eq!(hat_to_pov(x, y), angle, "hat ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn xbox_triggers_move_axes_but_not_buttons() {
+        let mut manager = manager();
+        manager.gamepads.push(tracked("Xbox Controller", 0, true));
+
+        manager.handle_axis(0, Axis::LeftZ, 0.25);
+        manager.handle_axis(0, Axis::RightZ, 0.8);
+        manager.handle_button(0, Button::LeftTrigger2, 1.0);
+        manager.handle_button(0, Button::RightTrigger2, 1.0);
+        let state = &manager.gamepads[0].state;
+        assert_eq!(state.axes[2..4], [0.25, 0.8]);
+        assert!(!state.buttons[10] && !state.buttons[11]);
+
+        // Opting into both restores the digital trigger buttons
+        manager.set_trigger_mode(true, TriggerMode::Both);
+        manager.handle_button(0, Button::RightTrigger2, 1.0);
+        assert!(manager.gamepads[0].state.buttons[11]);
+    }
+
+    #[test]
+    fn identical_controllers_return_to_their_locked_slots() {
+        let mut manager = manager();
+        let (a, b) = ([1; 16], [2; 16]);
+        let xbox = |slot, uuid| TrackedGamepad {
+            uuid: Some(uuid),
+            ..tracked("Xbox Controller", slot, true)
+        };
+        for (slot, uuid) in [(0, a), (3, b)] {
+            manager.gamepads.push(xbox(slot, uuid));
+            manager.lock_slot(slot);
+        }
+        manager.gamepads.clear();
+
+        // Reconnecting in the opposite order still finds each device's slot
+        assert_eq!(manager.claim_locked_slot(Some(b), "Xbox Controller"), Some(3));
+        manager.gamepads.push(xbox(3, b));
+        assert_eq!(manager.claim_locked_slot(Some(a), "Xbox Controller"), Some(0));
+        assert_eq!(manager.claim_locked_slot(Some([9; 16]), "Xbox Controller"), None);
+
+        // A name-only lock adopts the UUID of the first device to claim it
+        manager.locked_slots.insert(5, SlotLock { name: "Flight Stick".into(), uuid: None });
+        assert_eq!(manager.claim_locked_slot(Some([7; 16]), "Flight Stick"), Some(5));
+        assert_eq!(manager.locked_slots[&5].uuid, Some([7; 16]));
+        assert_eq!(manager.claim_locked_slot(Some([8; 16]), "Flight Stick"), None);
+    }
+
+    #[test]
+    fn virtual_inputs_reach_the_shared_joystick_state() {
+        let mut manager = manager();
+        manager.gamepads.push(tracked("Flight Stick", 0, false));
+        let slot = manager.create_virtual(None).unwrap();
+        assert_eq!(slot, 1);
+        assert!(manager.create_virtual(Some(0)).is_err());
+
+        manager.set_virtual_axis(slot, 1, -1.5).unwrap();
+        manager.set_virtual_button(slot, 17, true).unwrap();
+        // The protocol loop builds each packet from this shared state
+        let sent = manager.joystick_state.read()[slot].clone();
+        assert_eq!(sent.axes[1], -1.0);
+        assert_eq!(sent.buttons.len(), 18);
+        assert!(sent.buttons[17]);
+        assert_eq!(manager.descriptors.read()[slot].name, VIRTUAL_GAMEPAD_NAME);
+
+        assert!(manager.destroy_virtual(slot));
+        assert!(manager.set_virtual_axis(slot, 0, 0.5).is_err());
+        assert_eq!(manager.joystick_state.read().len(), 1);
+    }
+
+    #[test]
+    fn slew_limited_axes_ramp_toward_the_stick() {
+        let mut manager = manager();
+        manager.gamepads.push(tracked("Flight Stick", 0, false));
+        assert!(manager.set_slew_limit(0, Some(0.5)));
+
+        manager.handle_axis(0, Axis::LeftStickX, 1.0);
+        assert!(manager.step_slew());
+        assert_eq!(manager.gamepads[0].slewed_axes[0], 0.5);
+        assert!(manager.step_slew());
+        assert!(!manager.step_slew());
+        manager.sync_joystick_state();
+        assert_eq!(manager.joystick_state.read()[0].axes[0], 1.0);
+
+        // Without a limit the raw value is sent straight away
+        assert!(manager.set_slew_limit(0, None));
+        manager.handle_axis(0, Axis::LeftStickX, -1.0);
+        assert!(!manager.step_slew());
+        manager.sync_joystick_state();
+        assert_eq!(manager.joystick_state.read()[0].axes[0], -1.0);
+    }
+
+    #[test]
+    fn driver_profile_round_trips_and_restores_slots() {
+        let xbox = |slot| TrackedGamepad {
+            uuid: Some([4; 16]),
+            ..tracked("Xbox Controller", slot, true)
+        };
+        let mut saved = manager();
+        saved.gamepads.push(xbox(2));
+        saved.remap_button(2, 0, 3);
+        saved.set_layout(2, JoystickLayout { axes: 4, buttons: 10, povs: 2 });
+        saved.set_slew_limit(2, Some(0.1));
+        saved.set_trigger_mode(true, TriggerMode::Both);
+        saved.lock_slot(2);
+
+        let profile = crate::config::DriverProfile {
+            name: "Alex".into(),
+            gamepad: saved.settings(),
+        };
+        let json = serde_json::to_string(&profile).unwrap();
+        let restored: crate::config::DriverProfile = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, profile);
+
+        // The same controller on another machine, plugged into slot 0
+        let mut loaded = manager();
+        loaded.gamepads.push(xbox(0));
+        loaded.apply_settings(restored.gamepad);
+        assert_eq!(loaded.gamepads[0].slot, 2);
+        assert_eq!(loaded.settings(), profile.gamepad);
+        assert_eq!(loaded.joystick_state.read()[2].povs.len(), 2);
+    }
+
+    #[test]
+    fn axis_noise_is_not_a_change() {
+        let rest = update(0.0, false);
+        let soon = Duration::from_millis(20);
+        assert!(!updates_differ(&rest, &update(AXIS_CHANGE_EPSILON * 0.5, false)));
+        assert!(!should_emit_update(Some(&rest), &update(-0.004, false), soon));
+        assert!(should_emit_update(Some(&rest), &update(AXIS_CHANGE_EPSILON * 2.0, false), soon));
+
+        // Anything other than an axis counts however small
+        let mut locked = update(0.0, false);
+        locked.gamepads[0].locked = true;
+        assert!(updates_differ(&rest, &locked));
+        let mut moved = update(0.0, false);
+        moved.gamepads[0].slot = 3;
+        assert!(updates_differ(&rest, &moved));
+        assert!(updates_differ(&rest, &GamepadUpdate { gamepads: Vec::new() }));
+    }
+
+    #[test]
+    fn loaded_mapping_overrides_built_in_index() {
+        use crate::gamepad::input_map::{load_mappings, save_mappings};
+
+        let path = std::env::temp_dir()
+            .join(format!("ds-input-map-{}", std::process::id()))
+            .join("input_mappings.json");
+        let table = InputMappings::parse(
+            r#"{"Flight Stick": {"axes": {"LeftStickX": 3}, "buttons": {"South": 5}}}"#,
+        )
+        .unwrap();
+        save_mappings(&path, &table).unwrap();
+
+        let mut manager = manager();
+        manager.set_input_mappings(load_mappings(&path).unwrap());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        manager.gamepads.push(tracked("Flight Stick", 0, false));
+        manager.gamepads.push(tracked("Other Stick", 1, false));
+        for index in 0..2 {
+            manager.handle_axis(index, Axis::LeftStickX, 0.5);
+            manager.handle_button(index, Button::South, 1.0);
+        }
+
+        let mapped = &manager.gamepads[0].state;
+        assert_eq!(mapped.axes.get(3), Some(&0.5));
+        assert_ne!(mapped.axes.first(), Some(&0.5));
+        assert_eq!(mapped.buttons.get(5), Some(&true));
+        assert_ne!(mapped.buttons.first(), Some(&true));
+        // A device the file doesn't name keeps the built-in indices
+        let default = &manager.gamepads[1].state;
+        assert_eq!(default.axes.first(), Some(&0.5));
+        assert_eq!(default.buttons.first(), Some(&true));
+    }
+
+    #[test]
+    fn manager_without_gilrs_runs_with_no_devices() {
+        let mut manager = manager();
+        assert!(!manager.gamepad_available());
+        assert!(manager.poll().is_none());
+        assert!(manager.rescan().gamepads.is_empty());
+        assert!(manager.get_gamepad_update().gamepads.is_empty());
+        assert_eq!(manager.gamepad_count(), 0);
+
+        // Virtual joysticks still work, and a rescan leaves them alone
+        let slot = manager.create_virtual(None).unwrap();
+        assert!(manager.poll().is_none());
+        assert_eq!(manager.rescan().gamepads.len(), 1);
+        assert_eq!(manager.get_gamepad_update().gamepads[0].slot, slot);
+    }
+
+    #[test]
+    fn reconcile_adds_untracked_and_drops_missing_devices() {
+        assert_eq!(reconcile_devices(&[1, 2], &[2, 3]), (vec![3], vec![1]));
+        assert_eq!(reconcile_devices(&[1, 2], &[2, 1]), (vec![], vec![]));
+        assert_eq!(reconcile_devices(&[], &[4]), (vec![4], vec![]));
+        assert_eq!(reconcile_devices::<u8>(&[5], &[]), (vec![], vec![5]));
+    }
+
+    #[test]
+    fn resume_is_a_gap_well_past_the_loop_interval() {
+        let last = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let tick = Duration::from_millis(20);
+        assert_eq!(detect_resume(last, last + tick, tick), None);
+        // A busy loop running a few seconds late is not a resume
+        assert_eq!(detect_resume(last, last + tick + RESUME_GAP, tick), None);
+        let slept = Duration::from_secs(600);
+        assert_eq!(detect_resume(last, last + slept, tick), Some(slept));
+        // The clock stepping backwards isn't a resume either
+        assert_eq!(detect_resume(last, last - slept, tick), None);
+    }
+
+    #[test]
+    fn two_hat_device_reports_both_povs() {
+        let mut manager = manager();
+        let mut stick = tracked("Flight Stick", 0, false);
+        stick.state.povs = vec![90, 270];
+        manager.gamepads.push(stick);
+        manager.sync_joystick_state();
+
+        assert_eq!(manager.get_gamepad_update().gamepads[0].povs, vec![90, 270]);
+        assert_eq!(manager.joystick_state.read()[0].pov_count(), 2);
+        assert_eq!(manager.descriptors.read()[0].layout.povs, 2);
+
+        // A layout override changes what the UI and the packet both see
+        manager.set_layout(0, JoystickLayout { axes: 6, buttons: 16, povs: 3 });
+        assert_eq!(manager.get_gamepad_update().gamepads[0].povs, vec![90, 270, -1]);
+        assert_eq!(manager.joystick_state.read()[0].povs, vec![90, 270, -1]);
+        assert_eq!(manager.descriptors.read()[0].layout.povs, 3);
+    }
 }
@@ -1,3 +1,283 @@
-// Gamepad slot mapping utilities
-// Currently handled directly in GamepadManager.
-// This module is reserved for future custom mapping profiles.
+// Gamepad button remapping, trigger handling and axis shaping
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Per-device button remap, kept as a permutation so no two physical buttons
+/// ever land on the same output index. Only non-identity entries are stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ButtonRemap {
+    /// Physical button index → output button index
+    map: BTreeMap<usize, usize>,
+}
+
+impl ButtonRemap {
+    /// Output index for a physical button
+    pub fn apply(&self, index: usize) -> usize {
+        self.map.get(&index).copied().unwrap_or(index)
+    }
+
+    /// Send physical button `from` to output `to`. Whichever button was
+    /// feeding `to` takes over `from`'s old output, so the mapping stays
+    /// collision-free.
+    pub fn set(&mut self, from: usize, to: usize) {
+        let old_output = self.apply(from);
+        let displaced = self
+            .map
+            .iter()
+            .find(|&(_, &out)| out == to)
+            .map(|(&src, _)| src)
+            .unwrap_or(to);
+        self.insert(from, to);
+        if displaced != from {
+            self.insert(displaced, old_output);
+        }
+    }
+
+    fn insert(&mut self, from: usize, to: usize) {
+        if from == to {
+            self.map.remove(&from);
+        } else {
+            self.map.insert(from, to);
+        }
+    }
+}
+
+/// How analog triggers (`LeftTrigger2`/`RightTrigger2`) reach robot code.
+/// gilrs reports them both as an axis and as a digital button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerMode {
+    /// Axis only; the digital trigger buttons are suppressed
+    Axis,
+    /// Button only; the trigger axes stay at rest
+    Button,
+    /// Both, as gilrs reports them
+    Both,
+}
+
+impl TriggerMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode.to_ascii_lowercase().as_str() {
+            "axis" => Ok(TriggerMode::Axis),
+            "button" => Ok(TriggerMode::Button),
+            "both" => Ok(TriggerMode::Both),
+            _ => Err(format!("Unknown trigger mode: {mode}")),
+        }
+    }
+
+    pub fn sends_axis(self) -> bool {
+        self != TriggerMode::Button
+    }
+
+    pub fn sends_button(self) -> bool {
+        self != TriggerMode::Axis
+    }
+}
+
+/// Trigger mode per device type. Xbox controllers follow WPILib's
+/// `XboxController` (triggers are axes 2 and 3, never buttons); other
+/// devices keep everything gilrs reports, since their "triggers" are often
+/// real buttons or a throttle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerModes {
+    pub xbox: TriggerMode,
+    pub generic: TriggerMode,
+}
+
+impl Default for TriggerModes {
+    fn default() -> Self {
+        Self { xbox: TriggerMode::Axis, generic: TriggerMode::Both }
+    }
+}
+
+impl TriggerModes {
+    pub fn for_device(&self, is_xbox: bool) -> TriggerMode {
+        if is_xbox { self.xbox } else { self.generic }
+    }
+}
+
+/// Default per-tick slew limit: a full-scale jump (-1 → 1) in one tick, so
+/// no limiting happens
+pub const DEFAULT_SLEW_LIMIT: f32 = 2.0;
+
+/// Move `prev` toward `target` by at most `max_delta`
+pub fn apply_slew(prev: f32, target: f32, max_delta: f32) -> f32 {
+    prev + (target - prev).clamp(-max_delta, max_delta)
+}
+
+/// Map a raw axis reading so the physical `min`..`center`..`max` becomes
+/// -1..0..1. Each side of the center is scaled on its own, so an off-center
+/// neutral reads 0 and a stick that falls short of full range still reaches
+/// ±1.
+pub fn calibrate_axis(raw: f32, min: f32, center: f32, max: f32) -> f32 {
+    let value = if raw >= center {
+        if max > center { (raw - center) / (max - center) } else { 0.0 }
+    } else if center > min {
+        (raw - center) / (center - min)
+    } else {
+        0.0
+    };
+    value.clamp(-1.0, 1.0)
+}
+
+/// Physical range of one axis on a worn controller, as raw readings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AxisCalibration {
+    pub min: f32,
+    pub center: f32,
+    pub max: f32,
+}
+
+impl AxisCalibration {
+    pub fn new(min: f32, center: f32, max: f32) -> Result<Self, String> {
+        if !(min <= center && center <= max && min < max) {
+            return Err(format!(
+                "Calibration needs min <= center <= max with min < max, got {min}/{center}/{max}"
+            ));
+        }
+        Ok(Self { min, center, max })
+    }
+
+    pub fn apply(&self, raw: f32) -> f32 {
+        calibrate_axis(raw, self.min, self.center, self.max)
+    }
+}
+
+/// Smallest range an axis must sweep during capture to be calibrated
+const MIN_CAPTURED_RANGE: f32 = 0.2;
+
+/// A captured axis whose rest is within this fraction of its range from one
+/// end is a trigger or throttle, not a centered stick
+const REST_AT_END_FRACTION: f32 = 0.1;
+
+/// Records the raw extremes each axis of one device reaches while the driver
+/// sweeps the sticks. The last reading is taken as the center, so sticks
+/// should be released before the capture ends. Axes that rest at one end
+/// (triggers, throttles) are calibrated end to end instead, with the center
+/// halfway, so their full travel still spans -1..1.
+#[derive(Debug, Clone)]
+pub struct AxisCapture {
+    pub device: String,
+    /// Axis index → (min, max, last)
+    seen: BTreeMap<usize, (f32, f32, f32)>,
+}
+
+impl AxisCapture {
+    pub fn new(device: String) -> Self {
+        Self { device, seen: BTreeMap::new() }
+    }
+
+    pub fn record(&mut self, axis: usize, raw: f32) {
+        let entry = self.seen.entry(axis).or_insert((raw, raw, raw));
+        entry.0 = entry.0.min(raw);
+        entry.1 = entry.1.max(raw);
+        entry.2 = raw;
+    }
+
+    /// Calibrations for every axis that moved far enough
+    pub fn finish(&self) -> BTreeMap<usize, AxisCalibration> {
+        self.seen
+            .iter()
+            .filter(|(_, &(min, max, _))| max - min >= MIN_CAPTURED_RANGE)
+            .filter_map(|(&axis, &(min, max, last))| {
+                let margin = (max - min) * REST_AT_END_FRACTION;
+                let center = if last <= min + margin || last >= max - margin {
+                    (min + max) / 2.0
+                } else {
+                    last
+                };
+                Some((axis, AxisCalibration::new(min, center, max).ok()?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output index of every physical button 0..16
+    fn outputs(remap: &ButtonRemap) -> Vec<usize> {
+        (0..16).map(|i| remap.apply(i)).collect()
+    }
+
+    fn assert_no_collisions(remap: &ButtonRemap) {
+        let mut seen = outputs(remap);
+        seen.sort_unstable();
+        assert_eq!(seen, (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn remapped_press_lands_on_its_output_without_collisions() {
+        let mut remap = ButtonRemap::default();
+        remap.set(0, 3);
+        assert_eq!(remap.apply(0), 3);
+        // The button that fed 3 takes over 0's old output
+        assert_eq!(remap.apply(3), 0);
+        assert_no_collisions(&remap);
+
+        remap.set(1, 3);
+        assert_eq!(remap.apply(1), 3);
+        assert_eq!(remap.apply(0), 1);
+        assert_eq!(remap.apply(3), 0);
+        assert_no_collisions(&remap);
+
+        // Mapping everything back to itself leaves nothing stored
+        remap.set(1, 1);
+        remap.set(0, 0);
+        assert_eq!(remap, ButtonRemap::default());
+    }
+
+    #[test]
+    fn slew_ramps_up_and_down_by_at_most_the_limit() {
+        let mut value = 0.0;
+        let ramp: Vec<f32> = (0..5)
+            .map(|_| {
+                value = apply_slew(value, 1.0, 0.3);
+                value
+            })
+            .collect();
+        for (got, want) in ramp.iter().zip([0.3, 0.6, 0.9, 1.0, 1.0]) {
+            assert!((got - want).abs() < 1e-6, "{ramp:?}");
+        }
+        assert!((apply_slew(1.0, -1.0, 0.3) - 0.7).abs() < 1e-6);
+        assert_eq!(apply_slew(0.2, 0.25, 0.3), 0.25);
+
+        // The default limit covers a full-scale jump, so nothing is limited
+        assert_eq!(apply_slew(-1.0, 1.0, DEFAULT_SLEW_LIMIT), 1.0);
+        assert_eq!(apply_slew(1.0, -1.0, DEFAULT_SLEW_LIMIT), -1.0);
+    }
+
+    #[test]
+    fn calibration_handles_asymmetric_ranges_and_off_center_rest() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+        // Stick resting at 0.1 that only reaches -0.7 and 0.9
+        let cal = AxisCalibration::new(-0.7, 0.1, 0.9).unwrap();
+        assert_eq!(cal.apply(0.1), 0.0);
+        assert!(close(cal.apply(0.9), 1.0));
+        assert!(close(cal.apply(-0.7), -1.0));
+        assert!(close(cal.apply(0.5), 0.5));
+        assert!(close(cal.apply(-0.3), -0.5));
+        assert_eq!(cal.apply(1.0), 1.0);
+        assert!(AxisCalibration::new(0.5, 0.0, 1.0).is_err());
+
+        let mut capture = AxisCapture::new("Worn Pad".into());
+        for raw in [0.1, -0.7, 0.9, 0.4, 0.1] {
+            capture.record(0, raw);
+        }
+        // A trigger resting at its low end is calibrated end to end
+        for raw in [-0.8, 1.0, -0.78] {
+            capture.record(2, raw);
+        }
+        // Barely moved: left uncalibrated
+        capture.record(4, 0.0);
+        capture.record(4, 0.1);
+        let captured = capture.finish();
+        assert_eq!(captured.keys().copied().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(captured[&0], cal);
+        assert!(close(captured[&2].center, 0.1));
+        assert!(close(captured[&2].apply(-0.8), -1.0));
+    }
+}
@@ -1,2 +1,4 @@
+pub mod input_map;
+pub mod latency;
 pub mod manager;
 pub mod mapping;
@@ -1,29 +1,110 @@
 mod commands;
+mod config;
 mod discovery;
+mod event_server;
 mod events;
 mod gamepad;
 mod log_writer;
 mod logging;
 mod network;
 mod protocol;
+mod replay;
 mod system_info;
 
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use parking_lot::{Mutex, RwLock};
 use tauri::Manager;
 use tokio::sync::{mpsc, watch};
 
+use config::DsConfig;
+use gamepad::latency::SharedLatencyProbe;
 use gamepad::manager::GamepadManager;
-use protocol::connection::{protocol_loop, DsCommand, DsEvent};
-use protocol::types::{ConsoleMessage, JoystickState, PowerData, VersionInfo};
+use protocol::connection::{
+    protocol_loop, DsCommand, DsEvent, SharedOutboundPacket, SharedRobotSnapshot,
+};
+use log_writer::{LogControl, SharedLogSession};
+use replay::ReplayRecord;
+use system_info::SystemInfoHistory;
+use protocol::timeline::SessionTimeline;
+use protocol::types::{
+    ConsoleMessage, JoystickDescriptor, JoystickState, PowerData, RadioEvent, SocketStatus, VersionInfo,
+};
 
 pub struct AppState {
     pub cmd_tx: mpsc::Sender<DsCommand>,
+    pub event_tx: mpsc::Sender<DsEvent>,
     pub target_ip_tx: watch::Sender<String>,
     pub gamepad_manager: Mutex<GamepadManager>,
+    /// Only send periodic gamepad updates when values change (plus a slow heartbeat)
+    pub gamepad_changes_only: AtomicBool,
+    /// Time between periodic gamepad updates for the UI
+    pub gamepad_ui_interval_ms: AtomicU64,
+    /// Control and comms transitions recorded by the protocol loop
+    pub timeline: Arc<RwLock<SessionTimeline>>,
+    /// Log files written by this session (for export)
+    pub log_session: SharedLogSession,
+    pub log_control_tx: mpsc::Sender<LogControl>,
+    /// Collapse repeated console messages into one line with a count
+    pub console_coalesce: Arc<AtomicBool>,
+    /// Minimum console severity shown in the UI (and optionally logged)
+    pub console_filter: Arc<RwLock<logging::ConsoleFilter>>,
+    /// Prefixes that promote standard output lines to warnings/errors
+    pub severity_patterns: Arc<RwLock<logging::SeverityPatterns>>,
+    /// UDP socket and TCP console state published by the network tasks
+    pub socket_status: Arc<RwLock<SocketStatus>>,
+    /// A recorded session is being replayed; live events are held back
+    pub replaying: Arc<AtomicBool>,
+    /// Write frontend events to a session recording. Off by default.
+    pub session_recording: Arc<AtomicBool>,
+    /// Stops the running session replay
+    pub replay_stop_tx: Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+    /// Settings persisted to `config.json` in the app data directory
+    pub config: Mutex<DsConfig>,
+    /// Gamepad → outbound packet latency measurement
+    pub latency_probe: SharedLatencyProbe,
+    /// Disable the robot when the main window loses focus
+    pub disable_on_blur: Arc<AtomicBool>,
+    /// Rate-limit high-rate events (robot state, diagnostics, packet capture)
+    /// sent to the frontend
+    pub event_throttling: Arc<AtomicBool>,
+    /// Recent host PC samples for the system info sparkline
+    pub system_info_history: SystemInfoHistory,
+    /// PC CPU temperature (°C) that triggers an overheat alert
+    pub cpu_temp_threshold: Arc<RwLock<f32>>,
+    /// Latest robot state and diagnostics from the protocol loop
+    pub robot_snapshot: SharedRobotSnapshot,
+    /// Last control packet sent, for `get_last_outbound_packet`
+    pub last_outbound: SharedOutboundPacket,
+    /// Set to true on exit so long-lived tasks return instead of being killed
+    pub shutdown_tx: watch::Sender<bool>,
+    /// Copy of the event stream for the event server
+    pub event_fanout: event_server::EventFanout,
+    /// Running JSON-lines event server, if started
+    pub event_server: Mutex<Option<event_server::EventServer>>,
+    /// Dashboards launched from the DS that may still be running
+    pub dashboards: commands::config::LaunchedDashboards,
+    /// Recent result of the installed-dashboard scan
+    pub installed_dashboards: Mutex<commands::config::DashboardCache>,
 }
 
+impl AppState {
+    /// Tell long-lived tasks (the console listener) to stop. Dropping its
+    /// console channel in turn lets the log file writer flush and return.
+    /// Also closes launched dashboards if the user asked for that.
+    pub fn shutdown_all(&self) {
+        self.shutdown_tx.send_replace(true);
+        if self.config.lock().close_dashboards_on_exit {
+            commands::config::close_all_dashboards(&self.dashboards);
+        }
+    }
+}
+
+/// Time given to the protocol loop on close to send a few disabled packets
+/// (~5 at 50Hz) before the app exits
+const SHUTDOWN_FLUSH: std::time::Duration = std::time::Duration::from_millis(100);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tracing_subscriber::fmt()
@@ -31,18 +112,71 @@ pub fn run() {
         .init();
 
     let joystick_state: Arc<RwLock<Vec<JoystickState>>> = Arc::new(RwLock::new(Vec::new()));
+    let timeline: Arc<RwLock<SessionTimeline>> = Arc::new(RwLock::new(SessionTimeline::default()));
+    let log_session = SharedLogSession::default();
+    let (log_control_tx, log_control_rx) = mpsc::channel::<LogControl>(4);
+    let console_coalesce = Arc::new(AtomicBool::new(false));
+    let console_filter = Arc::new(RwLock::new(logging::ConsoleFilter::default()));
+    let severity_patterns = Arc::new(RwLock::new(logging::SeverityPatterns::default()));
+    let socket_status: Arc<RwLock<SocketStatus>> = Arc::new(RwLock::new(SocketStatus::default()));
+    let replaying = Arc::new(AtomicBool::new(false));
+    let session_recording = Arc::new(AtomicBool::new(false));
+    let disable_on_blur = Arc::new(AtomicBool::new(false));
+    let event_throttling = Arc::new(AtomicBool::new(true));
+    let system_info_history = SystemInfoHistory::default();
+    let cpu_temp_threshold = Arc::new(RwLock::new(system_info::DEFAULT_CPU_TEMP_THRESHOLD));
 
     let (cmd_tx, cmd_rx) = mpsc::channel::<DsCommand>(64);
     let (event_tx, event_rx) = mpsc::channel::<DsEvent>(256);
+    let (event_fanout, _) = tokio::sync::broadcast::channel(event_server::FANOUT_CAPACITY);
 
-    let (target_ip_tx, target_ip_rx) = watch::channel("127.0.0.1".to_string());
+    // Empty until setup seeds it with the restored target
+    let (target_ip_tx, target_ip_rx) = watch::channel(String::new());
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let robot_snapshot = SharedRobotSnapshot::default();
+    let last_outbound = SharedOutboundPacket::default();
 
-    let gamepad_manager = GamepadManager::new(joystick_state.clone());
+    let latency_probe = SharedLatencyProbe::default();
+    let joystick_descriptors: Arc<RwLock<Vec<JoystickDescriptor>>> =
+        Arc::new(RwLock::new(Vec::new()));
+    let gamepad_manager = GamepadManager::new(
+        joystick_state.clone(),
+        latency_probe.clone(),
+        joystick_descriptors.clone(),
+    );
 
     let app_state = AppState {
         cmd_tx: cmd_tx.clone(),
+        event_tx: event_tx.clone(),
         target_ip_tx: target_ip_tx.clone(),
         gamepad_manager: Mutex::new(gamepad_manager),
+        gamepad_changes_only: AtomicBool::new(true),
+        gamepad_ui_interval_ms: AtomicU64::new(
+            1000 / gamepad::manager::DEFAULT_UI_RATE_HZ as u64,
+        ),
+        timeline: timeline.clone(),
+        log_session: log_session.clone(),
+        log_control_tx,
+        console_coalesce: console_coalesce.clone(),
+        console_filter: console_filter.clone(),
+        severity_patterns: severity_patterns.clone(),
+        socket_status: socket_status.clone(),
+        replaying: replaying.clone(),
+        session_recording: session_recording.clone(),
+        replay_stop_tx: Mutex::new(None),
+        config: Mutex::new(DsConfig::default()),
+        latency_probe: latency_probe.clone(),
+        disable_on_blur: disable_on_blur.clone(),
+        event_throttling: event_throttling.clone(),
+        system_info_history: system_info_history.clone(),
+        cpu_temp_threshold: cpu_temp_threshold.clone(),
+        robot_snapshot: robot_snapshot.clone(),
+        last_outbound: last_outbound.clone(),
+        shutdown_tx,
+        event_fanout: event_fanout.clone(),
+        event_server: Mutex::new(None),
+        dashboards: Default::default(),
+        installed_dashboards: Mutex::new(Default::default()),
     };
 
     let event_tx_console = event_tx.clone();
@@ -63,38 +197,205 @@ pub fn run() {
             commands::robot::enable_robot,
             commands::robot::disable_robot,
             commands::robot::estop_robot,
+            commands::robot::key_estop,
+            commands::robot::heartbeat,
+            commands::robot::set_watchdog_timeout,
+            commands::robot::set_disconnect_timeout,
+            commands::robot::set_disable_on_blur,
+            commands::robot::set_event_throttling,
+            commands::robot::set_battery_thresholds,
+            commands::robot::scan_roborios,
             commands::robot::set_mode,
             commands::robot::reboot_rio,
             commands::robot::restart_code,
+            commands::robot::set_request_repeat,
+            commands::robot::set_comm_version,
+            commands::robot::get_session_timeline,
+            commands::robot::get_socket_status,
+            commands::robot::get_robot_state,
+            commands::robot::get_diagnostics,
+            commands::robot::get_metrics_text,
+            commands::robot::get_last_outbound_packet,
+            commands::robot::add_monitored_robot,
+            commands::robot::remove_monitored_robot,
+            commands::robot::set_active_robot,
+            commands::robot::set_packet_capture,
+            commands::robot::set_simulated_robot,
+            commands::robot::reset_diagnostics,
+            commands::robot::set_joysticks_enabled,
+            commands::robot::set_brownout_snapshots,
             commands::config::set_team_number,
             commands::config::set_alliance,
+            commands::config::get_alliance,
+            commands::config::set_alliance_override,
             commands::config::set_target_ip,
+            commands::config::set_ip_template,
+            commands::config::get_targets,
+            commands::config::add_target,
+            commands::config::switch_target,
             commands::config::set_game_data,
+            commands::config::get_game_data_presets,
+            commands::config::save_game_data_preset,
+            commands::config::delete_game_data_preset,
+            commands::config::apply_game_data_preset,
+            commands::config::set_simulation,
+            commands::config::set_prefer_usb,
             commands::config::get_installed_dashboards,
+            commands::config::refresh_dashboards,
             commands::config::launch_dashboard,
+            commands::config::add_custom_dashboard,
+            commands::config::delete_custom_dashboard,
+            commands::config::close_dashboard,
+            commands::config::get_running_dashboards,
+            commands::config::get_wpilib_years,
+            commands::config::set_wpilib_year,
+            commands::config::set_close_dashboards_on_exit,
+            commands::logs::export_console_log,
+            commands::logs::new_log_session,
+            commands::logs::set_console_coalesce,
+            commands::logs::set_console_level,
+            commands::logs::save_console_filter,
+            commands::logs::delete_console_filter,
+            commands::logs::list_console_filters,
+            commands::logs::apply_console_filter,
+            commands::logs::set_severity_patterns,
+            commands::logs::search_log,
+            commands::logs::load_log_file,
+            commands::logs::start_session_replay,
+            commands::logs::stop_session_replay,
+            commands::logs::set_session_recording,
+            commands::logs::set_control_logging,
             commands::gamepad::get_gamepads,
+            commands::gamepad::gamepad_available,
+            commands::gamepad::rescan_gamepads,
             commands::gamepad::reorder_gamepads,
             commands::gamepad::lock_gamepad_slot,
             commands::gamepad::unlock_gamepad_slot,
+            commands::gamepad::set_gamepad_changes_only,
+            commands::gamepad::set_gamepad_ui_rate,
+            commands::gamepad::set_min_button_count,
+            commands::gamepad::set_joystick_layout,
+            commands::gamepad::remap_button,
+            commands::gamepad::clear_button_remap,
+            commands::gamepad::get_trigger_modes,
+            commands::gamepad::set_trigger_mode,
+            commands::gamepad::create_virtual_joystick,
+            commands::gamepad::destroy_virtual_joystick,
+            commands::gamepad::set_virtual_axis,
+            commands::gamepad::set_virtual_button,
+            commands::gamepad::set_slew_limit,
+            commands::gamepad::set_axis_calibration,
+            commands::gamepad::clear_axis_calibration,
+            commands::gamepad::capture_axis_calibration,
+            commands::gamepad::reload_mappings,
+            commands::gamepad::import_sdl_mapping,
+            commands::gamepad::measure_input_latency,
+            commands::gamepad::save_profile,
+            commands::gamepad::load_profile,
+            commands::gamepad::list_profiles,
+            commands::system::get_system_info_history,
+            commands::system::set_cpu_temp_threshold,
+            commands::system::get_schema_version,
+            commands::system::run_self_test,
+            commands::system::start_event_server,
+            commands::system::stop_event_server,
         ])
         .setup(move |app| {
             let app_handle = app.handle().clone();
             let js_state = joystick_state.clone();
 
+            // Restore the last-used settings; queued before the protocol loop starts
+            let app_data_dir = app.path().app_data_dir().unwrap_or_default();
+            let saved = config::load_config(&config::config_path(&app_data_dir));
+            if saved.simulation {
+                // Before the team so no mDNS discovery is started for it
+                let _ = cmd_tx.try_send(DsCommand::SetSimulation(true));
+            }
+            let _ = cmd_tx.try_send(DsCommand::SetIpResolver(saved.ip_resolver()));
+            let _ = cmd_tx.try_send(DsCommand::SetTeamNumber(saved.team_number));
+            let _ = cmd_tx.try_send(DsCommand::SetAlliance(saved.alliance));
+            // A restored simulation keeps targeting localhost, matching
+            // `restored_target`
+            if let Some(ip) = saved.target_ip.clone().filter(|_| !saved.simulation) {
+                let _ = cmd_tx.try_send(DsCommand::SetTargetIp(ip));
+            }
+            // Seed the console's target so it never starts on a placeholder
+            target_ip_tx.send_replace(saved.restored_target());
+            *app.state::<AppState>().config.lock() = saved;
+            let mappings_path = gamepad::input_map::mappings_path(&app_data_dir);
+            match gamepad::input_map::load_mappings(&mappings_path) {
+                Ok(mappings) => {
+                    app.state::<AppState>().gamepad_manager.lock().set_input_mappings(mappings)
+                }
+                Err(e) => tracing::warn!("{e}; using the built-in gamepad mapping"),
+            }
+
+            // Optional safety: disable when the operator switches away. Never
+            // re-enables on focus; E-Stop is left to the operator.
+            if let Some(window) = app.get_webview_window("main") {
+                let cmd_tx = cmd_tx.clone();
+                let disable_on_blur = disable_on_blur.clone();
+                window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::Focused(false) = event {
+                        if disable_on_blur.load(Ordering::Relaxed) {
+                            // Wait for room rather than drop the disable when
+                            // the command queue is full
+                            let cmd_tx = cmd_tx.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = cmd_tx.send(DsCommand::Disable).await {
+                                    tracing::error!("Focus-loss disable not sent: {e}");
+                                }
+                            });
+                        }
+                    }
+                });
+            }
+
+            // Created here so the protocol loop can log control transitions
+            let (file_log_tx, file_log_rx) = mpsc::channel::<ConsoleMessage>(256);
+
             // Spawn the protocol loop
-            tauri::async_runtime::spawn(protocol_loop(cmd_rx, event_tx, js_state, target_ip_tx.clone()));
+            tauri::async_runtime::spawn(protocol_loop(
+                cmd_rx,
+                event_tx,
+                js_state,
+                target_ip_tx.clone(),
+                timeline.clone(),
+                socket_status.clone(),
+                latency_probe.clone(),
+                robot_snapshot.clone(),
+                file_log_tx.clone(),
+                last_outbound.clone(),
+            ));
+
+            // Record frontend events next to the console logs for later
+            // replay, once recording is turned on
+            let log_dir = app_data_dir.join("logs");
+            let (record_tx, record_rx) = mpsc::channel::<ReplayRecord>(512);
+            tauri::async_runtime::spawn(replay::session_recorder(record_rx, log_dir.clone()));
 
             // Spawn the event bridge to push events to the frontend
-            tauri::async_runtime::spawn(events::event_bridge(app_handle, event_rx));
+            tauri::async_runtime::spawn(events::event_bridge(
+                app_handle,
+                event_rx,
+                record_tx,
+                session_recording.clone(),
+                replaying.clone(),
+                event_throttling.clone(),
+                event_fanout,
+            ));
 
-            // Spawn TCP console log listener (connects to localhost initially)
-            let (log_tx, mut log_rx) = mpsc::channel::<ConsoleMessage>(256);
+            // Spawn TCP console log listener (waits for the first published target)
+            // Sized for bursts; the listener drops (and counts) rather than
+            // blocking when the bridge falls behind
+            let (log_tx, mut log_rx) = mpsc::channel::<ConsoleMessage>(1024);
             let (power_tx, mut power_rx) = mpsc::channel::<PowerData>(64);
             let (version_tx, mut version_rx) = mpsc::channel::<VersionInfo>(16);
-            let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+            let (radio_tx, mut radio_rx) = mpsc::channel::<RadioEvent>(16);
             let event_tx_log = event_tx_console.clone();
             let event_tx_power = event_tx_console.clone();
             let event_tx_version = event_tx_console.clone();
+            let event_tx_radio = event_tx_console.clone();
 
             tauri::async_runtime::spawn(logging::console_log_listener(
                 target_ip_rx,
@@ -102,18 +403,55 @@ pub fn run() {
                 power_tx,
                 shutdown_rx,
                 version_tx,
+                radio_tx,
+                socket_status.clone(),
+                joystick_descriptors.clone(),
+                severity_patterns.clone(),
             ));
 
             // Spawn log file writer
-            let log_dir = app.path().app_data_dir().unwrap_or_default().join("logs");
-            let (file_log_tx, file_log_rx) = mpsc::channel::<ConsoleMessage>(256);
-            tauri::async_runtime::spawn(log_writer::log_file_writer(file_log_rx, log_dir));
+            tauri::async_runtime::spawn(log_writer::log_file_writer(
+                file_log_rx,
+                log_control_rx,
+                log_dir,
+                log_writer::DEFAULT_MAX_FILE_BYTES,
+                log_writer::DEFAULT_MAX_FILES,
+                log_session.clone(),
+            ));
 
-            // Bridge console messages to event system + file writer
+            // Bridge console messages to event system + file writer,
+            // optionally collapsing repeated messages and filtering by severity
+            let coalesce = console_coalesce.clone();
+            let console_filter = console_filter.clone();
             tauri::async_runtime::spawn(async move {
-                while let Some(msg) = log_rx.recv().await {
-                    let _ = file_log_tx.send(msg.clone()).await;
-                    let _ = event_tx_log.send(DsEvent::Console(msg)).await;
+                let mut coalescer = logging::ConsoleCoalescer::new(logging::COALESCE_WINDOW);
+                let mut flush_tick = tokio::time::interval(std::time::Duration::from_millis(100));
+                loop {
+                    let mut ready: Vec<ConsoleMessage> = Vec::new();
+                    tokio::select! {
+                        msg = log_rx.recv() => {
+                            let Some(msg) = msg else { break };
+                            if coalesce.load(Ordering::Relaxed) {
+                                ready.extend(coalescer.push(msg, std::time::Instant::now()));
+                            } else {
+                                ready.extend(coalescer.flush());
+                                ready.push(msg);
+                            }
+                        }
+                        _ = flush_tick.tick() => {
+                            ready.extend(coalescer.flush_expired(std::time::Instant::now()));
+                        }
+                    }
+                    let filter = console_filter.read().clone();
+                    for msg in ready {
+                        let shown = filter.allows(&msg);
+                        if shown || !filter.apply_to_file {
+                            let _ = file_log_tx.send(msg.clone()).await;
+                        }
+                        if shown {
+                            let _ = event_tx_log.send(DsEvent::Console(msg)).await;
+                        }
+                    }
                 }
             });
 
@@ -131,12 +469,50 @@ pub fn run() {
                 }
             });
 
-            // Store shutdown sender for cleanup (not strictly needed for now)
-            std::mem::forget(shutdown_tx);
+            // Bridge radio events to the event system
+            tauri::async_runtime::spawn(async move {
+                while let Some(event) = radio_rx.recv().await {
+                    let _ = event_tx_radio.send(DsEvent::RadioEvent(event)).await;
+                }
+            });
 
-            // Spawn system info polling (1Hz — PC battery + CPU)
+            // On close, disable the robot and let a few packets carry that
+            // before exiting. Otherwise the robot's last packet says "enabled"
+            // and it stays enabled until its own comms timeout. A latched
+            // E-Stop is kept in those packets. The console listener is then
+            // told to stop.
+            if let Some(window) = app.get_webview_window("main") {
+                let cmd_tx = cmd_tx.clone();
+                let app_handle = app.handle().clone();
+                let closing = Arc::new(AtomicBool::new(false));
+                window.on_window_event(move |event| {
+                    let tauri::WindowEvent::CloseRequested { api, .. } = event else {
+                        return;
+                    };
+                    // A second close request while shutting down closes at once
+                    if closing.swap(true, Ordering::SeqCst) {
+                        return;
+                    }
+                    api.prevent_close();
+                    let cmd_tx = cmd_tx.clone();
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let _ = cmd_tx.send(DsCommand::Disable).await;
+                        // Tasks wind down while the disabled packets go out
+                        app_handle.state::<AppState>().shutdown_all();
+                        tokio::time::sleep(SHUTDOWN_FLUSH).await;
+                        app_handle.exit(0);
+                    });
+                });
+            }
+
+            // Spawn system info polling (1Hz — PC battery, CPU, memory and temperature)
             let event_tx_sysinfo = event_tx_console.clone();
-            tauri::async_runtime::spawn(system_info::system_info_loop(event_tx_sysinfo));
+            tauri::async_runtime::spawn(system_info::system_info_loop(
+                event_tx_sysinfo,
+                system_info_history.clone(),
+                cpu_temp_threshold.clone(),
+            ));
 
             // Spawn gamepad polling thread (~50Hz)
             // Uses a std::thread because gilrs needs a synchronous polling loop
@@ -144,30 +520,92 @@ pub fn run() {
             let event_tx_gamepad = event_tx_console.clone();
             std::thread::spawn(move || {
                 let mut last_ui_update = std::time::Instant::now();
+                let mut last_emit = std::time::Instant::now();
+                let mut last_sent: Option<protocol::connection::GamepadUpdate> = None;
+                let mut last_wake = std::time::SystemTime::now();
                 loop {
                     let state = app_handle_gamepad.state::<AppState>();
+                    // Built under the lock, sent once it is released so a
+                    // full event channel can't stall commands that need it
+                    let mut outgoing = Vec::new();
                     let mut mgr = state.gamepad_manager.lock();
 
+                    // After sleep/resume gilrs may have missed hotplug events
+                    // and the console socket is likely dead
+                    let now = std::time::SystemTime::now();
+                    let resumed = gamepad::manager::detect_resume(
+                        last_wake,
+                        now,
+                        std::time::Duration::from_millis(20),
+                    );
+                    last_wake = now;
+                    if let Some(slept) = resumed {
+                        tracing::info!("Resumed after {}s asleep", slept.as_secs());
+                        let update = mgr.rescan();
+                        last_sent = Some(update.clone());
+                        outgoing.push(DsEvent::GamepadUpdate(update));
+                        outgoing.push(DsEvent::Resumed {
+                            slept_ms: slept.as_millis() as u64,
+                        });
+                    }
+
                     if let Some(update) = mgr.poll() {
                         // Connection/disconnection — send immediately
-                        let _ = event_tx_gamepad.blocking_send(DsEvent::GamepadUpdate(update));
+                        last_sent = Some(update.clone());
+                        outgoing.push(DsEvent::GamepadUpdate(update));
                         last_ui_update = std::time::Instant::now();
-                    } else if last_ui_update.elapsed() >= std::time::Duration::from_millis(100)
+                        last_emit = last_ui_update;
+                    } else if last_ui_update.elapsed()
+                        >= std::time::Duration::from_millis(
+                            state.gamepad_ui_interval_ms.load(Ordering::Relaxed),
+                        )
                         && mgr.gamepad_count() > 0
                     {
-                        // Periodic update (~10Hz) for live axis/button display
-                        let update = mgr.get_gamepad_update();
-                        let _ = event_tx_gamepad.blocking_send(DsEvent::GamepadUpdate(update));
+                        // Periodic update (10Hz by default) for live axis/button display,
+                        // skipped while idle unless the heartbeat is due. The
+                        // snapshot is only built when the manager reports a change.
+                        let changes_only = state.gamepad_changes_only.load(Ordering::Relaxed);
+                        let dirty = mgr.take_ui_dirty();
+                        let heartbeat_due =
+                            last_emit.elapsed() >= gamepad::manager::UPDATE_HEARTBEAT;
+                        if !changes_only || dirty || heartbeat_due {
+                            let update = mgr.get_gamepad_update();
+                            if !changes_only
+                                || gamepad::manager::should_emit_update(
+                                    last_sent.as_ref(),
+                                    &update,
+                                    last_emit.elapsed(),
+                                )
+                            {
+                                last_sent = Some(update.clone());
+                                outgoing.push(DsEvent::GamepadUpdate(update));
+                                last_emit = std::time::Instant::now();
+                            }
+                        }
                         last_ui_update = std::time::Instant::now();
                     }
 
-                    drop(mgr); // Release lock before sleeping
+                    drop(mgr); // Release lock before sending and sleeping
+
+                    if resumed.is_some() {
+                        // Re-sending the target makes the console listener reconnect
+                        state.target_ip_tx.send_modify(|_| {});
+                    }
+                    for event in outgoing {
+                        let _ = event_tx_gamepad.blocking_send(event);
+                    }
                     std::thread::sleep(std::time::Duration::from_millis(20));
                 }
             });
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Exits that bypass the window close handler (e.g. updater restart)
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().shutdown_all();
+            }
+        });
 }
@@ -1,48 +1,554 @@
-use std::path::PathBuf;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use serde::Serialize;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
-use tokio::sync::mpsc;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, oneshot};
 
 use crate::protocol::types::ConsoleMessage;
 
-/// Writes console messages to timestamped log files in the given directory.
-pub async fn log_file_writer(mut log_rx: mpsc::Receiver<ConsoleMessage>, log_dir: PathBuf) {
-    if let Err(e) = fs::create_dir_all(&log_dir).await {
-        tracing::error!("Failed to create log directory: {e}");
-        return;
+/// Default size at which the current log file is rotated (10 MB)
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Default number of log files kept in the log directory
+pub const DEFAULT_MAX_FILES: usize = 20;
+
+/// How often buffered log lines are flushed to disk
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// File name for a session's log: `ds-{secs}.log`, then `ds-{secs}-{n}.log`
+/// for each rotated continuation.
+fn log_file_name(secs: u64, part: u32) -> String {
+    if part == 0 {
+        format!("ds-{secs}.log")
+    } else {
+        format!("ds-{secs}-{part}.log")
     }
+}
 
-    // Create a log file with timestamp in name
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = now.as_secs();
-    let filename = format!("ds-{secs}.log");
-    let path = log_dir.join(&filename);
+/// Whether writing `line_len` more bytes would push the file past the limit.
+/// An empty file always accepts the line so oversized lines still get written.
+fn needs_rotation(written: u64, line_len: u64, max_bytes: u64) -> bool {
+    written > 0 && written + line_len > max_bytes
+}
+
+/// Marks lines the DS writes about its own control actions, as opposed to
+/// robot console output
+pub const DS_ORIGIN_PREFIX: &str = "[DS]";
+
+/// Synthetic log message recording a DS action, e.g. an enable
+pub fn ds_message(timestamp: f64, text: &str) -> ConsoleMessage {
+    ConsoleMessage {
+        timestamp,
+        message: format!("{DS_ORIGIN_PREFIX} {text}"),
+        is_error: false,
+        is_warning: false,
+        sequence: 0,
+        repeat_count: 1,
+        error: None,
+    }
+}
+
+/// Format a message as `[ts] [LEVEL] message`. `record_to_message` is the
+/// inverse; keep the two in sync.
+fn format_line(msg: &ConsoleMessage) -> String {
+    let level = if msg.is_error {
+        "ERROR"
+    } else if msg.is_warning {
+        "WARNING"
+    } else {
+        "INFO"
+    };
+    format!("[{:.3}] [{level}] {}\n", msg.timestamp, msg.message)
+}
+
+/// Log files written by the current session, in the order they were opened
+#[derive(Debug, Clone, Default)]
+pub struct LogSession {
+    pub files: Vec<PathBuf>,
+}
+
+pub type SharedLogSession = Arc<RwLock<LogSession>>;
+
+/// Control requests for the log file writer
+#[derive(Debug)]
+pub enum LogControl {
+    /// Close the current file and start a new `ds-{secs}.log`; replies with
+    /// the new file's path (None if it could not be opened)
+    NewSession(oneshot::Sender<Option<PathBuf>>),
+}
+
+/// One console message read back from a log file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRecord {
+    pub timestamp: f64,
+    pub level: String,
+    pub message: String,
+}
+
+/// Parse the `[ts] [LEVEL] ` prefix written by `format_line`
+fn parse_line_prefix(line: &str) -> Option<(f64, &str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (ts, rest) = rest.split_once("] [")?;
+    let (level, message) = rest.split_once("] ")
+        .or_else(|| rest.strip_suffix(']').map(|l| (l, "")))?;
+    let timestamp = ts.parse().ok()?;
+    Some((timestamp, level, message))
+}
+
+/// Read log lines back into records. Lines without a `[ts] [LEVEL]` prefix are
+/// continuations (e.g. callstacks) of the previous message.
+pub fn parse_log<R: BufRead>(reader: R) -> std::io::Result<Vec<LogRecord>> {
+    let mut records: Vec<LogRecord> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        match parse_line_prefix(&line) {
+            Some((timestamp, level, message)) => records.push(LogRecord {
+                timestamp,
+                level: level.to_string(),
+                message: message.to_string(),
+            }),
+            None => {
+                if let Some(last) = records.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(&line);
+                }
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Maximum number of matches returned by `search_log`
+pub const MAX_SEARCH_MATCHES: usize = 500;
+
+/// One log line matching a search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogMatch {
+    /// Log file containing the line (a session may span rotated files)
+    pub file: String,
+    /// 1-based line number within `file`
+    pub line_number: usize,
+    /// Timestamp of the message the line belongs to; continuation lines
+    /// (e.g. callstacks) take their message's timestamp
+    pub timestamp: Option<f64>,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogSearchResult {
+    pub matches: Vec<LogMatch>,
+    /// More lines matched than were returned
+    pub truncated: bool,
+}
+
+/// Search the given log files line by line for `query`, as a plain substring
+/// or, with `regex`, a regular expression. Files are streamed, so only the
+/// matches are held in memory. Stops after `max_matches`.
+pub fn search_log(
+    files: &[PathBuf],
+    query: &str,
+    case_sensitive: bool,
+    regex: bool,
+    max_matches: usize,
+) -> Result<LogSearchResult, String> {
+    let pattern = if regex { query.to_string() } else { regex::escape(query) };
+    let re = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {e}"))?;
+
+    let mut result = LogSearchResult { matches: Vec::new(), truncated: false };
+    for path in files {
+        let file = match std::fs::File::open(path) {
+            Ok(f) => f,
+            // Pruned by retention; search what remains
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut timestamp = None;
+        for (i, line) in std::io::BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if let Some((ts, _, _)) = parse_line_prefix(&line) {
+                timestamp = Some(ts);
+            }
+            if !re.is_match(&line) {
+                continue;
+            }
+            if result.matches.len() >= max_matches {
+                result.truncated = true;
+                return Ok(result);
+            }
+            result.matches.push(LogMatch {
+                file: name.clone(),
+                line_number: i + 1,
+                timestamp,
+                line,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Rebuild a console message from a parsed log record. `sequence` is the
+/// record's position in the file; structured error fields are not kept in
+/// the log, so `error` is None.
+pub fn record_to_message(record: LogRecord, sequence: u16) -> ConsoleMessage {
+    ConsoleMessage {
+        timestamp: record.timestamp,
+        is_error: record.level.eq_ignore_ascii_case("ERROR"),
+        is_warning: record.level.eq_ignore_ascii_case("WARNING"),
+        message: record.message,
+        sequence,
+        repeat_count: 1,
+        error: None,
+    }
+}
+
+/// Load a saved `ds-*.log` file back into console messages. Messages past
+/// the 65536th all get the last sequence number.
+pub fn load_log_file(path: &Path) -> Result<Vec<ConsoleMessage>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let records = parse_log(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(i, r)| record_to_message(r, u16::try_from(i).unwrap_or(u16::MAX)))
+        .collect())
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Export the given log files to `out` as `"csv"` or `"txt"`. The sequence
+/// column is the message's position within the session. Returns the number
+/// of messages written.
+pub fn export_log(files: &[PathBuf], out: &Path, format: &str) -> Result<usize, String> {
+    if format != "csv" && format != "txt" {
+        return Err(format!("Unknown export format: {format}"));
+    }
+
+    let mut records = Vec::new();
+    for path in files {
+        let file = match std::fs::File::open(path) {
+            Ok(f) => f,
+            // Pruned by retention; export what remains
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        records.extend(parse_log(std::io::BufReader::new(file)).map_err(|e| e.to_string())?);
+    }
+
+    let file = std::fs::File::create(out).map_err(|e| e.to_string())?;
+    let mut w = std::io::BufWriter::new(file);
+    let result: std::io::Result<()> = (|| {
+        if format == "csv" {
+            writeln!(w, "timestamp,sequence,level,message")?;
+            for (seq, r) in records.iter().enumerate() {
+                writeln!(w, "{:.3},{seq},{},{}", r.timestamp, r.level, csv_escape(&r.message))?;
+            }
+        } else {
+            for r in &records {
+                writeln!(w, "[{:.3}] [{}] {}", r.timestamp, r.level, r.message)?;
+            }
+        }
+        w.flush()
+    })();
+    result.map_err(|e| e.to_string())?;
+
+    Ok(records.len())
+}
 
-    let file = match fs::OpenOptions::new()
+async fn open_log_file(path: &Path) -> Option<BufWriter<fs::File>> {
+    match fs::OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&path)
+        .open(path)
         .await
     {
-        Ok(f) => f,
+        Ok(f) => {
+            tracing::info!("Logging console messages to {}", path.display());
+            Some(BufWriter::new(f))
+        }
         Err(e) => {
             tracing::error!("Failed to open log file {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Delete the oldest `{prefix}*{suffix}` files (console logs, session
+/// recordings) so at most `max_files` remain.
+pub(crate) async fn enforce_retention(log_dir: &Path, prefix: &str, suffix: &str, max_files: usize) {
+    let mut entries = match fs::read_dir(log_dir).await {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::warn!("Failed to list log directory: {e}");
             return;
         }
     };
 
-    tracing::info!("Logging console messages to {}", path.display());
-    let mut writer = tokio::io::BufWriter::new(file);
+    let mut logs: Vec<(std::time::SystemTime, PathBuf)> = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !(name.starts_with(prefix) && name.ends_with(suffix)) {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .await
+            .and_then(|m| m.modified())
+            .unwrap_or(std::time::UNIX_EPOCH);
+        logs.push((modified, entry.path()));
+    }
+
+    if logs.len() <= max_files {
+        return;
+    }
+    logs.sort();
+    let excess = logs.len() - max_files;
+    for (_, path) in logs.into_iter().take(excess) {
+        match fs::remove_file(&path).await {
+            Ok(()) => tracing::info!("Removed old log file {}", path.display()),
+            Err(e) => tracing::warn!("Failed to remove old log file {}: {e}", path.display()),
+        }
+    }
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Writes console messages to timestamped log files in the given directory.
+///
+/// The file is rotated to `ds-{secs}-{n}.log` once it would exceed
+/// `max_file_bytes`, and the directory is pruned to the newest `max_files`
+/// logs. Writes are buffered and flushed every 100ms. A `LogControl` request
+/// starts a fresh session file after draining already-queued messages into
+/// the old one.
+pub async fn log_file_writer(
+    mut log_rx: mpsc::Receiver<ConsoleMessage>,
+    mut control_rx: mpsc::Receiver<LogControl>,
+    log_dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    session: SharedLogSession,
+) {
+    if let Err(e) = fs::create_dir_all(&log_dir).await {
+        tracing::error!("Failed to create log directory: {e}");
+        return;
+    }
+
+    // Create a log file with timestamp in name
+    let mut secs = unix_secs();
+    let mut part: u32 = 0;
+
+    let path = log_dir.join(log_file_name(secs, part));
+    let Some(mut writer) = open_log_file(&path).await else {
+        return;
+    };
+    session.write().files.push(path);
+    enforce_retention(&log_dir, "ds-", ".log", max_files).await;
+
+    let mut written: u64 = 0;
+    let mut dirty = false;
+    let mut flush_interval = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            msg = log_rx.recv() => {
+                let Some(msg) = msg else { break };
+                let line = format_line(&msg);
+
+                if needs_rotation(written, line.len() as u64, max_file_bytes) {
+                    let _ = writer.flush().await;
+                    part += 1;
+                    let path = log_dir.join(log_file_name(secs, part));
+                    let Some(next) = open_log_file(&path).await else {
+                        return;
+                    };
+                    session.write().files.push(path);
+                    writer = next;
+                    written = 0;
+                    enforce_retention(&log_dir, "ds-", ".log", max_files).await;
+                }
+
+                if let Err(e) = writer.write_all(line.as_bytes()).await {
+                    tracing::warn!("Failed to write log: {e}");
+                    break;
+                }
+                written += line.len() as u64;
+                dirty = true;
+            }
+            _ = flush_interval.tick(), if dirty => {
+                let _ = writer.flush().await;
+                dirty = false;
+            }
+            Some(ctrl) = control_rx.recv() => {
+                let LogControl::NewSession(reply) = ctrl;
+
+                // Messages queued before the request belong to the old session
+                while let Ok(msg) = log_rx.try_recv() {
+                    let _ = writer.write_all(format_line(&msg).as_bytes()).await;
+                }
+                let _ = writer.flush().await;
+                dirty = false;
+
+                // Never reuse the previous session's name (two requests in one second)
+                secs = unix_secs().max(secs + 1);
+                part = 0;
+                let path = log_dir.join(log_file_name(secs, part));
+                let Some(next) = open_log_file(&path).await else {
+                    let _ = reply.send(None);
+                    return;
+                };
+                session.write().files = vec![path.clone()];
+                writer = next;
+                written = 0;
+                enforce_retention(&log_dir, "ds-", ".log", max_files).await;
+                let _ = reply.send(Some(path));
+            }
+        }
+    }
+
+    let _ = writer.flush().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(timestamp: f64, text: &str, is_error: bool, is_warning: bool) -> ConsoleMessage {
+        ConsoleMessage {
+            timestamp,
+            message: text.to_string(),
+            is_error,
+            is_warning,
+            sequence: 0,
+            repeat_count: 1,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn formatted_lines_parse_back_to_the_same_messages() {
+        let original = vec![
+            message(1700000000.125, "Robot program starting", false, false),
+            message(
+                1700000001.5,
+                "Unhandled exception: java.lang.NullPointerException\n\
+                 \tat frc.robot.Robot.teleopPeriodic(Robot.java:42)\n\
+                 \tat edu.wpi.first.wpilibj.IterativeRobotBase.loopFunc(IterativeRobotBase.java:365)",
+                true,
+                false,
+            ),
+            message(1700000002.0, "Loop time of 0.02s overrun", false, true),
+            message(1700000003.0, "", false, false),
+        ];
+        let text: String = original.iter().map(format_line).collect();
+        let records = parse_log(std::io::Cursor::new(text)).unwrap();
+        let restored: Vec<ConsoleMessage> = records
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| record_to_message(r, i as u16))
+            .collect();
 
-    while let Some(msg) = log_rx.recv().await {
-        let level = if msg.is_error { "ERROR" } else { "INFO" };
-        let line = format!("[{:.3}] [{level}] {}\n", msg.timestamp, msg.message);
-        if let Err(e) = writer.write_all(line.as_bytes()).await {
-            tracing::warn!("Failed to write log: {e}");
-            break;
+        assert_eq!(restored.len(), original.len());
+        for (i, (restored, original)) in restored.iter().zip(&original).enumerate() {
+            assert_eq!(restored.timestamp, original.timestamp);
+            assert_eq!(restored.message, original.message);
+            assert_eq!(restored.is_error, original.is_error);
+            assert_eq!(restored.is_warning, original.is_warning);
+            assert_eq!(restored.sequence as usize, i);
         }
-        let _ = writer.flush().await;
+    }
+
+    #[test]
+    fn load_log_file_saturates_the_sequence() {
+        let path = std::env::temp_dir()
+            .join(format!("ds-sequence-test-{}.log", std::process::id()));
+        let line = format_line(&message(1.0, "tick", false, false));
+        std::fs::write(&path, line.repeat(usize::from(u16::MAX) + 3)).unwrap();
+        let messages = load_log_file(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let sequences: Vec<u16> = messages.unwrap().iter().map(|m| m.sequence).collect();
+        assert_eq!(sequences[0], 0);
+        assert_eq!(sequences[usize::from(u16::MAX)], u16::MAX);
+        assert_eq!(sequences[sequences.len() - 2..], [u16::MAX, u16::MAX]);
+    }
+
+    #[test]
+    fn rotation_happens_only_past_the_size_limit() {
+        assert!(!needs_rotation(90, 10, 100));
+        assert!(needs_rotation(91, 10, 100));
+        // An oversized line still goes into an empty file
+        assert!(!needs_rotation(0, 500, 100));
+        assert_eq!(log_file_name(1700000000, 0), "ds-1700000000.log");
+        assert_eq!(log_file_name(1700000000, 2), "ds-1700000000-2.log");
+    }
+
+    #[test]
+    fn csv_quotes_multiline_callstacks() {
+        assert_eq!(csv_escape("plain message"), "plain message");
+        assert_eq!(
+            csv_escape("Error \"x\", exiting\n\tat frc.robot.Robot.main(Robot.java:12)"),
+            "\"Error \"\"x\"\", exiting\n\tat frc.robot.Robot.main(Robot.java:12)\""
+        );
+    }
+
+    #[test]
+    fn search_matches_substrings_regexes_and_case() {
+        let path = std::env::temp_dir().join(format!("ds-search-test-{}.log", std::process::id()));
+        let text: String = [
+            message(10.0, "Robot program starting", false, false),
+            message(11.0, "CAN timeout on device 3\n\tat Drive.periodic(Drive.java:7)", true, false),
+            message(12.0, "can bus recovered", false, true),
+        ]
+        .iter()
+        .map(format_line)
+        .collect();
+        std::fs::write(&path, text).unwrap();
+        let files = [path.clone(), path.with_extension("missing")];
+        let search = |query, case_sensitive, regex| {
+            search_log(&files, query, case_sensitive, regex, MAX_SEARCH_MATCHES).unwrap()
+        };
+
+        let insensitive = search("can", false, false);
+        let lines: Vec<usize> = insensitive.matches.iter().map(|m| m.line_number).collect();
+        assert_eq!(lines, [2, 4]);
+        assert_eq!(search("CAN", true, false).matches.len(), 1);
+
+        // Continuation lines take the timestamp of their message
+        let callstack = search(r"Drive\.java:\d+", true, true);
+        assert_eq!(callstack.matches.len(), 1);
+        assert_eq!(callstack.matches[0].line_number, 3);
+        assert_eq!(callstack.matches[0].timestamp, Some(11.0));
+
+        // Regex metacharacters are literal in substring mode
+        assert!(search("Drive.java:\\d", true, false).matches.is_empty());
+        assert!(search_log(&files, "(", true, true, 10).is_err());
+
+        let capped = search_log(&files, "o", false, false, 2).unwrap();
+        assert_eq!(capped.matches.len(), 2);
+        assert!(capped.truncated);
+        let _ = std::fs::remove_file(&path);
     }
 }
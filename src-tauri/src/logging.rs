@@ -1,9 +1,20 @@
+use std::time::{Duration, Instant};
+
+use std::sync::Arc;
+
 use anyhow::Result;
-use tokio::io::AsyncReadExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, watch};
 
-use crate::protocol::types::{ConsoleMessage, PowerData, VersionInfo};
+use crate::protocol::resolve::resolve_target;
+use crate::protocol::types::{
+    AlertSeverity, ConsoleError, ConsoleMessage, JoystickDescriptor, PowerData, RadioEvent,
+    SocketStatus, VersionInfo,
+};
 
 /// Reads console output from the roboRIO TCP stream (port 1740)
 ///
@@ -16,26 +27,59 @@ use crate::protocol::types::{ConsoleMessage, PowerData, VersionInfo};
 ///                           + error_code(4 i32) + flags(1) + details(2+n)
 ///                           + location(2+n) + callstack(2+n)
 ///   0x0A = Version Info: image(2+n) + wpilib(2+n) + rio(2+n)
-///   0x00 = Radio Events
+///   0x00 = Radio Events: message(n) — UTF-8 text, no length prefix
 ///   0x04 = Disable Faults: comms(2 u16) + 12v(2 u16)
 ///   0x05 = Rail Faults: 6v(2 u16) + 5v(2 u16) + 3.3v(2 u16)
+///
+/// Tags (DS → roboRIO):
+///   0x02 = Joystick Descriptor, see `build_descriptor_frame`
+#[allow(clippy::too_many_arguments)]
 pub async fn console_log_listener(
     mut target_ip_rx: watch::Receiver<String>,
     log_tx: mpsc::Sender<ConsoleMessage>,
     power_tx: mpsc::Sender<PowerData>,
     mut shutdown_rx: watch::Receiver<bool>,
     version_tx: mpsc::Sender<VersionInfo>,
+    radio_tx: mpsc::Sender<RadioEvent>,
+    socket_status: Arc<RwLock<SocketStatus>>,
+    descriptors: Arc<RwLock<Vec<JoystickDescriptor>>>,
+    severity_patterns: Arc<RwLock<SeverityPatterns>>,
 ) {
     loop {
         if *shutdown_rx.borrow() {
             return;
         }
 
-        let addr = format!("{}:1740", *target_ip_rx.borrow());
+        // Mark the value seen so `changed()` only fires on later updates.
+        // An empty target means the protocol loop hasn't published one yet.
+        let target = target_ip_rx.borrow_and_update().clone();
+        if target.is_empty() {
+            tokio::select! {
+                _ = target_ip_rx.changed() => continue,
+                _ = shutdown_rx.changed() => return,
+            }
+        }
+
+        // Hostname targets are resolved the same way as the UDP destination
+        let addr = tokio::select! {
+            result = resolve_target(&target, 1740) => match result {
+                Ok(addr) => addr,
+                Err(e) => {
+                    tracing::debug!("TCP console: {e}");
+                    tokio::select! {
+                        _ = target_ip_rx.changed() => continue,
+                        _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => continue,
+                        _ = shutdown_rx.changed() => return,
+                    }
+                }
+            },
+            _ = target_ip_rx.changed() => continue,
+            _ = shutdown_rx.changed() => return,
+        };
         tracing::info!("Attempting TCP console connection to {addr}");
 
         let stream = tokio::select! {
-            result = TcpStream::connect(&addr) => {
+            result = TcpStream::connect(addr) => {
                 match result {
                     Ok(s) => s,
                     Err(e) => {
@@ -54,16 +98,233 @@ pub async fn console_log_listener(
         };
 
         tracing::info!("Connected to roboRIO console at {addr}");
+        {
+            let mut status = socket_status.write();
+            status.console_connected = true;
+            status.console_remote_addr = stream.peer_addr().ok().map(|a| a.to_string());
+        }
+
+        let (reader, writer) = stream.into_split();
+        let descriptor_task = tokio::spawn(send_joystick_descriptors(writer, descriptors.clone()));
 
-        if let Err(e) = read_console_stream(stream, &log_tx, &power_tx, &mut shutdown_rx, &mut target_ip_rx, &version_tx).await {
+        if let Err(e) = read_console_stream(reader, &log_tx, &power_tx, &mut shutdown_rx, &mut target_ip_rx, &version_tx, &radio_tx, &socket_status, &severity_patterns).await {
             tracing::warn!("Console stream error: {e}");
         }
+        descriptor_task.abort();
+
+        {
+            let mut status = socket_status.write();
+            status.console_connected = false;
+            status.console_remote_addr = None;
+        }
 
         tracing::info!("Console connection lost, reconnecting...");
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
 }
 
+/// How often joystick descriptors are resent while connected
+const DESCRIPTOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Build a Joystick Descriptor frame (tag 0x02) for slot `index`:
+///   Size(2 BE) + 0x02 + index(1) + is_xbox(1) + hid_type(1) + name_len(1) +
+///   name(n) + axis_count(1) + axis_types(axis_count) + button_count(1) +
+///   pov_count(1)
+/// Axis types are reported as the axis index (X, Y, Z, Twist, Throttle, ...).
+pub fn build_descriptor_frame(index: u8, desc: &JoystickDescriptor) -> Vec<u8> {
+    let mut name_len = desc.name.len().min(u8::MAX as usize);
+    while !desc.name.is_char_boundary(name_len) {
+        name_len -= 1;
+    }
+    let name = &desc.name.as_bytes()[..name_len];
+
+    let mut body = vec![0x02, index, desc.is_xbox as u8, desc.hid_type, name_len as u8];
+    body.extend_from_slice(name);
+    body.push(desc.layout.axes);
+    body.extend(0..desc.layout.axes);
+    body.push(desc.layout.buttons);
+    body.push(desc.layout.povs);
+
+    let mut frame = Vec::with_capacity(body.len() + 2);
+    frame.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    frame.extend_from_slice(&body);
+    frame
+}
+
+/// Sends the descriptor of every joystick slot on connect and then
+/// periodically, until the connection fails or the task is aborted
+async fn send_joystick_descriptors(
+    mut writer: OwnedWriteHalf,
+    descriptors: Arc<RwLock<Vec<JoystickDescriptor>>>,
+) {
+    let mut interval = tokio::time::interval(DESCRIPTOR_INTERVAL);
+    loop {
+        interval.tick().await;
+        let frames: Vec<u8> = {
+            let descriptors = descriptors.read();
+            let empty = JoystickDescriptor::default();
+            (0..6u8)
+                .flat_map(|i| build_descriptor_frame(i, descriptors.get(i as usize).unwrap_or(&empty)))
+                .collect()
+        };
+        if let Err(e) = writer.write_all(&frames).await {
+            tracing::debug!("Joystick descriptor send failed: {e}");
+            return;
+        }
+    }
+}
+
+/// Minimum severity of console messages shown in the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConsoleLevel {
+    /// Everything, including standard output
+    #[default]
+    All,
+    /// Warnings and errors
+    Warning,
+    Error,
+}
+
+impl ConsoleLevel {
+    pub fn parse(level: &str) -> Result<Self, String> {
+        match level.to_ascii_lowercase().as_str() {
+            "none" | "all" => Ok(ConsoleLevel::All),
+            "warning" => Ok(ConsoleLevel::Warning),
+            "error" => Ok(ConsoleLevel::Error),
+            _ => Err(format!("Unknown console level: {level}")),
+        }
+    }
+
+    /// Whether `msg` is at or above this level
+    pub fn allows(self, msg: &ConsoleMessage) -> bool {
+        match self {
+            ConsoleLevel::All => true,
+            ConsoleLevel::Warning => msg.is_error || msg.is_warning,
+            ConsoleLevel::Error => msg.is_error,
+        }
+    }
+}
+
+/// Text match for console messages, compiled from a saved filter when it is
+/// applied. Substrings match case-insensitively.
+#[derive(Debug, Clone)]
+pub struct ConsolePattern {
+    pub name: String,
+    matcher: regex::Regex,
+}
+
+impl ConsolePattern {
+    pub fn new(name: &str, pattern: &str, is_regex: bool) -> Result<Self, String> {
+        let source = if is_regex { pattern.to_string() } else { regex::escape(pattern) };
+        let matcher = regex::RegexBuilder::new(&source)
+            .case_insensitive(!is_regex)
+            .build()
+            .map_err(|e| format!("Invalid console filter pattern: {e}"))?;
+        Ok(Self { name: name.to_string(), matcher })
+    }
+
+    pub fn matches(&self, msg: &ConsoleMessage) -> bool {
+        self.matcher.is_match(&msg.message)
+    }
+}
+
+/// Console severity and text filter applied by the console bridge
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleFilter {
+    pub level: ConsoleLevel,
+    /// Also keep filtered messages out of the log file. Off by default so
+    /// the file stays a full record.
+    pub apply_to_file: bool,
+    /// Active saved filter; messages must match it and the level
+    pub pattern: Option<ConsolePattern>,
+}
+
+impl ConsoleFilter {
+    /// Whether `msg` passes both the level and the active pattern
+    pub fn allows(&self, msg: &ConsoleMessage) -> bool {
+        self.level.allows(msg) && self.pattern.as_ref().is_none_or(|p| p.matches(msg))
+    }
+}
+
+/// Window within which identical consecutive console messages are collapsed
+pub const COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Collapses runs of identical console messages (same text and severity)
+/// arriving within `window` of each other into one message with a repeat
+/// count. The latest message is held back until the run ends.
+pub struct ConsoleCoalescer {
+    window: Duration,
+    pending: Option<(ConsoleMessage, Instant)>,
+}
+
+impl ConsoleCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self { window, pending: None }
+    }
+
+    /// Add a message; returns the previous run if this message ends it
+    pub fn push(&mut self, msg: ConsoleMessage, now: Instant) -> Option<ConsoleMessage> {
+        if let Some((pending, last_seen)) = &mut self.pending {
+            if pending.message == msg.message
+                && pending.is_error == msg.is_error
+                && pending.is_warning == msg.is_warning
+                && now.duration_since(*last_seen) <= self.window
+            {
+                pending.repeat_count += 1;
+                *last_seen = now;
+                return None;
+            }
+        }
+        self.pending
+            .replace((msg, now))
+            .map(|(prev, _)| Self::finish(prev))
+    }
+
+    /// Release the held message once its window has passed without repeats
+    pub fn flush_expired(&mut self, now: Instant) -> Option<ConsoleMessage> {
+        match &self.pending {
+            Some((_, last_seen)) if now.duration_since(*last_seen) > self.window => self.flush(),
+            _ => None,
+        }
+    }
+
+    /// Release the held message unconditionally
+    pub fn flush(&mut self) -> Option<ConsoleMessage> {
+        self.pending.take().map(|(msg, _)| Self::finish(msg))
+    }
+
+    fn finish(mut msg: ConsoleMessage) -> ConsoleMessage {
+        if msg.repeat_count > 1 {
+            msg.message = format!("{} (x{})", msg.message, msg.repeat_count);
+        }
+        msg
+    }
+}
+
+/// Parse a Radio Event (tag 0x00) payload.
+///
+/// Layout: the whole payload is a UTF-8 status message with no length prefix
+/// or timestamp. The protocol carries no severity, so messages mentioning a
+/// loss or failure are reported as warnings and everything else as info.
+fn parse_radio_event(data: &[u8]) -> Option<RadioEvent> {
+    let message = String::from_utf8_lossy(data)
+        .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+        .to_string();
+    if message.is_empty() {
+        return None;
+    }
+    let lower = message.to_lowercase();
+    let severity = if ["disconnect", "lost", "fail", "error", "timeout"]
+        .iter()
+        .any(|w| lower.contains(w))
+    {
+        AlertSeverity::Warning
+    } else {
+        AlertSeverity::Info
+    };
+    Some(RadioEvent { message, severity })
+}
+
 /// Read a length-prefixed string: 2-byte BE length + UTF-8 bytes
 fn read_prefixed_string(data: &[u8], offset: usize) -> Option<(String, usize)> {
     if offset + 2 > data.len() {
@@ -80,13 +341,139 @@ fn read_prefixed_string(data: &[u8], offset: usize) -> Option<(String, usize)> {
     Some((s, start + len))
 }
 
+/// Line prefixes that mark a standard output line as a warning or error, for
+/// robot code that logs problems with `System.out`/`print` instead of the
+/// error reporting API. Matched case-insensitively after leading whitespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityPatterns {
+    pub error: Vec<String>,
+    pub warning: Vec<String>,
+}
+
+impl Default for SeverityPatterns {
+    fn default() -> Self {
+        Self {
+            error: vec!["[ERROR]".into(), "ERROR:".into()],
+            warning: vec!["[WARNING]".into(), "[WARN]".into(), "WARNING:".into()],
+        }
+    }
+}
+
+impl SeverityPatterns {
+    /// (is_error, is_warning) for a standard output line
+    pub fn classify(&self, message: &str) -> (bool, bool) {
+        let line = message.trim_start().to_lowercase();
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|p| !p.is_empty() && line.starts_with(&p.to_lowercase()))
+        };
+        if matches(&self.error) {
+            (true, false)
+        } else if matches(&self.warning) {
+            (false, true)
+        } else {
+            (false, false)
+        }
+    }
+}
+
+/// Parse a Standard Output (tag 0x0C) payload: timestamp(4 f32) + seqnum(2) +
+/// message(n). Severity comes from `patterns` since the tag carries none.
+fn parse_stdout_message(data: &[u8], patterns: &SeverityPatterns) -> Option<ConsoleMessage> {
+    if data.len() < 6 {
+        return None;
+    }
+    let timestamp = f32::from_be_bytes([data[0], data[1], data[2], data[3]]) as f64;
+    let sequence = u16::from_be_bytes([data[4], data[5]]);
+    let message = String::from_utf8_lossy(&data[6..]).trim_end().to_string();
+    let (is_error, is_warning) = patterns.classify(&message);
+    Some(ConsoleMessage {
+        timestamp,
+        message,
+        is_error,
+        is_warning,
+        sequence,
+        repeat_count: 1,
+        error: None,
+    })
+}
+
+/// Parse an Error/Warning Message (tag 0x0B) payload:
+///   timestamp(4 f32) + seqnum(2) + unknown(2) + error_code(4 i32) + flags(1) +
+///   details(2+n) + location(2+n) + callstack(2+n)
+/// Flags bit 0 set means error, clear means warning. Returns None when the
+/// fixed header is incomplete; missing strings are left empty.
+fn parse_error_message(data: &[u8]) -> Option<ConsoleMessage> {
+    if data.len() < 13 {
+        return None;
+    }
+    let timestamp = f32::from_be_bytes([data[0], data[1], data[2], data[3]]) as f64;
+    let sequence = u16::from_be_bytes([data[4], data[5]]);
+    let error_code = i32::from_be_bytes([data[8], data[9], data[10], data[11]]);
+    let is_error = (data[12] & 0x01) != 0;
+
+    let mut offset = 13;
+    let mut next_string = || {
+        let (s, next) = read_prefixed_string(data, offset)?;
+        offset = next;
+        Some(s)
+    };
+    let details = next_string().unwrap_or_default();
+    let location = next_string().unwrap_or_default();
+    let call_stack = next_string().unwrap_or_default();
+
+    // Build a readable message from the structured fields
+    let mut message = details.clone();
+    if !location.is_empty() {
+        message = format!("{message} @ {location}");
+    }
+    if !call_stack.is_empty() {
+        message = format!("{message}\n{call_stack}");
+    }
+
+    Some(ConsoleMessage {
+        timestamp,
+        message,
+        is_error,
+        is_warning: !is_error,
+        sequence,
+        repeat_count: 1,
+        error: Some(ConsoleError { error_code, details, location, call_stack }),
+    })
+}
+
+/// Queue a console message without waiting on downstream consumers (file
+/// writer, UI), so a slow disk never stalls reading the TCP stream. Messages
+/// that don't fit in the channel are dropped and counted.
+fn forward_console(
+    log_tx: &mpsc::Sender<ConsoleMessage>,
+    msg: ConsoleMessage,
+    socket_status: &RwLock<SocketStatus>,
+) {
+    if let Err(mpsc::error::TrySendError::Full(_)) = log_tx.try_send(msg) {
+        let dropped = {
+            let mut status = socket_status.write();
+            status.console_dropped += 1;
+            status.console_dropped
+        };
+        if dropped == 1 || dropped % 1000 == 0 {
+            tracing::warn!("Console consumers falling behind, {dropped} messages dropped");
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn read_console_stream(
-    mut stream: TcpStream,
+    mut stream: OwnedReadHalf,
     log_tx: &mpsc::Sender<ConsoleMessage>,
     power_tx: &mpsc::Sender<PowerData>,
     shutdown_rx: &mut watch::Receiver<bool>,
     target_ip_rx: &mut watch::Receiver<String>,
     version_tx: &mpsc::Sender<VersionInfo>,
+    radio_tx: &mpsc::Sender<RadioEvent>,
+    socket_status: &RwLock<SocketStatus>,
+    severity_patterns: &RwLock<SeverityPatterns>,
 ) -> Result<()> {
     // Accumulate power data across tags (0x04 and 0x05 arrive separately)
     let mut power = PowerData::default();
@@ -126,74 +513,18 @@ async fn read_console_stream(
         match tag {
             // Standard Output (0x0C): timestamp(4 f32) + seqnum(2) + message
             0x0C => {
-                if data.len() >= 6 {
-                    let timestamp = f32::from_be_bytes([
-                        data[0], data[1], data[2], data[3],
-                    ]) as f64;
-                    let sequence = u16::from_be_bytes([data[4], data[5]]);
-                    let message = String::from_utf8_lossy(&data[6..])
-                        .trim_end()
-                        .to_string();
-
-                    if !message.is_empty() {
-                        let _ = log_tx.send(ConsoleMessage {
-                            timestamp,
-                            message,
-                            is_error: false,
-                            is_warning: false,
-                            sequence,
-                        }).await;
+                let msg = parse_stdout_message(data, &severity_patterns.read());
+                if let Some(msg) = msg {
+                    if !msg.message.is_empty() {
+                        forward_console(log_tx, msg, socket_status);
                     }
                 }
             }
-            // Error/Warning Message (0x0B): timestamp(4) + seqnum(2) + unknown(2) + error_code(4)
-            //   + flags(1) + details(2+n) + location(2+n) + callstack(2+n)
-            //   flags bit 0: 1=error, 0=warning
+            // Error/Warning Message (0x0B), see `parse_error_message`
             0x0B => {
-                if data.len() >= 13 {
-                    let timestamp = f32::from_be_bytes([
-                        data[0], data[1], data[2], data[3],
-                    ]) as f64;
-                    let sequence = u16::from_be_bytes([data[4], data[5]]);
-                    // data[6..8] = unknown (2 bytes)
-                    // data[8..12] = error_code (4 bytes i32)
-                    let flags = data[12];
-                    let is_error = (flags & 0x01) != 0;
-                    let is_warning = !is_error;
-
-                    // Parse length-prefixed strings: Details, Location, Call Stack
-                    let mut offset = 13;
-                    let details = read_prefixed_string(data, offset);
-                    if let Some((ref _s, next)) = details {
-                        offset = next;
-                    }
-                    let location = read_prefixed_string(data, offset);
-                    if let Some((ref _s, next)) = location {
-                        offset = next;
-                    }
-                    let callstack = read_prefixed_string(data, offset);
-
-                    // Build a readable message from the structured fields
-                    let details_str = details.map(|(s, _)| s).unwrap_or_default();
-                    let location_str = location.map(|(s, _)| s).unwrap_or_default();
-                    let callstack_str = callstack.map(|(s, _)| s).unwrap_or_default();
-
-                    let mut message = details_str;
-                    if !location_str.is_empty() {
-                        message = format!("{message} @ {location_str}");
-                    }
-                    if !callstack_str.is_empty() {
-                        message = format!("{message}\n{callstack_str}");
-                    }
-
-                    if !message.is_empty() {
-                        let _ = log_tx.send(ConsoleMessage {
-                            timestamp,
-                            message,
-                            is_error,
-                            is_warning,
-                            sequence,
-                        }).await;
+                if let Some(msg) = parse_error_message(data) {
+                    if !msg.message.is_empty() {
+                        forward_console(log_tx, msg, socket_status);
                     }
                 } else if data.len() >= 6 {
                     // Fallback: treat like stdout format
@@ -206,13 +537,15 @@ async fn read_console_stream(
                         .to_string();
 
                     if !message.is_empty() {
-                        let _ = log_tx.send(ConsoleMessage {
+                        forward_console(log_tx, ConsoleMessage {
                             timestamp,
                             message,
                             is_error: true,
                             is_warning: false,
                             sequence,
-                        }).await;
+                            repeat_count: 1,
+                            error: None,
+                        }, socket_status);
                     }
                 }
             }
@@ -254,6 +587,13 @@ async fn read_console_stream(
                 tracing::info!("Version info: image={}, wpilib={}, rio={}", info.image_version, info.wpilib_version, info.rio_version);
                 let _ = version_tx.send(info).await;
             }
+            // Radio Events (0x00): message(n)
+            0x00 => {
+                if let Some(event) = parse_radio_event(data) {
+                    tracing::info!("Radio event: {}", event.message);
+                    let _ = radio_tx.send(event).await;
+                }
+            }
             // Other tags — log for debugging but don't display
             other => {
                 if !data.is_empty() {
@@ -263,3 +603,189 @@ async fn read_console_stream(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{JoystickLayout, HID_TYPE_XINPUT_GAMEPAD};
+
+    fn message(text: &str) -> ConsoleMessage {
+        ConsoleMessage {
+            timestamp: 0.0,
+            message: text.to_string(),
+            is_error: false,
+            is_warning: false,
+            sequence: 0,
+            repeat_count: 1,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn repeats_inside_the_window_are_coalesced() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut coalescer = ConsoleCoalescer::new(COALESCE_WINDOW);
+
+        assert!(coalescer.push(message("Loop overrun"), ms(0)).is_none());
+        // Each repeat extends the window from the latest one
+        assert!(coalescer.push(message("Loop overrun"), ms(400)).is_none());
+        assert!(coalescer.push(message("Loop overrun"), ms(800)).is_none());
+        assert!(coalescer.flush_expired(ms(1200)).is_none());
+
+        let run = coalescer.push(message("Other"), ms(1250)).unwrap();
+        assert_eq!(run.message, "Loop overrun (x3)");
+        assert_eq!(run.repeat_count, 3);
+
+        // Released unchanged once its window passes without a repeat
+        assert_eq!(coalescer.flush_expired(ms(1751)).unwrap().message, "Other");
+        assert!(coalescer.push(message("Other"), ms(1800)).is_none());
+        assert_eq!(coalescer.flush().unwrap().repeat_count, 1);
+    }
+
+    #[test]
+    fn radio_event_payload_is_parsed() {
+        let event = parse_radio_event(b"Radio link lost to 10.12.34.1\0\n").unwrap();
+        assert_eq!(event.message, "Radio link lost to 10.12.34.1");
+        assert_eq!(event.severity, AlertSeverity::Warning);
+
+        let event = parse_radio_event(b"Radio connected").unwrap();
+        assert_eq!(event.severity, AlertSeverity::Info);
+        assert!(parse_radio_event(b" \0").is_none());
+    }
+
+    #[test]
+    fn stalled_consumers_drop_messages_instead_of_blocking() {
+        // Nothing drains the channel, like a file writer stuck on a slow disk
+        let (log_tx, mut log_rx) = mpsc::channel(2);
+        let status = RwLock::new(SocketStatus::default());
+        for i in 0..5 {
+            forward_console(&log_tx, message(&format!("line {i}")), &status);
+        }
+        assert_eq!(status.read().console_dropped, 3);
+        assert_eq!(log_rx.try_recv().unwrap().message, "line 0");
+        assert_eq!(log_rx.try_recv().unwrap().message, "line 1");
+        assert!(log_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn descriptor_frame_for_an_xbox_controller() {
+        let desc = JoystickDescriptor {
+            name: "Xbox".into(),
+            is_xbox: true,
+            hid_type: HID_TYPE_XINPUT_GAMEPAD,
+            layout: JoystickLayout { axes: 3, buttons: 10, povs: 1 },
+        };
+        assert_eq!(
+            build_descriptor_frame(2, &desc),
+            [
+                0, 15, // size: tag through the POV count
+                0x02, 2, 1, HID_TYPE_XINPUT_GAMEPAD, 4, b'X', b'b', b'o', b'x',
+                3, 0, 1, 2, // axis count, then axis types
+                10, 1,
+            ]
+        );
+    }
+
+    #[test]
+    fn error_message_decodes_all_three_strings() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&12.5f32.to_be_bytes());
+        data.extend_from_slice(&7u16.to_be_bytes());
+        data.extend_from_slice(&[0, 1]);
+        data.extend_from_slice(&(-44004i32).to_be_bytes());
+        data.push(0x01);
+        for s in ["Joystick Button 3 missing", "DriverStation.java:123", "at Robot.teleop()"] {
+            data.extend_from_slice(&(s.len() as u16).to_be_bytes());
+            data.extend_from_slice(s.as_bytes());
+        }
+
+        let msg = parse_error_message(&data).unwrap();
+        assert_eq!(msg.timestamp, 12.5);
+        assert_eq!(msg.sequence, 7);
+        assert!(msg.is_error && !msg.is_warning);
+        assert_eq!(
+            msg.message,
+            "Joystick Button 3 missing @ DriverStation.java:123\nat Robot.teleop()"
+        );
+        let error = msg.error.unwrap();
+        assert_eq!(error.error_code, -44004);
+        assert_eq!(error.details, "Joystick Button 3 missing");
+        assert_eq!(error.location, "DriverStation.java:123");
+        assert_eq!(error.call_stack, "at Robot.teleop()");
+
+        // Flag bit clear is a warning; a short header is rejected
+        data[12] = 0;
+        assert!(parse_error_message(&data).unwrap().is_warning);
+        assert!(parse_error_message(&data[..12]).is_none());
+    }
+
+    #[test]
+    fn console_levels_allow_their_severity_and_above() {
+        let info = message("info");
+        let warning = ConsoleMessage { is_warning: true, ..message("warning") };
+        let error = ConsoleMessage { is_error: true, ..message("error") };
+        let allowed = |level: ConsoleLevel| {
+            [&info, &warning, &error].map(|msg| level.allows(msg))
+        };
+        assert_eq!(allowed(ConsoleLevel::All), [true, true, true]);
+        assert_eq!(allowed(ConsoleLevel::Warning), [false, true, true]);
+        assert_eq!(allowed(ConsoleLevel::Error), [false, false, true]);
+
+        assert_eq!(ConsoleLevel::parse("None"), Ok(ConsoleLevel::All));
+        assert_eq!(ConsoleLevel::parse("WARNING"), Ok(ConsoleLevel::Warning));
+        assert!(ConsoleLevel::parse("verbose").is_err());
+    }
+
+    #[test]
+    fn stdout_prefixes_set_the_severity() {
+        let patterns = SeverityPatterns::default();
+        let stdout = |text: &str| {
+            let mut data = 3.0f32.to_be_bytes().to_vec();
+            data.extend_from_slice(&9u16.to_be_bytes());
+            data.extend_from_slice(text.as_bytes());
+            parse_stdout_message(&data, &patterns).unwrap()
+        };
+
+        let error = stdout("[ERROR] foo");
+        assert!(error.is_error && !error.is_warning);
+        assert_eq!((error.message.as_str(), error.sequence), ("[ERROR] foo", 9));
+        let warning = stdout("  warning: battery low");
+        assert!(warning.is_warning && !warning.is_error);
+        let plain = stdout("Error count: 0");
+        assert!(!plain.is_error && !plain.is_warning);
+        assert!(parse_stdout_message(&[0; 5], &patterns).is_none());
+    }
+
+    #[tokio::test]
+    async fn listener_returns_once_shutdown_is_signalled() {
+        // Both before a target is known and while retrying a loopback target
+        for target in ["", "127.0.0.1"] {
+            let (_target_tx, target_rx) = watch::channel(target.to_string());
+            let (shutdown_tx, shutdown_rx) = watch::channel(false);
+            let (log_tx, _log_rx) = mpsc::channel(8);
+            let (power_tx, _power_rx) = mpsc::channel(8);
+            let (version_tx, _version_rx) = mpsc::channel(8);
+            let (radio_tx, _radio_rx) = mpsc::channel(8);
+            let task = tokio::spawn(console_log_listener(
+                target_rx,
+                log_tx,
+                power_tx,
+                shutdown_rx,
+                version_tx,
+                radio_tx,
+                Arc::default(),
+                Arc::default(),
+                Arc::default(),
+            ));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(!task.is_finished(), "target {target:?}");
+
+            shutdown_tx.send(true).unwrap();
+            tokio::time::timeout(Duration::from_secs(1), task)
+                .await
+                .unwrap_or_else(|_| panic!("listener kept running for target {target:?}"))
+                .unwrap();
+        }
+    }
+}
@@ -11,33 +11,32 @@ pub struct NetworkInfo {
     pub wifi: bool,
     /// A USB-tethered roboRIO interface (172.22.11.x) is present
     pub usb: bool,
+    /// Any non-loopback interface has an address of either family (including
+    /// IPv6 link-local), even if `enet_link` is false
+    pub any_link: bool,
 }
 
 /// Scan local network interfaces for link, WiFi, and USB status
 pub fn check_interfaces() -> NetworkInfo {
-    let ifaces = match if_addrs::get_if_addrs() {
-        Ok(i) => i,
-        Err(_) => {
-            return NetworkInfo {
-                enet_link: false,
-                enet_ip: None,
-                wifi: false,
-                usb: false,
-            };
-        }
-    };
+    let ifaces = if_addrs::get_if_addrs().unwrap_or_default();
+    classify_interfaces(&ifaces)
+}
 
+/// Derive link, WiFi, and USB status from an interface list
+fn classify_interfaces(ifaces: &[if_addrs::Interface]) -> NetworkInfo {
     let mut enet_link = false;
+    let mut any_link = false;
     let mut enet_ip: Option<String> = None;
     let mut wifi = false;
     let mut usb = false;
 
     let mut wifi_ip: Option<String> = None;
 
-    for iface in &ifaces {
+    for iface in ifaces {
         if iface.is_loopback() {
             continue;
         }
+        any_link = true;
         let ip = iface.addr.ip();
         if !ip.is_ipv4() {
             continue;
@@ -81,12 +80,16 @@ pub fn check_interfaces() -> NetworkInfo {
         enet_ip,
         wifi,
         usb,
+        any_link,
     }
 }
 
-/// Try a quick TCP connect to the radio (port 80) with a short timeout
-pub async fn check_radio(radio_ip: &str) -> bool {
-    let addr = format!("{radio_ip}:80");
+/// NetworkTables 4 server port on the robot
+pub const NT4_PORT: u16 = 5810;
+
+/// Try a quick TCP connect to `host:port` with a short timeout
+async fn port_open(host: &str, port: u16) -> bool {
+    let addr = format!("{host}:{port}");
     tokio::time::timeout(
         Duration::from_millis(200),
         TcpStream::connect(&addr),
@@ -96,7 +99,30 @@ pub async fn check_radio(radio_ip: &str) -> bool {
     .unwrap_or(false)
 }
 
-/// Derive the radio IP from the team number (10.TE.AM.1)
+/// Try a quick TCP connect to the radio (port 80) with a short timeout
+pub async fn check_radio(radio_ip: &str) -> bool {
+    port_open(radio_ip, 80).await
+}
+
+/// Probe the roboRIO's SSH (22) and console (1740) ports with a short timeout.
+/// Succeeds once the RIO has booted, even if robot code isn't running yet.
+pub async fn check_robot(robot_ip: &str) -> bool {
+    for port in [22, 1740] {
+        if port_open(robot_ip, port).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether the robot's NetworkTables server accepts connections. Only
+/// reachability: no NT client handshake is done.
+pub async fn check_networktables(robot_ip: &str, port: u16) -> bool {
+    port_open(robot_ip, port).await
+}
+
+/// Derive the radio IP from the team number (10.TE.AM.1). Assumes
+/// team <= 25599, like `TeamIpResolver::team_to_ip`.
 pub fn team_to_radio_ip(team: u32) -> String {
     if team == 0 {
         return "127.0.0.1".to_string();
@@ -105,3 +131,69 @@ pub fn team_to_radio_ip(team: u32) -> String {
     let am = team % 100;
     format!("10.{te}.{am}.1")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use if_addrs::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+
+    fn iface(name: &str, ip: &str) -> Interface {
+        let addr = match ip.parse().unwrap() {
+            std::net::IpAddr::V4(ip) => IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask: [255, 255, 255, 0].into(),
+                prefixlen: 24,
+                broadcast: None,
+            }),
+            std::net::IpAddr::V6(ip) => IfAddr::V6(Ifv6Addr {
+                ip,
+                netmask: [0xffff, 0xffff, 0xffff, 0xffff, 0, 0, 0, 0].into(),
+                prefixlen: 64,
+                broadcast: None,
+            }),
+        };
+        Interface {
+            name: name.to_string(),
+            addr,
+            index: None,
+            #[cfg(windows)]
+            adapter_name: String::new(),
+        }
+    }
+
+    #[test]
+    fn ipv6_only_link_counts_as_any_link() {
+        let info = classify_interfaces(&[iface("lo", "::1"), iface("eth0", "fe80::1")]);
+        assert!(info.any_link);
+        assert!(!info.enet_link);
+        assert_eq!(info.enet_ip, None);
+    }
+
+    #[test]
+    fn dual_stack_link_reports_the_ipv4_address() {
+        let info = classify_interfaces(&[
+            iface("eth0", "fe80::1"),
+            iface("eth0", "10.12.34.5"),
+            iface("wlan0", "192.168.1.20"),
+        ]);
+        assert!(info.any_link && info.enet_link && info.wifi);
+        assert_eq!(info.enet_ip.as_deref(), Some("10.12.34.5"));
+    }
+
+    #[test]
+    fn loopback_only_has_no_link() {
+        let info = classify_interfaces(&[iface("lo", "127.0.0.1"), iface("lo", "::1")]);
+        assert!(!info.any_link && !info.enet_link && !info.wifi && !info.usb);
+    }
+
+    #[tokio::test]
+    async fn networktables_check_sees_a_local_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(check_networktables("127.0.0.1", port).await);
+
+        // Nothing listens on the port once it is closed
+        drop(listener);
+        assert!(!check_networktables("127.0.0.1", port).await);
+    }
+}
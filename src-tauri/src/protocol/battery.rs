@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Packets averaged to smooth the battery voltage (~0.5s at 50Hz)
+const SMOOTHING_WINDOW: usize = 25;
+
+/// How far above a threshold the smoothed voltage must recover before that
+/// warning can fire again
+const HYSTERESIS: f32 = 0.3;
+
+/// Low-battery severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BatteryLevel {
+    Warning,
+    Critical,
+}
+
+/// Voltages below which the battery is reported low
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BatteryThresholds {
+    pub warn: f32,
+    pub critical: f32,
+}
+
+impl Default for BatteryThresholds {
+    fn default() -> Self {
+        Self { warn: 7.5, critical: 6.8 }
+    }
+}
+
+/// Tracks the smoothed battery voltage and reports downward threshold
+/// crossings. Each level fires once until the voltage recovers above its
+/// threshold plus `HYSTERESIS`.
+#[derive(Debug, Default)]
+pub struct BatteryMonitor {
+    thresholds: BatteryThresholds,
+    samples: VecDeque<f32>,
+    level: Option<BatteryLevel>,
+}
+
+impl BatteryMonitor {
+    pub fn set_thresholds(&mut self, thresholds: BatteryThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Forget readings and the current level, e.g. when the robot disconnects
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.level = None;
+    }
+
+    /// Feed one voltage reading. Returns the new level and smoothed voltage
+    /// when the battery drops into a worse level.
+    pub fn update(&mut self, voltage: f32) -> Option<(BatteryLevel, f32)> {
+        // No reading (e.g. robot code not reporting yet)
+        if voltage <= 0.0 {
+            return None;
+        }
+        self.samples.push_back(voltage);
+        if self.samples.len() > SMOOTHING_WINDOW {
+            self.samples.pop_front();
+        }
+        // Don't judge a single transient sag
+        if self.samples.len() < SMOOTHING_WINDOW {
+            return None;
+        }
+        let smoothed = self.samples.iter().sum::<f32>() / self.samples.len() as f32;
+
+        let t = self.thresholds;
+        let level = if smoothed < t.critical
+            || (self.level == Some(BatteryLevel::Critical) && smoothed < t.critical + HYSTERESIS)
+        {
+            Some(BatteryLevel::Critical)
+        } else if smoothed < t.warn || (self.level.is_some() && smoothed < t.warn + HYSTERESIS) {
+            Some(BatteryLevel::Warning)
+        } else {
+            None
+        };
+
+        let worse = level > self.level;
+        self.level = level;
+        match level {
+            Some(level) if worse => Some((level, smoothed)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Levels fired while feeding `from`..`to` in 10mV steps, with ±0.1V
+    /// packet-to-packet noise so the smoothed value wobbles at each threshold
+    fn ramp(monitor: &mut BatteryMonitor, from: f32, to: f32) -> Vec<BatteryLevel> {
+        let steps = ((to - from).abs() / 0.01) as usize;
+        (0..=steps)
+            .filter_map(|i| {
+                let noise = if i % 2 == 0 { 0.1 } else { -0.1 };
+                let voltage = from + (to - from) * i as f32 / steps as f32 + noise;
+                monitor.update(voltage).map(|(level, _)| level)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn falling_voltage_warns_once_per_level() {
+        let mut monitor = BatteryMonitor::default();
+        assert_eq!(
+            ramp(&mut monitor, 12.0, 6.0),
+            [BatteryLevel::Warning, BatteryLevel::Critical]
+        );
+        // Recovering is silent, and re-arms the warnings
+        assert!(ramp(&mut monitor, 6.0, 12.0).is_empty());
+        assert_eq!(
+            ramp(&mut monitor, 12.0, 6.0),
+            [BatteryLevel::Warning, BatteryLevel::Critical]
+        );
+    }
+
+    #[test]
+    fn hovering_at_a_threshold_does_not_refire() {
+        let mut monitor = BatteryMonitor::default();
+        assert_eq!(ramp(&mut monitor, 8.0, 7.3), [BatteryLevel::Warning]);
+        // Back up, but not past warn + HYSTERESIS, then down again
+        assert!(ramp(&mut monitor, 7.3, 7.7).is_empty());
+        assert!(ramp(&mut monitor, 7.7, 7.3).is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use byteorder::{BigEndian, WriteBytesExt};
 use parking_lot::RwLock;
@@ -10,7 +10,16 @@ use tokio::sync::mpsc;
 
 use tokio::sync::watch;
 
+use crate::gamepad::latency::SharedLatencyProbe;
+use crate::gamepad::mapping::ButtonRemap;
 use crate::system_info::SystemInfoData;
+use super::battery::{BatteryLevel, BatteryMonitor, BatteryThresholds};
+use super::monitor::{RobotMonitor, PRIMARY_ROBOT_ID};
+use super::resolve::{Resolution, TargetResolver};
+use super::simulator::SimulatedRobot;
+use super::snapshot::{TelemetryHistory, TelemetrySample};
+use super::link::{classify_link, LinkQuality, LinkStats, Quality};
+use super::timeline::{DisconnectReason, SessionTimeline, TimelineEvent};
 use super::types::*;
 
 /// Convert days since Unix epoch to (year, month, day)
@@ -29,11 +38,298 @@ fn days_to_date(days: u64) -> (u16, u8, u8) {
     (y, m, d)
 }
 
+/// Current wall-clock time as fractional seconds since the Unix epoch
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn alert_event(kind: AlertKind, message: &str) -> DsEvent {
+    DsEvent::Alert(Alert::new(kind, message, unix_now()))
+}
+
+/// Returns the event for a robot code start/stop edge, if one occurred
+fn code_transition_event(was_running: bool, running: bool) -> Option<DsEvent> {
+    match (was_running, running) {
+        (false, true) => Some(DsEvent::RobotCodeStarted { timestamp: unix_now() }),
+        (true, false) => Some(DsEvent::RobotCodeStopped { timestamp: unix_now() }),
+        _ => None,
+    }
+}
+
+/// Default time without any frontend command before an enabled robot is disabled
+pub const DEFAULT_WATCHDOG_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Whether the enable watchdog should disable the robot
+fn watchdog_expired(enabled: bool, since_last_command: Duration, timeout: Duration) -> bool {
+    enabled && since_last_command > timeout
+}
+
+/// Default time without a robot reply before it is marked disconnected
+pub const DEFAULT_DISCONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Whether the robot has gone quiet long enough to count as disconnected
+fn comms_lost(since_last_recv: Duration, timeout: Duration) -> bool {
+    since_last_recv > timeout
+}
+
+/// Receive buffer size: the maximum UDP payload over IPv4
+const MAX_UDP_PACKET: usize = 65_507;
+
+/// Consecutive robot packets reporting "enabled" while the DS commands
+/// disabled before the robot is considered stuck enabled (~200ms at 50Hz)
+const STUCK_ENABLED_THRESHOLD: u32 = 10;
+
+/// Detects a robot that keeps reporting enabled after the DS disabled it.
+/// Fires once per episode; resets as soon as the two states agree again.
+#[derive(Debug, Default)]
+struct StuckEnabledDetector {
+    mismatched_packets: u32,
+    alerted: bool,
+}
+
+impl StuckEnabledDetector {
+    /// Feed one inbound packet; returns true when the alert should fire
+    fn update(&mut self, ds_enabled: bool, robot_enabled: bool) -> bool {
+        if ds_enabled || !robot_enabled {
+            self.mismatched_packets = 0;
+            self.alerted = false;
+            return false;
+        }
+        self.mismatched_packets += 1;
+        if self.mismatched_packets >= STUCK_ENABLED_THRESHOLD && !self.alerted {
+            self.alerted = true;
+            return true;
+        }
+        false
+    }
+}
+
+/// Debounces re-resolved robot addresses: a new address must come back from
+/// two consecutive re-discoveries before the target follows it, so a lookup
+/// alternating between mDNS and the static fallback doesn't flap the link.
+#[derive(Debug, Default)]
+struct AddressChangeFilter {
+    candidate: Option<String>,
+}
+
+impl AddressChangeFilter {
+    /// Feed one resolved address; returns true when the target should switch
+    fn confirm(&mut self, current: &str, resolved: &str) -> bool {
+        if resolved == current {
+            self.candidate = None;
+            return false;
+        }
+        if self.candidate.as_deref() == Some(resolved) {
+            self.candidate = None;
+            return true;
+        }
+        self.candidate = Some(resolved.to_string());
+        false
+    }
+}
+
+/// Length of the autonomous period timed by the practice match timer
+const PRACTICE_AUTO_DURATION: Duration = Duration::from_secs(15);
+/// Length of the teleop period timed by the practice match timer
+const PRACTICE_TELEOP_DURATION: Duration = Duration::from_secs(135);
+
+/// Practice match timer used as the match-time source off FMS. Starts a
+/// period countdown when the DS enables and restarts it on a mode change,
+/// mirroring the frontend's practice timer.
+#[derive(Debug, Default)]
+struct PracticeTimer {
+    running: Option<(Mode, Instant)>,
+}
+
+impl PracticeTimer {
+    /// Feed the commanded enable state and mode once per tick
+    fn update(&mut self, enabled: bool, mode: Mode, now: Instant) {
+        match self.running {
+            _ if !enabled => self.running = None,
+            Some((running_mode, _)) if running_mode == mode => {}
+            _ => self.running = Some((mode, now)),
+        }
+    }
+
+    /// Seconds left in the current period, None while the timer is stopped
+    fn remaining(&self, now: Instant) -> Option<f32> {
+        let (mode, started) = self.running?;
+        let duration = match mode {
+            Mode::Autonomous => PRACTICE_AUTO_DURATION,
+            Mode::Teleoperated | Mode::Test => PRACTICE_TELEOP_DURATION,
+        };
+        Some(duration.saturating_sub(now.duration_since(started)).as_secs_f32())
+    }
+}
+
+/// How long the robot has to echo a latched E-Stop before the driver is warned
+const ESTOP_CONFIRM_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Outcome of watching the robot's status byte after an E-Stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EStopCheck {
+    Confirmed,
+    Unconfirmed,
+}
+
+/// Tracks whether the robot has echoed a latched E-Stop. The warning fires
+/// once after `ESTOP_CONFIRM_TIMEOUT`; a late echo still confirms.
+#[derive(Debug, Default)]
+struct EStopConfirmation {
+    /// When the E-Stop latched, and whether the timeout warning was sent
+    pending: Option<(Instant, bool)>,
+}
+
+impl EStopConfirmation {
+    fn latched(&mut self, now: Instant) {
+        self.pending = Some((now, false));
+    }
+
+    /// The latch was cleared (roboRIO reboot): stop waiting
+    fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// Feed the robot's reported E-Stop bit from a status packet
+    fn on_status(&mut self, robot_estopped: bool) -> Option<EStopCheck> {
+        if robot_estopped && self.pending.take().is_some() {
+            return Some(EStopCheck::Confirmed);
+        }
+        None
+    }
+
+    fn check_timeout(&mut self, now: Instant) -> Option<EStopCheck> {
+        let (since, warned) = self.pending.as_mut()?;
+        if *warned || now.duration_since(*since) < ESTOP_CONFIRM_TIMEOUT {
+            return None;
+        }
+        *warned = true;
+        Some(EStopCheck::Unconfirmed)
+    }
+}
+
+/// Minimum spacing of packet captures per direction (10Hz each, 20Hz total)
+const CAPTURE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Rate limiter for packet capture events
+#[derive(Debug, Default)]
+struct CaptureThrottle {
+    last: Option<Instant>,
+}
+
+impl CaptureThrottle {
+    /// Whether a capture may be emitted at `now`; records it if so
+    fn ready(&mut self, now: Instant) -> bool {
+        if self.last.is_some_and(|last| now.duration_since(last) < CAPTURE_INTERVAL) {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+fn hex_string(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// List the tags in a packet from byte `start` (8 inbound, 6 outbound),
+/// stopping at the first malformed one
+fn packet_tags(data: &[u8], start: usize) -> Vec<CapturedTag> {
+    let mut tags = Vec::new();
+    let mut i = start;
+    while i + 1 < data.len() {
+        let size = data[i] as usize;
+        if size == 0 || i + 1 + size > data.len() {
+            break;
+        }
+        tags.push(CapturedTag { id: data[i + 1], len: size - 1 });
+        i += 1 + size;
+    }
+    tags
+}
+
+fn packet_capture_event(direction: PacketDirection, data: &[u8]) -> DsEvent {
+    DsEvent::PacketCapture(PacketCapture {
+        direction,
+        bytes: hex_string(data),
+        seq: data.get(..2).map_or(0, |b| u16::from_be_bytes([b[0], b[1]])),
+        tags: match direction {
+            PacketDirection::Inbound => packet_tags(data, 8),
+            PacketDirection::Outbound => Vec::new(),
+        },
+    })
+}
+
+/// Header fields and tag layout of an outbound packet
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutboundSummary {
+    pub comm_version: u8,
+    pub estop: bool,
+    pub enabled: bool,
+    pub mode: Mode,
+    pub reboot: bool,
+    pub restart_code: bool,
+    /// None for an out-of-range station byte
+    pub alliance: Option<Alliance>,
+    /// Payload length of each joystick tag (0x0C), in slot order
+    pub joystick_tag_sizes: Vec<usize>,
+    pub tags: Vec<CapturedTag>,
+}
+
+/// Decode the header of a packet from `build_outbound_packet`; None if it is
+/// too short to have one
+pub fn summarize_outbound(pkt: &[u8]) -> Option<OutboundSummary> {
+    let &[_, _, comm_version, control, request, alliance, ..] = pkt else {
+        return None;
+    };
+    let tags = packet_tags(pkt, 6);
+    Some(OutboundSummary {
+        comm_version,
+        estop: control & 0x80 != 0,
+        enabled: control & 0x04 != 0,
+        mode: Mode::from_bits(control),
+        reboot: request & 0x08 != 0,
+        restart_code: request & 0x04 != 0,
+        alliance: Alliance::from_byte(alliance),
+        joystick_tag_sizes: tags.iter().filter(|t| t.id == 0x0C).map(|t| t.len).collect(),
+        tags,
+    })
+}
+
+/// The last control packet sent to the robot, decoded for debugging
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboundPacket {
+    pub hex: String,
+    pub seq: u16,
+    pub parsed: OutboundSummary,
+}
+
+impl OutboundPacket {
+    pub fn decode(pkt: &[u8]) -> Option<Self> {
+        Some(Self {
+            hex: hex_string(pkt),
+            seq: u16::from_be_bytes([*pkt.first()?, *pkt.get(1)?]),
+            parsed: summarize_outbound(pkt)?,
+        })
+    }
+}
+
+/// Raw bytes of the last control packet built by the protocol loop (empty
+/// before the first). Decoded only when read.
+pub type SharedOutboundPacket = Arc<RwLock<Vec<u8>>>;
+
 /// Builds the DS→Robot UDP packet (sent to port 1110 every 20ms)
-fn build_outbound_packet(
+pub(super) fn build_outbound_packet(
     seq: u16,
     state: &DsState,
     joysticks: &[JoystickState],
+    robot_requests: &RobotFeatureRequests,
 ) -> Vec<u8> {
     let mut pkt = Vec::with_capacity(64);
 
@@ -41,7 +337,7 @@ fn build_outbound_packet(
     pkt.extend_from_slice(&seq.to_be_bytes());
 
     // Byte 2: Comm version tag
-    pkt.push(0x01);
+    pkt.push(state.comm_version);
 
     // Byte 3: Control byte
     let mut control: u8 = 0;
@@ -56,24 +352,35 @@ fn build_outbound_packet(
 
     // Byte 4: Request byte
     let mut request: u8 = 0;
-    if state.request_reboot {
+    if state.reboot_packets_left > 0 {
         request |= 0x08; // bit 3: Reboot
     }
-    if state.request_restart_code {
+    if state.restart_packets_left > 0 {
         request |= 0x04; // bit 2: Restart code
     }
     pkt.push(request);
 
     // Byte 5: Alliance station
-    pkt.push(state.alliance.to_byte());
+    pkt.push(state.effective_alliance().to_byte());
+
+    // With joysticks disabled every device is sent at rest, keeping its
+    // layout so robot code sees no joystick disappear
+    let at_rest: Vec<JoystickState>;
+    let joysticks = if state.joysticks_enabled {
+        joysticks
+    } else {
+        at_rest = joysticks.iter().map(JoystickState::at_rest).collect();
+        &at_rest
+    };
 
     // Joystick tags (tag 0x0C)
     // Tag format: [size][id][data...] where size = len(id + data), NOT including size byte itself
     for js in joysticks.iter().take(6) {
-        let num_buttons = js.buttons.len();
+        // Pad up to the configured minimum so robot code sees a consistent count
+        let num_buttons = js.buttons.len().max(state.min_button_count as usize);
         let button_bytes = (num_buttons + 7) / 8;
         // data = axes_count(1) + axes + button_count(1) + button_bytes + pov_count(1) + povs*2
-        let data_size = 1 + js.axes.len() + 1 + button_bytes + 1 + js.povs.len() * 2;
+        let data_size = 1 + js.axes.len() + 1 + button_bytes + 1 + js.pov_count() * 2;
         // size field = id(1) + data
         let tag_size: u8 = (1 + data_size) as u8;
 
@@ -94,7 +401,7 @@ fn build_outbound_packet(
             let mut byte: u8 = 0;
             for bit in 0..8 {
                 let btn_idx = byte_idx * 8 + bit;
-                if btn_idx < num_buttons && js.buttons[btn_idx] {
+                if btn_idx < js.buttons.len() && js.buttons[btn_idx] {
                     byte |= 1 << (7 - bit);
                 }
             }
@@ -102,15 +409,15 @@ fn build_outbound_packet(
         }
 
         // POVs
-        pkt.push(js.povs.len() as u8);
+        pkt.push(js.pov_count() as u8);
         for &pov in &js.povs {
             let _ = (&mut pkt as &mut Vec<u8>).write_i16::<BigEndian>(pov);
         }
     }
 
-    // Date/time tag (tag 0x0F) - sent periodically
+    // Date/time tag (tag 0x0F) - sent when the robot asks for it
     // Tag format: [size][id][data...]
-    if seq % 50 == 0 {
+    if robot_requests.wants_date_time(seq) {
         if let Ok(dur) = SystemTime::now().duration_since(UNIX_EPOCH) {
             let secs = dur.as_secs();
             let micros = dur.subsec_micros();
@@ -148,8 +455,71 @@ fn build_outbound_packet(
     pkt
 }
 
+/// Data the robot asked the DS to send, from the request byte (byte 7) of
+/// its status packets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RobotFeatureRequests {
+    /// Bit 0: the robot wants the date/time tag
+    pub date_time: bool,
+    /// The robot has set a request bit since it connected. Until then the
+    /// date/time tag falls back to every 50th packet.
+    pub negotiated: bool,
+}
+
+impl RobotFeatureRequests {
+    /// Read the request byte of a status packet
+    pub fn update(&mut self, data: &[u8]) {
+        let Some(&request) = data.get(7) else {
+            return;
+        };
+        self.date_time = request & 0x01 != 0;
+        if request != 0 {
+            self.negotiated = true;
+        }
+    }
+
+    /// Whether packet `seq` should carry the date/time tag
+    pub fn wants_date_time(&self, seq: u16) -> bool {
+        if self.negotiated {
+            self.date_time
+        } else {
+            seq % 50 == 0
+        }
+    }
+}
+
+/// Big-endian u32 at `offset`, or None if the data is too short
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// CAN metrics tag (0x0E): utilization(4 f32) + bus_off(4 u32) + tx_full(4 u32)
+///   + rx_error(1 u8) + tx_error(1 u8) = 14 bytes
+///
+/// Older firmware sends a shorter tag, so each field is decoded only if
+/// present and missing ones keep their previous value. The tag carries bus
+/// totals only; there is no per-device fault information.
+fn parse_can_metrics(tag_data: &[u8], diag: &mut DiagnosticData) {
+    if let Some(bits) = be_u32(tag_data, 0) {
+        diag.can_utilization = f32::from_bits(bits);
+    }
+    if let Some(bus_off) = be_u32(tag_data, 4) {
+        diag.can_bus_off = bus_off;
+    }
+    if let Some(tx_full) = be_u32(tag_data, 8) {
+        diag.can_tx_full = tx_full;
+    }
+    if let Some(&rx_error) = tag_data.get(12) {
+        diag.can_rx_error = rx_error as u32;
+    }
+    if let Some(&tx_error) = tag_data.get(13) {
+        diag.can_tx_error = tx_error as u32;
+    }
+}
+
 /// Parses Robot→DS UDP packet (from port 1150)
-fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut DiagnosticData) {
+pub(super) fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut DiagnosticData) {
     if data.len() < 7 {
         return;
     }
@@ -159,8 +529,12 @@ fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut Di
 
     // Byte 3: Status byte
     let status = data[3];
+    let was_brownout = robot_state.brownout;
     robot_state.estopped = (status & 0x80) != 0;
     robot_state.brownout = (status & 0x10) != 0;
+    if robot_state.brownout && !was_brownout {
+        diag.record_brownout(unix_now());
+    }
     robot_state.fms_connected = (status & 0x08) != 0;
     robot_state.enabled = (status & 0x04) != 0;
     robot_state.mode = Mode::from_bits(status);
@@ -171,11 +545,14 @@ fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut Di
 
     // Bytes 5-6: Battery voltage (integer + fractional/256)
     robot_state.battery_voltage = data[5] as f32 + (data[6] as f32 / 256.0);
+    diag.record_voltage(unix_now(), robot_state.battery_voltage);
 
     robot_state.connected = true;
 
     // Parse tags starting at byte 8
     // Tag format: [size][id][data...] where size = len(id + data)
+    // Every handler below must bounds-check `tag_data` — the length varies by
+    // firmware and the packet may be malformed.
     let mut i = 8;
     while i < data.len() {
         let size = data[i] as usize;
@@ -207,7 +584,7 @@ fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut Di
                 // CPU usage: num_cpus(1) + 4 priority groups × num_cpus × f32
                 // Groups: critical, above_normal, normal, low
                 // Total per-core = sum of all 4 groups; values are percentages (0-100)
-                let num_cpus = tag_data[0] as usize;
+                let num_cpus = tag_data.first().copied().unwrap_or(0) as usize;
                 let expected_len = 1 + 4 * num_cpus * 4;
                 if num_cpus > 0 && tag_data.len() >= expected_len {
                     let mut per_core_totals = vec![0.0f32; num_cpus];
@@ -236,24 +613,7 @@ fn parse_inbound_packet(data: &[u8], robot_state: &mut RobotState, diag: &mut Di
                     ]);
                 }
             }
-            0x0E => {
-                // CAN metrics: utilization(4 f32) + bus_off(4 u32) + tx_full(4 u32)
-                //   + rx_error(1 u8) + tx_error(1 u8) = 14 bytes
-                if tag_data.len() >= 12 {
-                    diag.can_utilization =
-                        f32::from_bits(u32::from_be_bytes([
-                            tag_data[0], tag_data[1], tag_data[2], tag_data[3],
-                        ]));
-                    diag.can_bus_off =
-                        u32::from_be_bytes([tag_data[4], tag_data[5], tag_data[6], tag_data[7]]);
-                    diag.can_tx_full =
-                        u32::from_be_bytes([tag_data[8], tag_data[9], tag_data[10], tag_data[11]]);
-                }
-                if tag_data.len() >= 14 {
-                    diag.can_rx_error = tag_data[12] as u32;
-                    diag.can_tx_error = tag_data[13] as u32;
-                }
-            }
+            0x0E => parse_can_metrics(tag_data, diag),
             other => {
                 tracing::debug!("Unknown UDP tag 0x{:02X}, {} bytes", other, tag_data.len());
             }
@@ -270,11 +630,136 @@ pub struct DsState {
     pub enabled: bool,
     pub estop: bool,
     pub alliance: Alliance,
-    pub request_reboot: bool,
-    pub request_restart_code: bool,
+    /// Temporary alliance for testing; takes precedence over `alliance`
+    /// without replacing the saved setting
+    pub alliance_override: Option<Alliance>,
+    /// Packets still to carry the reboot request bit
+    pub reboot_packets_left: u8,
+    /// Packets still to carry the restart-code request bit
+    pub restart_packets_left: u8,
+    /// How many packets a reboot/restart request is repeated in, so a single
+    /// dropped packet doesn't lose it
+    pub request_repeat: u8,
     pub game_data: String,
+    /// Target localhost regardless of team number
+    pub simulation: bool,
+    /// Talk to a USB-tethered roboRIO at 172.22.11.2 whenever one is detected
+    pub prefer_usb: bool,
+    /// Minimum button count per joystick tag; shorter devices are padded with
+    /// released buttons (0 = send the device's own count)
+    pub min_button_count: u8,
+    /// Comm version byte (byte 2) of every outbound packet
+    pub comm_version: u8,
+    /// Send live joystick input; when false every joystick is sent at rest
+    pub joysticks_enabled: bool,
 }
 
+impl DsState {
+    /// The alliance station actually sent to the robot
+    pub fn effective_alliance(&self) -> Alliance {
+        self.alliance_override.unwrap_or(self.alliance)
+    }
+
+    pub fn alliance_info(&self) -> AllianceInfo {
+        AllianceInfo::new(self.effective_alliance(), self.alliance_override.is_some())
+    }
+
+    /// Apply a control command. This is the only place the enable/E-Stop
+    /// state changes in response to commands, and it keeps one invariant:
+    /// an E-Stopped DS is never enabled.
+    ///
+    /// - `Enable` is ignored while E-Stopped.
+    /// - `Disable`, `SetMode`, `RestartCode` disable the robot.
+    /// - `EStop` latches E-Stop and disables.
+    /// - `RebootRio` is the only way to clear the E-Stop latch (the roboRIO
+    ///   restarts with a clean state); the robot stays disabled.
+    /// - `RestartCode` keeps the E-Stop latch since the roboRIO keeps running.
+    ///
+    /// Returns the timeline entry for the transition, if any. Other commands
+    /// leave the state untouched.
+    pub fn apply_control(&mut self, cmd: &DsCommand) -> Option<TimelineEvent> {
+        let event = match cmd {
+            DsCommand::Enable if self.estop => None,
+            DsCommand::Enable => {
+                self.enabled = true;
+                Some(TimelineEvent::Enabled)
+            }
+            // Already disabled (e.g. repeated focus loss): nothing to record
+            DsCommand::Disable if !self.enabled => None,
+            DsCommand::Disable => {
+                self.enabled = false;
+                Some(TimelineEvent::Disabled)
+            }
+            // Already latched (e.g. a held E-Stop key repeating)
+            DsCommand::EStop if self.estop => None,
+            DsCommand::EStop => {
+                self.estop = true;
+                self.enabled = false;
+                Some(TimelineEvent::EStop)
+            }
+            DsCommand::SetMode(mode) => {
+                self.mode = *mode;
+                self.enabled = false;
+                Some(TimelineEvent::ModeChanged { mode: *mode })
+            }
+            DsCommand::RebootRio => {
+                self.reboot_packets_left = self.request_repeat;
+                self.estop = false;
+                self.enabled = false;
+                None
+            }
+            DsCommand::RestartCode => {
+                self.restart_packets_left = self.request_repeat;
+                self.enabled = false;
+                None
+            }
+            _ => None,
+        };
+        debug_assert!(!(self.estop && self.enabled));
+        event
+    }
+
+    /// Count one sent packet against the pending requests. Returns the
+    /// requests whose repeat window just ended.
+    pub fn request_packet_sent(&mut self) -> Vec<RobotRequest> {
+        let mut finished = Vec::new();
+        for (left, request) in [
+            (&mut self.reboot_packets_left, RobotRequest::RebootRio),
+            (&mut self.restart_packets_left, RobotRequest::RestartCode),
+        ] {
+            if *left > 0 {
+                *left -= 1;
+                if *left == 0 {
+                    finished.push(request);
+                }
+            }
+        }
+        finished
+    }
+
+    /// The robot reported its code stopped: any pending request took effect,
+    /// so stop repeating it. Returns the requests that were pending.
+    pub fn acknowledge_requests(&mut self) -> Vec<RobotRequest> {
+        let mut acknowledged = Vec::new();
+        if std::mem::take(&mut self.reboot_packets_left) > 0 {
+            acknowledged.push(RobotRequest::RebootRio);
+        }
+        if std::mem::take(&mut self.restart_packets_left) > 0 {
+            acknowledged.push(RobotRequest::RestartCode);
+        }
+        acknowledged
+    }
+}
+
+/// Comm version sent by current WPILib driver stations
+pub const DEFAULT_COMM_VERSION: u8 = 0x01;
+
+/// Default number of packets a reboot/restart request is repeated in
+pub const DEFAULT_REQUEST_REPEAT: u8 = 5;
+
+/// WPILib supports at most 32 buttons per joystick
+pub const MAX_BUTTON_COUNT: u8 = 32;
+
 impl Default for DsState {
     fn default() -> Self {
         Self {
@@ -282,9 +767,16 @@ impl Default for DsState {
             enabled: false,
             estop: false,
             alliance: Alliance::Red1,
-            request_reboot: false,
-            request_restart_code: false,
+            alliance_override: None,
+            reboot_packets_left: 0,
+            restart_packets_left: 0,
+            request_repeat: DEFAULT_REQUEST_REPEAT,
             game_data: String::new(),
+            simulation: false,
+            prefer_usb: true,
+            min_button_count: 0,
+            comm_version: DEFAULT_COMM_VERSION,
+            joysticks_enabled: true,
         }
     }
 }
@@ -298,10 +790,59 @@ pub enum DsCommand {
     Disable,
     EStop,
     SetAlliance(Alliance),
+    /// Temporarily override the alliance (None reverts to the saved alliance)
+    SetAllianceOverride(Option<Alliance>),
     RebootRio,
     RestartCode,
+    /// Explicit target address. Wins over the team/simulation IP until
+    /// the team number changes or simulation mode is toggled.
     SetTargetIp(String),
     SetGameData(String),
+    /// Force the target to 127.0.0.1 regardless of team number. Turning it
+    /// off restores the team-derived IP (and re-runs discovery).
+    SetSimulation(bool),
+    /// Pad every joystick's button bitfield to at least this many buttons
+    SetMinButtonCount(u8),
+    /// Prefer the USB roboRIO address over the team/explicit target
+    SetPreferUsb(bool),
+    /// Connect to a second robot and report its state by id
+    AddMonitoredRobot { id: String, ip: std::net::IpAddr },
+    RemoveMonitoredRobot(String),
+    /// Send unaddressed control commands and the joysticks to this robot
+    /// (`PRIMARY_ROBOT_ID` or a monitored robot's id). Disables every robot.
+    /// Unaddressed `Disable` and `EStop` still reach every robot.
+    SetActiveRobot(String),
+    /// A control command (enable, mode, E-Stop, reboot...) for one robot
+    /// rather than the active one
+    ForRobot { robot_id: String, command: Box<DsCommand> },
+    /// Emit throttled hex dumps of UDP traffic for protocol debugging
+    SetPacketCapture(bool),
+    /// Write recent telemetry to this directory on each brownout (None = off)
+    SetBrownoutSnapshots(Option<std::path::PathBuf>),
+    /// Frontend liveness ping; any command resets the enable watchdog
+    Heartbeat,
+    SetWatchdogTimeout(Duration),
+    SetBatteryThresholds(BatteryThresholds),
+    /// Packets each reboot/restart request is repeated in
+    SetRequestRepeat(u8),
+    /// Write control transitions (enable, mode, E-Stop, reboot...) to the
+    /// log file. On by default.
+    SetControlLogging(bool),
+    /// Override the comm version byte for testing other robot firmware
+    SetCommVersion(u8),
+    /// Time without a reply before the robot is marked disconnected (and
+    /// E-Stop cleared)
+    SetDisconnectTimeout(Duration),
+    /// Team number → IP mapping; takes effect on the next team change
+    SetIpResolver(TeamIpResolver),
+    /// Fake a connected robot for UI development; no packets are sent or
+    /// accepted while it runs
+    SetSimulatedRobot(bool),
+    /// Zero the session counters in diagnostics and power data (DS side only)
+    ResetDiagnostics,
+    /// Safety toggle for pit work: false sends every joystick at rest while
+    /// the gamepad UI keeps showing live input
+    SetJoysticksEnabled(bool),
 }
 
 /// Events emitted from the protocol loop to the frontend
@@ -316,50 +857,267 @@ pub enum DsEvent {
     ConnectionStatus(ConnectionStatus),
     PowerData(PowerData),
     VersionInfo(VersionInfo),
+    /// Robot code began reporting as running (Unix timestamp, seconds)
+    RobotCodeStarted { timestamp: f64 },
+    /// Robot code stopped reporting as running (crash or restart)
+    RobotCodeStopped { timestamp: f64 },
+    /// A new log session started; the frontend should clear its console
+    ConsoleCleared,
+    Alert(Alert),
+    RadioEvent(RadioEvent),
+    /// The saved target in use changed (None = manual team/IP)
+    ActiveTarget { label: Option<String> },
+    /// A per-robot event (state, diagnostics, battery) from a secondary
+    /// robot added with `AddMonitoredRobot`. Untagged events are the
+    /// primary robot's.
+    ForRobot { robot_id: String, event: Box<DsEvent> },
+    PacketCapture(PacketCapture),
+    /// The alliance station sent to the robot changed
+    AllianceChanged(AllianceInfo),
+    /// Connection health summary for the status bar (2Hz)
+    LinkQuality(LinkQuality),
+    /// A dashboard launched by the DS exited right away
+    DashboardLaunchFailed { name: String },
+    /// The robot's status packet echoed a latched E-Stop
+    EStopConfirmed,
+    /// A latched E-Stop has not been echoed by the robot in time
+    EStopUnconfirmed,
+    /// Smoothed battery voltage dropped below a low-battery threshold
+    BatteryWarning { level: BatteryLevel, voltage: f32 },
+    /// A reboot/restart request finished: acknowledged by the robot's code
+    /// stopping, or sent for its full repeat count without confirmation
+    RobotRequest { request: RobotRequest, acknowledged: bool },
+    /// Joystick input is being sent (true) or suppressed
+    JoysticksEnabled { enabled: bool },
+    /// The system woke from sleep; gamepads were rescanned and the console
+    /// reconnected
+    Resumed { slept_ms: u64 },
+    /// Unaddressed control commands now go to this robot
+    ActiveRobotChanged { robot_id: String },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GamepadUpdate {
     pub gamepads: Vec<GamepadInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GamepadInfo {
     pub id: usize,
     pub name: String,
     pub slot: usize,
     pub axes: Vec<f32>,
     pub buttons: Vec<bool>,
+    /// Every POV sent for this device, in WPILib index order (POV 0 first),
+    /// -1 when centered. The count matches the outbound packet, including
+    /// any layout override on the slot.
     pub povs: Vec<i16>,
     pub locked: bool,
+    /// None when the device doesn't report power state
+    pub battery: Option<GamepadBattery>,
+    /// Active button remap (physical index → output index)
+    pub button_remap: ButtonRemap,
+}
+
+/// Controller power state as reported by gilrs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", content = "percent")]
+pub enum GamepadBattery {
+    Wired,
+    Charging(u8),
+    Discharging(u8),
+    Charged,
+}
+
+/// Fixed address of a roboRIO tethered over USB
+pub const USB_ROBOT_IP: &str = "172.22.11.2";
+
+/// Whether to talk to the roboRIO over USB instead of the team/explicit
+/// target. Simulation always wins, since the robot is on localhost.
+fn usb_preferred(prefer_usb: bool, simulation: bool, usb_detected: bool) -> bool {
+    prefer_usb && !simulation && usb_detected
 }
 
-/// Resolves the target IP for a given team number
-pub fn team_to_ip(team: u32) -> String {
-    if team == 0 {
-        // Simulation mode — connect to localhost
-        return "127.0.0.1".to_string();
+/// The address packets and the TCP console actually use
+fn effective_target(target_ip: &str, usb_active: bool) -> &str {
+    if usb_active {
+        USB_ROBOT_IP
+    } else {
+        target_ip
     }
-    let te = team / 100;
-    let am = team % 100;
-    format!("10.{te}.{am}.2")
 }
 
+/// Standard FRC robot address, 10.TE.AM.2
+pub const DEFAULT_IP_TEMPLATE: &str = "10.{te}.{am}.2";
+
+/// Highest team number the 10.TE.AM scheme can address (TE must fit in an
+/// octet). FRC has no alternate scheme for larger numbers.
+pub const MAX_TEAM_NUMBER: u32 = 25599;
+
+/// Accepts 0 (localhost) and 1..=`MAX_TEAM_NUMBER`
+pub fn validate_team_number(team: u32) -> Result<(), String> {
+    if team > MAX_TEAM_NUMBER {
+        return Err(format!("Team number must be 0-{MAX_TEAM_NUMBER}, got {team}"));
+    }
+    Ok(())
+}
+
+/// Maps team numbers to robot IPs from an address template with `{te}`
+/// (team / 100), `{am}` (team % 100) and `{team}` placeholders. Off-season
+/// events with nonstandard addressing can override the default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamIpResolver {
+    template: String,
+}
+
+impl Default for TeamIpResolver {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_IP_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl TeamIpResolver {
+    /// Checks that the template produces a valid IPv4 address for both the
+    /// smallest and the largest team number
+    pub fn new(template: &str) -> Result<Self, String> {
+        let resolver = Self {
+            template: template.trim().to_string(),
+        };
+        for team in [1, MAX_TEAM_NUMBER] {
+            let sample = resolver.render(team);
+            sample.parse::<std::net::Ipv4Addr>().map_err(|_| {
+                format!("IP template \"{template}\" gives \"{sample}\" for team {team}, not an IPv4 address")
+            })?;
+        }
+        Ok(resolver)
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    fn render(&self, team: u32) -> String {
+        self.template
+            .replace("{team}", &team.to_string())
+            .replace("{te}", &(team / 100).to_string())
+            .replace("{am}", &(team % 100).to_string())
+    }
+
+    /// Resolves the target IP for a given team number. Assumes team <=
+    /// `MAX_TEAM_NUMBER`; larger numbers give an invalid address.
+    pub fn team_to_ip(&self, team: u32) -> String {
+        if team == 0 {
+            // Simulation mode — connect to localhost
+            return "127.0.0.1".to_string();
+        }
+        self.render(team)
+    }
+}
+
+/// Log text for a control command, or None if it changed nothing.
+/// Reboot/restart requests are always logged; other commands only when they
+/// produced a timeline transition.
+fn control_action(cmd: &DsCommand, event: Option<&TimelineEvent>) -> Option<String> {
+    match (cmd, event) {
+        (DsCommand::RebootRio, _) => Some("Reboot roboRIO requested".to_string()),
+        (DsCommand::RestartCode, _) => Some("Restart robot code requested".to_string()),
+        (_, Some(TimelineEvent::Enabled)) => Some("Enabled".to_string()),
+        (_, Some(TimelineEvent::Disabled)) => Some("Disabled".to_string()),
+        (_, Some(TimelineEvent::EStop)) => Some("E-Stop".to_string()),
+        (_, Some(TimelineEvent::ModeChanged { mode })) => Some(format!("Mode set to {mode:?}")),
+        _ => None,
+    }
+}
+
+/// `{action} -> {mode}, enabled|disabled[, E-Stopped]`
+fn control_log_text(action: &str, state: &DsState) -> String {
+    format!(
+        "{action} -> {:?}, {}{}",
+        state.mode,
+        if state.enabled { "enabled" } else { "disabled" },
+        if state.estop { ", E-Stopped" } else { "" },
+    )
+}
+
+/// Queue a control transition for the log file. Never blocks the loop; a
+/// full writer channel drops the line.
+fn log_control(file_log_tx: &mpsc::Sender<ConsoleMessage>, action: &str, state: &DsState) {
+    let msg = crate::log_writer::ds_message(unix_now(), &control_log_text(action, state));
+    let _ = file_log_tx.try_send(msg);
+}
+
+/// Tell the UI (and the snapshot) about the alliance station if it changed
+async fn publish_alliance(
+    before: &AllianceInfo,
+    state: &DsState,
+    snapshot: &SharedRobotSnapshot,
+    event_tx: &mpsc::Sender<DsEvent>,
+) {
+    let info = state.alliance_info();
+    if info != *before {
+        snapshot.write().alliance = info.clone();
+        let _ = event_tx.send(DsEvent::AllianceChanged(info)).await;
+    }
+}
+
+/// Copy the loop's current robot state, diagnostics and alliance into the
+/// shared snapshot. The link summary is kept; it updates on its own timer.
+fn publish_snapshot(
+    snapshot: &SharedRobotSnapshot,
+    robot_state: &RobotState,
+    diag: &DiagnosticData,
+    state: &DsState,
+) {
+    let mut snap = snapshot.write();
+    snap.robot_state = robot_state.clone();
+    snap.diagnostics = diag.clone();
+    snap.alliance = state.alliance_info();
+}
+
+/// Latest robot state published by the protocol loop. Written after each
+/// parsed packet and on the 10Hz event tick; readers only hold the lock
+/// long enough to clone.
+pub type SharedRobotSnapshot = Arc<RwLock<RobotSnapshot>>;
+
 /// The main protocol loop, run as a Tokio task
+#[allow(clippy::too_many_arguments)]
 pub async fn protocol_loop(
     mut cmd_rx: mpsc::Receiver<DsCommand>,
     event_tx: mpsc::Sender<DsEvent>,
     joystick_state: Arc<RwLock<Vec<JoystickState>>>,
     target_ip_tx: watch::Sender<String>,
+    timeline: Arc<RwLock<SessionTimeline>>,
+    socket_status: Arc<RwLock<SocketStatus>>,
+    latency_probe: SharedLatencyProbe,
+    snapshot: SharedRobotSnapshot,
+    file_log_tx: mpsc::Sender<ConsoleMessage>,
+    last_outbound: SharedOutboundPacket,
 ) {
     let mut team_number: u32 = 0;
-    let mut target_ip = team_to_ip(0);
+    let mut ip_resolver = TeamIpResolver::default();
+    let mut target_ip = ip_resolver.team_to_ip(0);
     let mut ds_state = DsState::default();
+    let mut control_logging = true;
+    let mut estop_confirmation = EStopConfirmation::default();
+    let mut robot_requests = RobotFeatureRequests::default();
+    let mut disconnect_timeout = DEFAULT_DISCONNECT_TIMEOUT;
+    {
+        let mut status = socket_status.write();
+        status.comm_version = ds_state.comm_version;
+        status.disconnect_timeout_ms = disconnect_timeout.as_millis() as u64;
+    }
     let mut pending_discovery: Option<tokio::task::JoinHandle<()>> = None;
     let (discovery_tx, mut discovery_rx) = mpsc::channel::<String>(4);
     let mut robot_state = RobotState::default();
     let mut diag = DiagnosticData::default();
     let mut sequence: u16 = 0;
+    // Replaces UDP comms entirely while set
+    let mut simulated_robot: Option<SimulatedRobot> = None;
+    // Hostname targets are resolved off the send tick
+    let mut target_resolver = TargetResolver::default();
+    let (resolve_tx, mut resolve_rx) = mpsc::channel::<Resolution>(4);
     let mut last_recv = Instant::now();
     let mut send_socket: Option<UdpSocket> = None;
     let mut recv_socket: Option<UdpSocket> = None;
@@ -368,6 +1126,8 @@ pub async fn protocol_loop(
     match UdpSocket::bind("0.0.0.0:1150").await {
         Ok(sock) => {
             tracing::info!("Bound UDP receive socket on port 1150");
+            let local = sock.local_addr().ok().map(|a| a.to_string());
+            socket_status.write().set_udp(true, local);
             recv_socket = Some(sock);
         }
         Err(e) => {
@@ -378,6 +1138,8 @@ pub async fn protocol_loop(
     // Bind send socket
     match UdpSocket::bind("0.0.0.0:0").await {
         Ok(sock) => {
+            let local = sock.local_addr().ok().map(|a| a.to_string());
+            socket_status.write().set_udp(false, local);
             send_socket = Some(sock);
         }
         Err(e) => {
@@ -385,9 +1147,12 @@ pub async fn protocol_loop(
         }
     }
 
-    let mut recv_buf = [0u8; 1024];
+    // Large enough for any UDP datagram, so a full buffer means truncation
+    let mut recv_buf = vec![0u8; MAX_UDP_PACKET];
     let mut tick_interval = tokio::time::interval(std::time::Duration::from_millis(20));
     let mut event_interval = tokio::time::interval(std::time::Duration::from_millis(100));
+    let mut link_interval = tokio::time::interval(std::time::Duration::from_millis(500));
+    let mut link_stats = LinkStats::default();
 
     // Radio check runs in a spawned task to avoid blocking the protocol loop.
     // On Windows, TCP connect to a non-listening port waits the full timeout (~200ms),
@@ -396,114 +1161,399 @@ pub async fn protocol_loop(
     let (radio_result_tx, mut radio_result_rx) = mpsc::channel::<bool>(4);
     let mut last_radio_check = Instant::now() - std::time::Duration::from_secs(10); // trigger immediately
 
-    // USB roboRIO detection — cached and refreshed every 2s
+    // roboRIO TCP probe (ports 22/1740) — detects a powered-on RIO before UDP comms
+    let mut robot_probe_ok = false;
+
+    let mut stuck_enabled = StuckEnabledDetector::default();
+    let mut practice_timer = PracticeTimer::default();
+    let mut battery = BatteryMonitor::default();
+
+    // Enable watchdog — disables if the frontend stops sending commands
+    let mut last_command = Instant::now();
+    let mut watchdog_timeout = DEFAULT_WATCHDOG_TIMEOUT;
+    let (probe_result_tx, mut probe_result_rx) = mpsc::channel::<bool>(4);
+
+    // NetworkTables reachability, checked with the radio
+    let mut nt_reachable = false;
+    let (nt_result_tx, mut nt_result_rx) = mpsc::channel::<bool>(4);
+
+    // USB roboRIO detection — cached and refreshed every 2s. While
+    // `usb_active`, the effective target is 172.22.11.2 and `target_ip` is
+    // kept as the fallback for when the cable is unplugged.
     let mut usb_detected = false;
+    let mut usb_active = false;
+
+    // Raw packet capture (off by default) — throttled per direction
+    let mut packet_capture = false;
+    let mut capture_out = CaptureThrottle::default();
+    let mut capture_in = CaptureThrottle::default();
+
+    // Telemetry window for automatic brownout snapshots (off by default)
+    let mut telemetry = TelemetryHistory::default();
+    let mut snapshot_dir: Option<std::path::PathBuf> = None;
+
+    // Secondary robots; control commands reach whichever robot is active
+    let mut monitor = RobotMonitor::default();
     let mut last_iface_check = Instant::now() - std::time::Duration::from_secs(10);
 
-    // Periodic re-discovery when not connected
+    // Periodic re-discovery when not connected. Results of a re-check only
+    // move the target once confirmed, and never while the link is up.
     let mut last_discovery_attempt = Instant::now();
+    let mut discovery_is_recheck = false;
+    let mut address_filter = AddressChangeFilter::default();
 
     loop {
         tokio::select! {
             // Process commands from frontend
             Some(cmd) = cmd_rx.recv() => {
+                last_command = Instant::now();
+                let cmd = match cmd {
+                    DsCommand::ForRobot { robot_id, command } if robot_id != PRIMARY_ROBOT_ID => {
+                        if let Err(e) = monitor.apply_control(&robot_id, &command) {
+                            tracing::warn!("{e}");
+                        }
+                        continue;
+                    }
+                    DsCommand::ForRobot { command, .. } => *command,
+                    // E-Stop and disable (including the safety disables)
+                    // stop every robot, whichever is active
+                    DsCommand::EStop => {
+                        monitor.estop_all();
+                        DsCommand::EStop
+                    }
+                    DsCommand::Disable => {
+                        monitor.disable_all();
+                        DsCommand::Disable
+                    }
+                    cmd @ (DsCommand::SetMode(_)
+                    | DsCommand::Enable
+                    | DsCommand::RebootRio
+                    | DsCommand::RestartCode) if monitor.active().is_some() => {
+                        let id = monitor.active().unwrap_or_default().to_string();
+                        let _ = monitor.apply_control(&id, &cmd);
+                        continue;
+                    }
+                    cmd => cmd,
+                };
                 match cmd {
                     DsCommand::SetTeamNumber(team) => {
                         team_number = team;
-                        target_ip = team_to_ip(team);
-                        let _ = target_ip_tx.send(target_ip.clone());
+                        if ds_state.simulation {
+                            // Keep targeting localhost; the team IP applies when sim is turned off
+                            tracing::info!("Team set to {team} (simulation mode, staying on localhost)");
+                            continue;
+                        }
+                        target_ip = ip_resolver.team_to_ip(team);
+                        let _ = target_ip_tx.send(effective_target(&target_ip, usb_active).to_string());
                         tracing::info!("Team set to {team}, target IP: {target_ip}");
                         // Reset connection state
                         robot_state = RobotState::default();
+                        robot_requests = RobotFeatureRequests::default();
                         ds_state.enabled = false;
                         // Spawn mDNS discovery (result will override static IP)
                         if let Some(h) = pending_discovery.take() {
                             h.abort();
                         }
+                        discovery_is_recheck = false;
                         let dtx = discovery_tx.clone();
                         pending_discovery = Some(tokio::spawn(
-                            crate::discovery::discover_roborio(team, dtx),
+                            crate::discovery::discover_roborio(team, ip_resolver.clone(), dtx),
                         ));
                     }
-                    DsCommand::SetMode(mode) => {
-                        ds_state.mode = mode;
-                        // Disable when switching modes (safety)
-                        ds_state.enabled = false;
-                    }
-                    DsCommand::Enable => {
-                        if !ds_state.estop {
-                            ds_state.enabled = true;
+                    DsCommand::SetSimulation(on) => {
+                        if on == ds_state.simulation {
+                            continue;
                         }
-                    }
-                    DsCommand::Disable => {
+                        ds_state.simulation = on;
+                        if let Some(h) = pending_discovery.take() {
+                            h.abort();
+                        }
+                        target_ip = if on {
+                            "127.0.0.1".to_string()
+                        } else {
+                            ip_resolver.team_to_ip(team_number)
+                        };
+                        // Simulation never uses USB; re-evaluate on the next tick
+                        usb_active = false;
+                        last_iface_check = Instant::now() - std::time::Duration::from_secs(10);
+                        let _ = target_ip_tx.send(target_ip.clone());
+                        tracing::info!("Simulation mode {}, target IP: {target_ip}", if on { "on" } else { "off" });
+                        robot_state = RobotState::default();
+                        robot_requests = RobotFeatureRequests::default();
                         ds_state.enabled = false;
+                        if !on {
+                            discovery_is_recheck = false;
+                            let dtx = discovery_tx.clone();
+                            pending_discovery = Some(tokio::spawn(
+                                crate::discovery::discover_roborio(team_number, ip_resolver.clone(), dtx),
+                            ));
+                        }
                     }
-                    DsCommand::EStop => {
-                        ds_state.estop = true;
-                        ds_state.enabled = false;
+                    cmd @ (DsCommand::SetMode(_)
+                    | DsCommand::Enable
+                    | DsCommand::Disable
+                    | DsCommand::EStop
+                    | DsCommand::RebootRio
+                    | DsCommand::RestartCode) => {
+                        let was_estopped = ds_state.estop;
+                        let event = ds_state.apply_control(&cmd);
+                        if control_logging {
+                            if let Some(action) = control_action(&cmd, event.as_ref()) {
+                                log_control(&file_log_tx, &action, &ds_state);
+                            }
+                        }
+                        if let Some(event) = event {
+                            timeline.write().record(unix_now(), event);
+                        }
+                        if ds_state.estop && !was_estopped {
+                            estop_confirmation.latched(Instant::now());
+                            let _ = event_tx.send(alert_event(AlertKind::EStop, "E-Stop engaged")).await;
+                        } else if was_estopped && !ds_state.estop {
+                            estop_confirmation.cancel();
+                        }
                     }
                     DsCommand::SetAlliance(alliance) => {
+                        let before = ds_state.alliance_info();
                         ds_state.alliance = alliance;
+                        publish_alliance(&before, &ds_state, &snapshot, &event_tx).await;
                     }
-                    DsCommand::RebootRio => {
-                        ds_state.request_reboot = true;
-                        ds_state.estop = false;
-                        ds_state.enabled = false;
-                    }
-                    DsCommand::RestartCode => {
-                        ds_state.request_restart_code = true;
+                    DsCommand::SetAllianceOverride(alliance) => {
+                        let before = ds_state.alliance_info();
+                        ds_state.alliance_override = alliance;
+                        publish_alliance(&before, &ds_state, &snapshot, &event_tx).await;
                     }
                     DsCommand::SetTargetIp(ip) => {
-                        target_ip = ip.clone();
-                        let _ = target_ip_tx.send(ip);
+                        target_ip = ip;
+                        let _ = target_ip_tx.send(effective_target(&target_ip, usb_active).to_string());
                     }
                     DsCommand::SetGameData(data) => {
                         ds_state.game_data = data;
                     }
+                    DsCommand::SetMinButtonCount(count) => {
+                        ds_state.min_button_count = count.min(MAX_BUTTON_COUNT);
+                    }
+                    DsCommand::SetPreferUsb(on) => {
+                        ds_state.prefer_usb = on;
+                        // Apply on the next tick instead of waiting for the 2s refresh
+                        last_iface_check = Instant::now() - std::time::Duration::from_secs(10);
+                    }
+                    DsCommand::AddMonitoredRobot { id, ip } => {
+                        if ip.to_string() == effective_target(&target_ip, usb_active) {
+                            tracing::warn!("Not monitoring {ip}: it is the primary robot");
+                        } else if let Err(e) = monitor.add(id, ip).await {
+                            tracing::error!("Failed to bind monitor socket: {e}");
+                        }
+                    }
+                    DsCommand::RemoveMonitoredRobot(id) => {
+                        let was_active = monitor.active() == Some(id.as_str());
+                        if monitor.remove(&id) && was_active {
+                            let robot_id = PRIMARY_ROBOT_ID.to_string();
+                            let _ = event_tx.send(DsEvent::ActiveRobotChanged { robot_id }).await;
+                        }
+                    }
+                    DsCommand::SetActiveRobot(id) => {
+                        let before = monitor.active().map(str::to_string);
+                        if let Err(e) = monitor.set_active(&id) {
+                            tracing::warn!("{e}");
+                            continue;
+                        }
+                        if before.as_deref() != monitor.active() {
+                            if let Some(event) = ds_state.apply_control(&DsCommand::Disable) {
+                                timeline.write().record(unix_now(), event);
+                            }
+                            tracing::info!("Active robot: {id}");
+                            let _ = event_tx.send(DsEvent::ActiveRobotChanged { robot_id: id }).await;
+                        }
+                    }
+                    DsCommand::ForRobot { .. } => {}
+                    DsCommand::SetPacketCapture(on) => {
+                        packet_capture = on;
+                    }
+                    DsCommand::SetBrownoutSnapshots(dir) => {
+                        snapshot_dir = dir;
+                    }
+                    DsCommand::Heartbeat => {}
+                    DsCommand::SetWatchdogTimeout(timeout) => {
+                        watchdog_timeout = timeout;
+                    }
+                    DsCommand::SetBatteryThresholds(thresholds) => {
+                        battery.set_thresholds(thresholds);
+                    }
+                    DsCommand::SetRequestRepeat(count) => {
+                        ds_state.request_repeat = count.max(1);
+                    }
+                    DsCommand::SetControlLogging(on) => {
+                        control_logging = on;
+                    }
+                    DsCommand::SetCommVersion(version) => {
+                        tracing::info!("Comm version set to {version:#04x}");
+                        ds_state.comm_version = version;
+                        socket_status.write().comm_version = version;
+                    }
+                    DsCommand::SetDisconnectTimeout(timeout) => {
+                        tracing::info!("Disconnect timeout set to {timeout:?}");
+                        disconnect_timeout = timeout;
+                        socket_status.write().disconnect_timeout_ms = timeout.as_millis() as u64;
+                    }
+                    DsCommand::SetIpResolver(resolver) => {
+                        tracing::info!("Team IP template set to {}", resolver.template());
+                        ip_resolver = resolver;
+                    }
+                    DsCommand::SetSimulatedRobot(on) => {
+                        if on == simulated_robot.is_some() {
+                            continue;
+                        }
+                        tracing::info!("Simulated robot {}", if on { "on" } else { "off" });
+                        simulated_robot = on.then(|| SimulatedRobot::new(unix_now()));
+                        robot_state = RobotState::default();
+                        diag = DiagnosticData::default();
+                        ds_state.enabled = false;
+                    }
+                    DsCommand::SetJoysticksEnabled(on) => {
+                        if on != ds_state.joysticks_enabled {
+                            tracing::info!("Joystick input {}", if on { "enabled" } else { "suppressed" });
+                            ds_state.joysticks_enabled = on;
+                        }
+                        let _ = event_tx.send(DsEvent::JoysticksEnabled { enabled: on }).await;
+                    }
+                    DsCommand::ResetDiagnostics => {
+                        tracing::info!("Diagnostic counters reset");
+                        diag.reset_counters();
+                        let _ = event_tx.send(DsEvent::Diagnostics(diag.clone())).await;
+                        let _ = event_tx.send(DsEvent::PowerData(PowerData::default())).await;
+                    }
                 }
             }
 
             // 50Hz send tick
             _ = tick_interval.tick() => {
+                if estop_confirmation.check_timeout(Instant::now()).is_some() {
+                    tracing::warn!("Robot has not confirmed E-Stop after {ESTOP_CONFIRM_TIMEOUT:?}");
+                    let _ = event_tx.send(DsEvent::EStopUnconfirmed).await;
+                }
+
+                // Disable (E-Stop stays latched) if the frontend went quiet
+                let any_enabled = ds_state.enabled || monitor.any_enabled();
+                if watchdog_expired(any_enabled, last_command.elapsed(), watchdog_timeout) {
+                    ds_state.enabled = false;
+                    monitor.disable_all();
+                    timeline.write().record(unix_now(), TimelineEvent::Disabled);
+                    if control_logging {
+                        log_control(&file_log_tx, "Disabled by watchdog", &ds_state);
+                    }
+                    tracing::warn!("No frontend heartbeat for {watchdog_timeout:?}, disabling robot");
+                    let _ = event_tx.send(alert_event(
+                        AlertKind::Watchdog,
+                        "Disabled: driver station UI stopped responding",
+                    )).await;
+                }
+
+                // Nothing goes on the wire while simulating, so a real robot
+                // on the network can never be driven by it
+                if let Some(sim) = simulated_robot.as_mut() {
+                    for event in sim.step(&ds_state, &mut robot_state, &mut diag, unix_now()) {
+                        let _ = event_tx.send(event).await;
+                    }
+                    if estop_confirmation.on_status(robot_state.estopped).is_some() {
+                        let _ = event_tx.send(DsEvent::EStopConfirmed).await;
+                    }
+                    last_recv = Instant::now();
+                    continue;
+                }
+
                 if let Some(ref sock) = send_socket {
                     // Periodically refresh USB interface detection
                     if last_iface_check.elapsed() > std::time::Duration::from_secs(2) {
                         usb_detected = crate::network::check_interfaces().usb;
                         last_iface_check = Instant::now();
-                    }
 
-                    let joysticks = joystick_state.read().clone();
-                    let pkt = build_outbound_packet(sequence, &ds_state, &joysticks);
-                    let dest: SocketAddr = format!("{target_ip}:1110")
-                        .parse()
-                        .unwrap_or_else(|_| "127.0.0.1:1110".parse().unwrap());
+                        let use_usb = usb_preferred(ds_state.prefer_usb, ds_state.simulation, usb_detected);
+                        if use_usb != usb_active {
+                            usb_active = use_usb;
+                            if usb_active {
+                                tracing::info!("USB roboRIO detected, targeting {USB_ROBOT_IP}");
+                            } else {
+                                tracing::info!("Leaving USB target, falling back to {target_ip}");
+                            }
+                            let _ = target_ip_tx.send(effective_target(&target_ip, usb_active).to_string());
+                        }
+                    }
 
-                    if let Err(e) = sock.send_to(&pkt, dest).await {
-                        tracing::trace!("Send error: {e}");
+                    let (joysticks, probe) = {
+                        let js = joystick_state.read();
+                        (js.clone(), latency_probe.lock().take_injection())
+                    };
+                    // Joysticks drive only the active robot; the primary keeps
+                    // its layout at rest while another robot is active
+                    let at_rest: Vec<JoystickState>;
+                    let primary_joysticks = if monitor.active().is_none() {
+                        &joysticks
+                    } else {
+                        at_rest = joysticks.iter().map(JoystickState::at_rest).collect();
+                        &at_rest
+                    };
+                    let pkt = build_outbound_packet(sequence, &ds_state, primary_joysticks, &robot_requests);
+                    last_outbound.write().clone_from(&pkt);
+                    if let Some((injected_at, reply)) = probe {
+                        let _ = reply.send(injected_at.elapsed());
+                    }
+                    let target = effective_target(&target_ip, usb_active);
+                    // None until a hostname target first resolves
+                    if let Some(dest) = target_resolver.dest(target, 1110, Instant::now(), &resolve_tx) {
+                        if let Err(e) = sock.send_to(&pkt, dest).await {
+                            tracing::trace!("Send error: {e}");
+                        } else {
+                            link_stats.on_sent(sequence, Instant::now());
+                        }
+                    }
+                    if packet_capture && capture_out.ready(Instant::now()) {
+                        let _ = event_tx.try_send(packet_capture_event(PacketDirection::Outbound, &pkt));
                     }
 
                     // Also send to USB roboRIO IP if a USB interface is detected
-                    if usb_detected && target_ip != "172.22.11.2" {
-                        let usb_dest: SocketAddr = "172.22.11.2:1110".parse().unwrap();
+                    if usb_detected && !usb_active && target_ip != USB_ROBOT_IP {
+                        let usb_dest: SocketAddr = format!("{USB_ROBOT_IP}:1110").parse().unwrap();
                         let _ = sock.send_to(&pkt, usb_dest).await;
                     }
 
                     sequence = sequence.wrapping_add(1);
+                    monitor.send_all(&joysticks).await;
 
-                    // Clear one-shot requests after sending
-                    ds_state.request_reboot = false;
-                    ds_state.request_restart_code = false;
+                    // Repeated requests expire after their packet count
+                    for request in ds_state.request_packet_sent() {
+                        let _ = event_tx.send(DsEvent::RobotRequest { request, acknowledged: false }).await;
+                    }
 
-                    // If no response for 3 seconds, mark disconnected
-                    if last_recv.elapsed() > std::time::Duration::from_secs(3) {
+                    // No response within the disconnect timeout: mark disconnected
+                    if comms_lost(last_recv.elapsed(), disconnect_timeout) {
                         if robot_state.connected {
                             // Robot just disconnected — clear E-Stop so it can
                             // be re-enabled after a reboot/restart
                             ds_state.estop = false;
                             ds_state.enabled = false;
-                            tracing::info!("Robot disconnected, clearing E-Stop");
+                            // Minimum voltage, trend and telemetry are tracked per connection
+                            diag.reset_voltage();
+                            telemetry.clear();
+                            battery.reset();
+                            let reason = DisconnectReason::classify(radio_reachable, robot_probe_ok);
+                            timeline.write().record(
+                                unix_now(),
+                                TimelineEvent::CommsLost {
+                                    target_ip: effective_target(&target_ip, usb_active).to_string(),
+                                    reason,
+                                },
+                            );
+                            tracing::info!("Robot disconnected ({reason:?}), clearing E-Stop");
+                            // A hostname may point somewhere new after a reboot
+                            target_resolver.invalidate();
+                            let _ = event_tx.send(alert_event(
+                                AlertKind::CommsLost,
+                                &format!("Lost communication with robot ({reason:?})"),
+                            )).await;
                         }
                         robot_state.connected = false;
+                        robot_requests = RobotFeatureRequests::default();
+                        robot_state.brownout = false;
                         robot_state.battery_voltage = 0.0;
                         robot_state.code_running = false;
                         robot_state.enabled = false;
@@ -521,15 +1571,104 @@ pub async fn protocol_loop(
                 }
             } => {
                 if let Ok((len, addr)) = result {
+                    if simulated_robot.is_some() {
+                        continue;
+                    }
+                    if len >= recv_buf.len() {
+                        tracing::warn!("Dropping oversized UDP packet from {addr} ({len}+ bytes)");
+                        continue;
+                    }
+                    if packet_capture && capture_in.ready(Instant::now()) {
+                        let _ = event_tx.try_send(packet_capture_event(PacketDirection::Inbound, &recv_buf[..len]));
+                    }
+                    // Replies from monitored robots never touch the primary state
+                    if monitor.handle_packet(addr.ip(), &recv_buf[..len]) {
+                        continue;
+                    }
                     // Only update last_recv for valid packets (>= 7 bytes)
                     if len >= 7 {
+                        let was_running = robot_state.code_running;
+                        let was_connected = robot_state.connected;
+                        let brownouts_before = diag.brownout_count;
                         parse_inbound_packet(&recv_buf[..len], &mut robot_state, &mut diag);
+                        robot_requests.update(&recv_buf[..len]);
+                        link_stats.on_received(robot_state.sequence_number, Instant::now());
+                        if estop_confirmation.on_status(robot_state.estopped).is_some() {
+                            tracing::info!("Robot confirmed E-Stop");
+                            let _ = event_tx.send(DsEvent::EStopConfirmed).await;
+                        }
+                        publish_snapshot(&snapshot, &robot_state, &diag, &ds_state);
                         last_recv = Instant::now();
+                        let now = unix_now();
+                        telemetry.push(TelemetrySample {
+                            timestamp: now,
+                            battery_voltage: robot_state.battery_voltage,
+                            brownout: robot_state.brownout,
+                            enabled: robot_state.enabled,
+                            cpu_usage: diag.cpu_usage,
+                            can_utilization: diag.can_utilization,
+                        });
+
+                        if !was_connected {
+                            timeline.write().record(
+                                unix_now(),
+                                TimelineEvent::CommsConnected { target_ip: addr.ip().to_string() },
+                            );
+                        }
+
+                        if let Some(event) = code_transition_event(was_running, robot_state.code_running) {
+                            let stopped = matches!(event, DsEvent::RobotCodeStopped { .. });
+                            let _ = event_tx.send(event).await;
+                            if stopped {
+                                for request in ds_state.acknowledge_requests() {
+                                    let _ = event_tx.send(DsEvent::RobotRequest { request, acknowledged: true }).await;
+                                }
+                                let _ = event_tx.send(alert_event(
+                                    AlertKind::CodeStopped,
+                                    "Robot code stopped",
+                                )).await;
+                            }
+                        }
+
+                        if diag.brownout_count > brownouts_before {
+                            let _ = event_tx.send(alert_event(
+                                AlertKind::Brownout,
+                                "Robot brownout",
+                            )).await;
+                            if let Some(dir) = snapshot_dir.clone() {
+                                let samples = telemetry.samples();
+                                let count = diag.brownout_count;
+                                tokio::task::spawn_blocking(move || {
+                                    match super::snapshot::write_snapshot(&dir, now, count, &samples) {
+                                        Ok(path) => tracing::info!("Saved brownout snapshot to {}", path.display()),
+                                        Err(e) => tracing::warn!("Failed to save brownout snapshot: {e}"),
+                                    }
+                                });
+                            }
+                        }
+
+                        if let Some((level, voltage)) = battery.update(robot_state.battery_voltage) {
+                            tracing::warn!("Low battery ({level:?}): {voltage:.2}V");
+                            let _ = event_tx.send(DsEvent::BatteryWarning { level, voltage }).await;
+                            let _ = event_tx.send(alert_event(
+                                AlertKind::LowBattery,
+                                &format!("Low battery: {voltage:.1}V"),
+                            )).await;
+                        }
+
+                        if stuck_enabled.update(ds_state.enabled, robot_state.enabled) {
+                            tracing::error!("Robot still reports enabled after DS disable");
+                            let _ = event_tx.send(alert_event(
+                                AlertKind::StuckEnabled,
+                                "Robot is ignoring disable and still reports enabled",
+                            )).await;
+                        }
 
                         // Lock onto the responding IP (e.g. USB 172.22.11.2 vs static 10.TE.AM.2)
-                        // so TCP console also connects to the right address
+                        // so TCP console also connects to the right address. A preferred
+                        // USB link stays in place even if the robot also answers elsewhere.
                         let resp_ip = addr.ip().to_string();
-                        if resp_ip != target_ip {
+                        if !usb_active && resp_ip != target_ip {
                             tracing::info!("Robot responding from {resp_ip} (was {target_ip}), switching target");
                             target_ip = resp_ip.clone();
                             let _ = target_ip_tx.send(resp_ip);
@@ -540,9 +1679,28 @@ pub async fn protocol_loop(
 
             // mDNS discovery result
             Some(ip) = discovery_rx.recv() => {
-                tracing::info!("mDNS discovery resolved: {ip}");
-                target_ip = ip.clone();
-                let _ = target_ip_tx.send(ip);
+                if ds_state.simulation {
+                    continue;
+                }
+                if discovery_is_recheck {
+                    if robot_state.connected || !address_filter.confirm(&target_ip, &ip) {
+                        continue;
+                    }
+                    tracing::info!("Robot address changed: {target_ip} -> {ip} (resolved twice), switching target");
+                } else {
+                    tracing::info!("mDNS discovery resolved: {ip}");
+                    address_filter = AddressChangeFilter::default();
+                }
+                target_ip = ip;
+                let _ = target_ip_tx.send(effective_target(&target_ip, usb_active).to_string());
+            }
+
+            // Hostname lookup result (from spawned task)
+            Some(resolution) = resolve_rx.recv() => {
+                if let Some(e) = target_resolver.on_resolved(resolution, Instant::now()) {
+                    tracing::warn!("{e}; keeping the previous target");
+                    let _ = event_tx.send(alert_event(AlertKind::TargetUnresolved, &e)).await;
+                }
             }
 
             // Radio check result (from spawned task)
@@ -550,21 +1708,65 @@ pub async fn protocol_loop(
                 radio_reachable = result;
             }
 
+            // roboRIO probe result (from spawned task)
+            Some(result) = probe_result_rx.recv() => {
+                robot_probe_ok = result;
+            }
+
+            // NetworkTables check result (from spawned task)
+            Some(result) = nt_result_rx.recv() => {
+                nt_reachable = result;
+            }
+
+            // 2Hz link health summary
+            _ = link_interval.tick() => {
+                let (packet_loss, rtt_ms) = link_stats.take_window();
+                let quality = if robot_state.connected {
+                    classify_link(packet_loss, rtt_ms)
+                } else {
+                    Quality::None
+                };
+                let link = LinkQuality {
+                    connected: robot_state.connected,
+                    rtt_ms,
+                    packet_loss,
+                    battery_voltage: robot_state.battery_voltage,
+                    quality,
+                };
+                snapshot.write().link = Some(link.clone());
+                let _ = event_tx.send(DsEvent::LinkQuality(link)).await;
+            }
+
             // 10Hz event emission to frontend
             _ = event_interval.tick() => {
+                // The FMS times real matches; off FMS the practice timer does
+                let now = Instant::now();
+                practice_timer.update(ds_state.enabled, ds_state.mode, now);
+                robot_state.match_time = if robot_state.fms_connected {
+                    None
+                } else {
+                    practice_timer.remaining(now)
+                };
                 let _ = event_tx.send(DsEvent::RobotState(robot_state.clone())).await;
                 let _ = event_tx.send(DsEvent::Diagnostics(diag.clone())).await;
+                // Also catches changes made outside packet parsing (disconnect)
+                publish_snapshot(&snapshot, &robot_state, &diag, &ds_state);
+                for event in monitor.take_events() {
+                    let _ = event_tx.send(event).await;
+                }
 
                 // Re-discover roboRIO every 10s while not connected
                 if !robot_state.connected
                     && team_number > 0
+                    && !ds_state.simulation
                     && last_discovery_attempt.elapsed() > std::time::Duration::from_secs(10)
                 {
                     if pending_discovery.as_ref().map_or(true, |h| h.is_finished()) {
+                        discovery_is_recheck = true;
                         let dtx = discovery_tx.clone();
                         let team = team_number;
                         pending_discovery = Some(tokio::spawn(
-                            crate::discovery::discover_roborio(team, dtx),
+                            crate::discovery::discover_roborio(team, ip_resolver.clone(), dtx),
                         ));
                         last_discovery_attempt = Instant::now();
                     }
@@ -579,6 +1781,22 @@ pub async fn protocol_loop(
                         let result = crate::network::check_radio(&radio_ip).await;
                         let _ = rtx.send(result).await;
                     });
+                    // Skip the probe while UDP comms are up — the RIO is clearly reachable
+                    if !robot_state.connected {
+                        let robot_ip = effective_target(&target_ip, usb_active).to_string();
+                        let ptx = probe_result_tx.clone();
+                        tokio::spawn(async move {
+                            let result = crate::network::check_robot(&robot_ip).await;
+                            let _ = ptx.send(result).await;
+                        });
+                    }
+                    let nt_ip = effective_target(&target_ip, usb_active).to_string();
+                    let ntx = nt_result_tx.clone();
+                    tokio::spawn(async move {
+                        let port = crate::network::NT4_PORT;
+                        let result = crate::network::check_networktables(&nt_ip, port).await;
+                        let _ = ntx.send(result).await;
+                    });
                     last_radio_check = Instant::now();
                 }
 
@@ -586,16 +1804,494 @@ pub async fn protocol_loop(
                 let net = crate::network::check_interfaces();
                 let conn_status = ConnectionStatus {
                     enet_link: net.enet_link,
+                    any_link: net.any_link,
                     enet_ip: net.enet_ip,
                     robot_radio: radio_reachable,
                     robot: robot_state.connected,
-                    robot_ip: if robot_state.connected { Some(target_ip.clone()) } else { None },
+                    robot_ip: if robot_state.connected {
+                        Some(effective_target(&target_ip, usb_active).to_string())
+                    } else {
+                        None
+                    },
+                    target_ip: effective_target(&target_ip, usb_active).to_string(),
+                    simulation: ds_state.simulation,
+                    robot_status: RobotStatus::from_probe(
+                        robot_probe_ok,
+                        robot_state.connected,
+                        robot_state.code_running,
+                    ),
                     fms: robot_state.fms_connected,
                     wifi: net.wifi,
                     usb: net.usb,
+                    nt_connected: nt_reachable,
                 };
                 let _ = event_tx.send(DsEvent::ConnectionStatus(conn_status)).await;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// roboRIO status packet header with the given status byte and voltage,
+    /// followed by `tags`
+    fn status_packet(status: u8, volts: u8, volts_256ths: u8, tags: &[u8]) -> Vec<u8> {
+        let mut pkt = vec![0, 1, 1, status, 0x20, volts, volts_256ths, 0];
+        pkt.extend_from_slice(tags);
+        pkt
+    }
+
+    #[test]
+    fn watchdog_trips_only_when_enabled_and_quiet() {
+        let timeout = Duration::from_secs(2);
+        assert!(watchdog_expired(true, Duration::from_millis(2001), timeout));
+        assert!(!watchdog_expired(true, Duration::from_secs(2), timeout));
+        assert!(!watchdog_expired(true, Duration::from_millis(500), timeout));
+        assert!(!watchdog_expired(false, Duration::from_secs(60), timeout));
+    }
+
+    #[test]
+    fn practice_timer_counts_down_each_period() {
+        let start = Instant::now();
+        let mut timer = PracticeTimer::default();
+        timer.update(false, Mode::Autonomous, start);
+        assert_eq!(timer.remaining(start), None);
+
+        timer.update(true, Mode::Autonomous, start);
+        assert_eq!(timer.remaining(start + Duration::from_secs(5)), Some(10.0));
+        assert_eq!(timer.remaining(start + Duration::from_secs(20)), Some(0.0));
+
+        // Switching to teleop restarts the countdown for that period
+        let teleop = start + Duration::from_secs(16);
+        timer.update(true, Mode::Teleoperated, teleop);
+        timer.update(true, Mode::Teleoperated, teleop + Duration::from_secs(1));
+        assert_eq!(timer.remaining(teleop + Duration::from_secs(35)), Some(100.0));
+
+        timer.update(false, Mode::Teleoperated, teleop + Duration::from_secs(40));
+        assert_eq!(timer.remaining(teleop + Duration::from_secs(40)), None);
+    }
+
+    #[test]
+    fn brownout_onsets_and_minimum_voltage_are_tracked() {
+        let mut state = RobotState::default();
+        let mut diag = DiagnosticData::default();
+        // Brownout bit on, on, off, on: two onsets
+        let packets = [(0, 12, 0), (0x10, 6, 128), (0x10, 6, 64), (0, 11, 0), (0x10, 7, 0)];
+        for (status, volts, frac) in packets {
+            parse_inbound_packet(&status_packet(status, volts, frac, &[]), &mut state, &mut diag);
+        }
+        assert_eq!(diag.brownout_count, 2);
+        assert_eq!(diag.brownout_history.len(), 2);
+        assert_eq!(diag.min_voltage, Some(6.25));
+        assert!(state.brownout);
+    }
+
+    #[test]
+    fn buttons_are_padded_to_the_minimum_count() {
+        let state = DsState { min_button_count: 16, ..DsState::default() };
+        let mut buttons = vec![false; 10];
+        buttons[9] = true;
+        let js = JoystickState { axes: vec![0.0; 2], buttons, povs: vec![-1] };
+        let pkt = build_outbound_packet(1, &state, &[js], &RobotFeatureRequests::default());
+
+        // Header (6), then [size][0x0C][2 axes][count][button bytes][1 POV]
+        assert_eq!(&pkt[6..8], &[1 + 3 + 3 + 3, 0x0C]);
+        assert_eq!(pkt[11], 16);
+        assert_eq!(&pkt[12..14], &[0x00, 0x40]);
+        assert_eq!(pkt[14], 1);
+        assert_eq!(pkt.len(), 17);
+    }
+
+    #[test]
+    fn code_stopping_emits_one_stop_event() {
+        let mut state = RobotState::default();
+        let mut diag = DiagnosticData::default();
+        let running = status_packet(0, 12, 0, &[]);
+        let mut stopped = running.clone();
+        stopped[4] = 0;
+
+        let mut events = Vec::new();
+        for pkt in [&running, &running, &stopped, &stopped] {
+            let was_running = state.code_running;
+            parse_inbound_packet(pkt, &mut state, &mut diag);
+            events.extend(code_transition_event(was_running, state.code_running));
+        }
+        assert!(matches!(
+            events[..],
+            [DsEvent::RobotCodeStarted { .. }, DsEvent::RobotCodeStopped { .. }]
+        ));
+    }
+
+    #[test]
+    fn alliance_override_changes_only_the_sent_station() {
+        let mut state = DsState { alliance: Alliance::Red1, ..DsState::default() };
+        state.alliance_override = Some(Alliance::Blue3);
+        let pkt = build_outbound_packet(1, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt[5], Alliance::Blue3.to_byte());
+        assert_eq!(state.alliance, Alliance::Red1);
+        assert!(state.alliance_info().overridden);
+
+        state.alliance_override = None;
+        let pkt = build_outbound_packet(2, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt[5], Alliance::Red1.to_byte());
+    }
+
+    #[test]
+    fn stuck_enabled_alerts_only_after_the_threshold() {
+        let mut detector = StuckEnabledDetector::default();
+        // Transient lag after a disable: the robot catches up in time
+        for _ in 0..STUCK_ENABLED_THRESHOLD - 1 {
+            assert!(!detector.update(false, true));
+        }
+        assert!(!detector.update(false, false));
+
+        let alerts = (0..3 * STUCK_ENABLED_THRESHOLD)
+            .filter(|_| detector.update(false, true))
+            .count();
+        assert_eq!(alerts, 1);
+    }
+
+    #[test]
+    fn random_status_packets_never_panic() {
+        // xorshift, so failures reproduce without a rand dependency
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let mut state = RobotState::default();
+        let mut diag = DiagnosticData::default();
+        for _ in 0..2_000 {
+            let len = (next() % 96) as usize;
+            let mut data: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            // Keep some packets on the tag-parsing path with plausible sizes
+            if len > 9 && next() % 2 == 0 {
+                data[8] = (next() % 16) as u8;
+            }
+            parse_inbound_packet(&data, &mut state, &mut diag);
+        }
+    }
+
+    #[test]
+    fn no_command_sequence_enables_while_estopped() {
+        let commands = [
+            DsCommand::Enable,
+            DsCommand::Disable,
+            DsCommand::EStop,
+            DsCommand::SetMode(Mode::Autonomous),
+            DsCommand::RebootRio,
+            DsCommand::RestartCode,
+        ];
+        let n = commands.len();
+        for sequence in 0..n.pow(4) {
+            let mut state = DsState::default();
+            let mut rest = sequence;
+            for _ in 0..4 {
+                let cmd = &commands[rest % n];
+                rest /= n;
+                let was_estopped = state.estop;
+                state.apply_control(cmd);
+                assert!(!(state.estop && state.enabled), "after {cmd:?}");
+                // Only a roboRIO reboot clears the latch
+                if was_estopped && !matches!(cmd, DsCommand::RebootRio) {
+                    assert!(state.estop, "{cmd:?} cleared E-Stop");
+                }
+                let pkt = build_outbound_packet(1, &state, &[], &RobotFeatureRequests::default());
+                assert_ne!(pkt[3] & 0x84, 0x84);
+            }
+        }
+    }
+
+    #[test]
+    fn usb_link_takes_precedence_only_when_preferred_and_present() {
+        // (prefer_usb, simulation, usb_detected) -> USB in use
+        let cases = [
+            ((true, false, true), true),
+            ((true, false, false), false),
+            ((false, false, true), false),
+            ((true, true, true), false),
+        ];
+        for ((prefer, simulation, detected), expected) in cases {
+            let use_usb = usb_preferred(prefer, simulation, detected);
+            assert_eq!(use_usb, expected, "{prefer} {simulation} {detected}");
+            let target = effective_target("10.12.34.2", use_usb);
+            assert_eq!(target, if expected { USB_ROBOT_IP } else { "10.12.34.2" });
+        }
+    }
+
+    #[test]
+    fn game_data_is_sent_until_cleared() {
+        let mut state = DsState { game_data: "LRL".into(), ..DsState::default() };
+        let pkt = build_outbound_packet(1, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(&pkt[6..], &[4, 0x0E, b'L', b'R', b'L']);
+
+        // An empty preset clears it: no game data tag at all
+        state.game_data.clear();
+        let pkt = build_outbound_packet(2, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt.len(), 6);
+    }
+
+    #[test]
+    fn configured_layout_sizes_the_joystick_tag() {
+        let mut js = JoystickState::default();
+        JoystickLayout { axes: 4, buttons: 12, povs: 2 }.apply(&mut js);
+        let pkt = build_outbound_packet(1, &DsState::default(), &[js], &RobotFeatureRequests::default());
+
+        // id + (count + 4 axes) + (count + 2 button bytes) + (count + 2 POVs * 2)
+        assert_eq!(pkt[6], 1 + 5 + 3 + 5);
+        assert_eq!(pkt[8], 4);
+        assert_eq!(pkt[13], 12);
+        assert_eq!(pkt[16], 2);
+        assert_eq!(&pkt[17..], &[0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn request_bits_are_sent_for_exactly_the_repeat_count() {
+        let mut state = DsState { request_repeat: 3, ..DsState::default() };
+        state.apply_control(&DsCommand::RebootRio);
+        let mut finished = Vec::new();
+        for seq in 0..3 {
+            let pkt = build_outbound_packet(seq, &state, &[], &RobotFeatureRequests::default());
+            assert_eq!(pkt[4], 0x08, "packet {seq}");
+            finished.extend(state.request_packet_sent());
+        }
+        assert!(matches!(finished[..], [RobotRequest::RebootRio]));
+        let pkt = build_outbound_packet(3, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt[4], 0);
+
+        state.apply_control(&DsCommand::RestartCode);
+        let pkt = build_outbound_packet(4, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt[4], 0x04);
+        assert!(matches!(state.acknowledge_requests()[..], [RobotRequest::RestartCode]));
+        let pkt = build_outbound_packet(5, &state, &[], &RobotFeatureRequests::default());
+        assert_eq!(pkt[4], 0);
+    }
+
+    #[test]
+    fn snapshot_reflects_the_last_parsed_packet() {
+        let snapshot = SharedRobotSnapshot::default();
+        let state = DsState { alliance: Alliance::Blue2, ..DsState::default() };
+        let (mut robot_state, mut diag) = (RobotState::default(), DiagnosticData::default());
+        for (status, volts) in [(0x04, 12), (0x00, 11)] {
+            parse_inbound_packet(&status_packet(status, volts, 128, &[]), &mut robot_state, &mut diag);
+            publish_snapshot(&snapshot, &robot_state, &diag, &state);
+        }
+
+        let snap = snapshot.read().clone();
+        assert!((snap.robot_state.battery_voltage - 11.5).abs() < 0.01);
+        assert!(!snap.robot_state.enabled);
+        assert_eq!(snap.alliance, state.alliance_info());
+        assert!(snap.link.is_none());
+    }
+
+    #[test]
+    fn enabling_logs_one_control_line() {
+        let (file_log_tx, mut file_log_rx) = mpsc::channel(8);
+        let mut state = DsState::default();
+        for cmd in [DsCommand::Enable, DsCommand::Disable, DsCommand::Disable] {
+            let event = state.apply_control(&cmd);
+            if let Some(action) = control_action(&cmd, event.as_ref()) {
+                log_control(&file_log_tx, &action, &state);
+            }
+        }
+
+        let lines: Vec<String> =
+            std::iter::from_fn(|| file_log_rx.try_recv().ok()).map(|m| m.message).collect();
+        let prefix = crate::log_writer::DS_ORIGIN_PREFIX;
+        assert_eq!(
+            lines,
+            [
+                format!("{prefix} Enabled -> Teleoperated, enabled"),
+                // The repeated disable changed nothing and is not logged
+                format!("{prefix} Disabled -> Teleoperated, disabled"),
+            ]
+        );
+        assert_eq!(
+            control_action(&DsCommand::RestartCode, None).as_deref(),
+            Some("Restart robot code requested")
+        );
+    }
+
+    #[test]
+    fn estop_is_confirmed_by_the_status_bit() {
+        let start = Instant::now();
+        let (mut robot_state, mut diag) = (RobotState::default(), DiagnosticData::default());
+        let mut confirmation = EStopConfirmation::default();
+        // A stopped robot before any E-Stop confirms nothing
+        parse_inbound_packet(&status_packet(0x80, 12, 0, &[]), &mut robot_state, &mut diag);
+        assert_eq!(confirmation.on_status(robot_state.estopped), None);
+
+        confirmation.latched(start);
+        parse_inbound_packet(&status_packet(0x00, 12, 0, &[]), &mut robot_state, &mut diag);
+        assert_eq!(confirmation.on_status(robot_state.estopped), None);
+        parse_inbound_packet(&status_packet(0x80, 12, 0, &[]), &mut robot_state, &mut diag);
+        assert_eq!(confirmation.on_status(robot_state.estopped), Some(EStopCheck::Confirmed));
+        assert_eq!(confirmation.on_status(robot_state.estopped), None);
+        assert_eq!(confirmation.check_timeout(start + ESTOP_CONFIRM_TIMEOUT), None);
+
+        // Without the bit, the warning fires once after the timeout
+        confirmation.latched(start);
+        assert_eq!(confirmation.check_timeout(start + Duration::from_millis(100)), None);
+        let late = start + ESTOP_CONFIRM_TIMEOUT;
+        assert_eq!(confirmation.check_timeout(late), Some(EStopCheck::Unconfirmed));
+        assert_eq!(confirmation.check_timeout(late), None);
+    }
+
+    #[test]
+    fn byte_two_carries_the_comm_version() {
+        let requests = RobotFeatureRequests::default();
+        let pkt = build_outbound_packet(1, &DsState::default(), &[], &requests);
+        assert_eq!(pkt[2], DEFAULT_COMM_VERSION);
+
+        let state = DsState { comm_version: 0x02, ..DsState::default() };
+        let pkt = build_outbound_packet(1, &state, &[], &requests);
+        assert_eq!(pkt[2], 0x02);
+        assert_eq!(summarize_outbound(&pkt).unwrap().comm_version, 0x02);
+    }
+
+    #[test]
+    fn date_tag_follows_the_robot_request_bit() {
+        let has_date = |seq, requests: &RobotFeatureRequests| {
+            let pkt = build_outbound_packet(seq, &DsState::default(), &[], requests);
+            packet_tags(&pkt, 6).iter().any(|t| t.id == 0x0F)
+        };
+        let mut requests = RobotFeatureRequests::default();
+        // Before negotiation, every 50th packet carries it
+        assert!(has_date(50, &requests));
+        assert!(!has_date(51, &requests));
+
+        let mut status = status_packet(0, 12, 0, &[]);
+        status[7] = 0x01;
+        requests.update(&status);
+        assert!(has_date(51, &requests));
+
+        // Once negotiated, a cleared bit stops it entirely
+        status[7] = 0x00;
+        requests.update(&status);
+        assert!(requests.negotiated);
+        assert!(!has_date(50, &requests));
+    }
+
+    #[test]
+    fn reply_just_inside_the_disconnect_timeout_keeps_the_link() {
+        let timeout = DEFAULT_DISCONNECT_TIMEOUT;
+        assert!(!comms_lost(timeout - Duration::from_millis(1), timeout));
+        assert!(!comms_lost(timeout, timeout));
+        assert!(comms_lost(timeout + Duration::from_millis(1), timeout));
+
+        let slow_link = Duration::from_secs(5);
+        assert!(!comms_lost(Duration::from_millis(4_900), slow_link));
+    }
+
+    #[test]
+    fn ip_resolver_default_and_custom_templates() {
+        let resolver = TeamIpResolver::default();
+        assert_eq!(resolver.team_to_ip(1234), "10.12.34.2");
+        assert_eq!(resolver.team_to_ip(254), "10.2.54.2");
+        assert_eq!(resolver.team_to_ip(1), "10.0.1.2");
+        assert_eq!(resolver.team_to_ip(0), "127.0.0.1");
+
+        let custom = TeamIpResolver::new(" 172.22.{te}.{am} ").unwrap();
+        assert_eq!(custom.template(), "172.22.{te}.{am}");
+        assert_eq!(custom.team_to_ip(1234), "172.22.12.34");
+
+        assert!(TeamIpResolver::new("10.{team}.0.2").is_err());
+        assert!(TeamIpResolver::new("roborio-{team}-frc.local").is_err());
+    }
+
+    #[test]
+    fn can_metrics_decode_full_and_truncated_tags() {
+        let mut tag = 0.25f32.to_be_bytes().to_vec();
+        tag.extend_from_slice(&3u32.to_be_bytes());
+        tag.extend_from_slice(&7u32.to_be_bytes());
+        tag.extend_from_slice(&[12, 34]);
+        let mut diag = DiagnosticData::default();
+        parse_can_metrics(&tag, &mut diag);
+        let counters = |d: &DiagnosticData| {
+            (d.can_bus_off, d.can_tx_full, d.can_rx_error, d.can_tx_error)
+        };
+        assert_eq!(diag.can_utilization, 0.25);
+        assert_eq!(counters(&diag), (3, 7, 12, 34));
+
+        // Older firmware: utilization and bus-off only; the rest is kept
+        let mut short = 0.5f32.to_be_bytes().to_vec();
+        short.extend_from_slice(&4u32.to_be_bytes());
+        short.extend_from_slice(&[0, 0]);
+        parse_can_metrics(&short, &mut diag);
+        assert_eq!(diag.can_utilization, 0.5);
+        assert_eq!(counters(&diag), (4, 7, 12, 34));
+        parse_can_metrics(&[], &mut diag);
+        assert_eq!(diag.can_utilization, 0.5);
+    }
+
+    #[test]
+    fn last_outbound_packet_decodes_to_the_sent_state() {
+        let last_outbound = SharedOutboundPacket::default();
+        assert!(OutboundPacket::decode(&last_outbound.read()).is_none());
+
+        let state = DsState {
+            mode: Mode::Autonomous,
+            enabled: true,
+            alliance: Alliance::Blue3,
+            ..DsState::default()
+        };
+        let js = [JoystickState::default()];
+        let pkt = build_outbound_packet(0x1234, &state, &js, &RobotFeatureRequests::default());
+        last_outbound.write().clone_from(&pkt);
+
+        let decoded = OutboundPacket::decode(&last_outbound.read()).unwrap();
+        assert_eq!(decoded.seq, 0x1234);
+        assert!(decoded.hex.to_ascii_lowercase().starts_with("12 34"), "{}", decoded.hex);
+        let parsed = decoded.parsed;
+        assert_eq!((parsed.mode, parsed.enabled, parsed.estop), (Mode::Autonomous, true, false));
+        assert_eq!(parsed.alliance, Some(Alliance::Blue3));
+        // (count + 6 axes) + (count + 2 button bytes) + (count + 1 POV * 2)
+        assert_eq!(parsed.joystick_tag_sizes, [7 + 3 + 3]);
+    }
+
+    #[test]
+    fn disabled_joysticks_are_sent_at_rest() {
+        let mut js = JoystickState::default();
+        js.axes[0] = 1.0;
+        js.axes[5] = -0.5;
+        js.buttons[0] = true;
+        js.povs[0] = 90;
+        let state = DsState { joysticks_enabled: false, ..DsState::default() };
+        let requests = RobotFeatureRequests::default();
+        let pkt = build_outbound_packet(1, &state, &[js.clone()], &requests);
+        let idle = build_outbound_packet(1, &state, &[js.at_rest()], &requests);
+        assert_eq!(pkt, idle);
+
+        // Same layout, every input at rest
+        assert_eq!(pkt[8], 6);
+        assert_eq!(pkt[9..15], [0; 6]);
+        assert_eq!(pkt[15..18], [16, 0, 0]);
+        assert_eq!(pkt[18..], [1, 0xFF, 0xFF]);
+
+        let live = build_outbound_packet(1, &DsState::default(), &[js], &requests);
+        assert_eq!(live[9], 127);
+    }
+
+    #[test]
+    fn team_numbers_validate_up_to_the_addressable_limit() {
+        let resolver = TeamIpResolver::default();
+        for (team, ip) in [
+            (0, "127.0.0.1"),
+            (1, "10.0.1.2"),
+            (9999, "10.99.99.2"),
+            (25599, "10.255.99.2"),
+        ] {
+            assert_eq!(validate_team_number(team), Ok(()));
+            assert_eq!(resolver.team_to_ip(team), ip);
+        }
+        // 10.256.0.2 isn't an IPv4 address
+        for team in [25600, 99999] {
+            let err = validate_team_number(team).unwrap_err();
+            assert!(err.contains(&team.to_string()), "{err}");
+        }
+    }
+}
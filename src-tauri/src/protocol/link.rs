@@ -0,0 +1,108 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Sent packets remembered for matching replies (~1.3s at 50Hz)
+const SEQ_HISTORY: usize = 64;
+
+/// Coarse link health for the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quality {
+    Good,
+    Fair,
+    Poor,
+    /// No replies from the robot
+    None,
+}
+
+/// Loss (percent) and round trip (ms) at or below which the link is Good
+const GOOD_LOSS: f32 = 2.0;
+const GOOD_RTT: f32 = 20.0;
+/// Loss (percent) and round trip (ms) at or below which the link is Fair
+const FAIR_LOSS: f32 = 10.0;
+const FAIR_RTT: f32 = 50.0;
+
+/// Classify a link from its packet loss (percent) and average round trip
+/// (ms, None if no reply arrived)
+pub fn classify_link(loss: f32, rtt: Option<f32>) -> Quality {
+    let Some(rtt) = rtt else {
+        return Quality::None;
+    };
+    if loss >= 100.0 {
+        Quality::None
+    } else if loss <= GOOD_LOSS && rtt <= GOOD_RTT {
+        Quality::Good
+    } else if loss <= FAIR_LOSS && rtt <= FAIR_RTT {
+        Quality::Fair
+    } else {
+        Quality::Poor
+    }
+}
+
+/// Link health summary, emitted at 2Hz
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkQuality {
+    pub connected: bool,
+    /// Average round trip over the last window; None without replies
+    pub rtt_ms: Option<f32>,
+    pub packet_loss: f32,
+    pub battery_voltage: f32,
+    pub quality: Quality,
+}
+
+/// Matches robot replies to sent packets by sequence number (the roboRIO
+/// echoes the DS sequence) to measure round trip and loss per window
+#[derive(Debug)]
+pub struct LinkStats {
+    sent_at: [Option<(u16, Instant)>; SEQ_HISTORY],
+    sent: u32,
+    received: u32,
+    rtt_total_ms: f64,
+}
+
+impl Default for LinkStats {
+    fn default() -> Self {
+        Self {
+            sent_at: [None; SEQ_HISTORY],
+            sent: 0,
+            received: 0,
+            rtt_total_ms: 0.0,
+        }
+    }
+}
+
+impl LinkStats {
+    pub fn on_sent(&mut self, seq: u16, now: Instant) {
+        self.sent_at[seq as usize % SEQ_HISTORY] = Some((seq, now));
+        self.sent += 1;
+    }
+
+    /// Record a reply echoing `seq`. Replies to unknown or already-matched
+    /// packets are ignored.
+    pub fn on_received(&mut self, seq: u16, now: Instant) {
+        let slot = &mut self.sent_at[seq as usize % SEQ_HISTORY];
+        if let Some((sent_seq, at)) = *slot {
+            if sent_seq == seq {
+                *slot = None;
+                self.received += 1;
+                self.rtt_total_ms += now.duration_since(at).as_secs_f64() * 1000.0;
+            }
+        }
+    }
+
+    /// Loss (percent) and average round trip (ms) since the last call, then
+    /// start a new window
+    pub fn take_window(&mut self) -> (f32, Option<f32>) {
+        let loss = if self.sent == 0 {
+            0.0
+        } else {
+            let received = self.received.min(self.sent);
+            100.0 * (self.sent - received) as f32 / self.sent as f32
+        };
+        let rtt = (self.received > 0).then(|| (self.rtt_total_ms / self.received as f64) as f32);
+        self.sent = 0;
+        self.received = 0;
+        self.rtt_total_ms = 0.0;
+        (loss, rtt)
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt::{Display, Write};
+
+use super::types::{Mode, RobotSnapshot};
+
+/// Render the robot snapshot in the Prometheus text exposition format, for
+/// pit dashboards that scrape several DS instances. Metric names are stable:
+///
+/// | metric                        | type    | value                            |
+/// |-------------------------------|---------|----------------------------------|
+/// | `ds_robot_connected`          | gauge   | 1 while the robot replies        |
+/// | `ds_robot_code_running`       | gauge   | 1/0                              |
+/// | `ds_robot_enabled`            | gauge   | 1/0                              |
+/// | `ds_robot_estopped`           | gauge   | 1/0                              |
+/// | `ds_robot_mode{mode=...}`     | gauge   | 1 for the current mode           |
+/// | `ds_battery_voltage`          | gauge   | volts                            |
+/// | `ds_brownouts_total`          | counter | brownout onsets this session     |
+/// | `ds_link_rtt_ms`              | gauge   | NaN without replies              |
+/// | `ds_link_packet_loss_percent` | gauge   | last 0.5s window                 |
+/// | `ds_cpu_usage_percent`        | gauge   | roboRIO CPU, 0-100               |
+/// | `ds_can_utilization_percent`  | gauge   | CAN bus load, 0-100              |
+/// | `ds_ram_free_bytes`           | gauge   |                                  |
+/// | `ds_disk_free_bytes`          | gauge   |                                  |
+/// | `ds_can_<fault>_total`        | counter | bus_off, tx_full                 |
+/// | `ds_can_rx_error`             | gauge   | receive error count (REC)        |
+/// | `ds_can_tx_error`             | gauge   | transmit error count (TEC)       |
+///
+/// Counters restart from zero when diagnostics are reset.
+pub fn render_metrics(snapshot: &RobotSnapshot) -> String {
+    let state = &snapshot.robot_state;
+    let diag = &snapshot.diagnostics;
+    let link = snapshot.link.as_ref();
+    let mut out = String::new();
+
+    gauge(&mut out, "ds_robot_connected", "Robot is communicating", u8::from(state.connected));
+    gauge(&mut out, "ds_robot_code_running", "Robot code is running", u8::from(state.code_running));
+    gauge(&mut out, "ds_robot_enabled", "Robot is enabled", u8::from(state.enabled));
+    gauge(&mut out, "ds_robot_estopped", "Robot is E-Stopped", u8::from(state.estopped));
+
+    header(&mut out, "ds_robot_mode", "Control mode (1 = current)", "gauge");
+    for (mode, label) in [
+        (Mode::Teleoperated, "teleoperated"),
+        (Mode::Autonomous, "autonomous"),
+        (Mode::Test, "test"),
+    ] {
+        let _ = writeln!(out, "ds_robot_mode{{mode=\"{label}\"}} {}", u8::from(state.mode == mode));
+    }
+
+    gauge(&mut out, "ds_battery_voltage", "Battery voltage", state.battery_voltage);
+    counter(&mut out, "ds_brownouts_total", "Brownout onsets", diag.brownout_count);
+    let rtt = link.and_then(|l| l.rtt_ms).unwrap_or(f32::NAN);
+    gauge(&mut out, "ds_link_rtt_ms", "Average round trip to the robot", rtt);
+    let loss = link.map_or(0.0, |l| l.packet_loss);
+    gauge(&mut out, "ds_link_packet_loss_percent", "Control packets without a reply", loss);
+    // Diagnostics hold fractions (0..1)
+    gauge(&mut out, "ds_cpu_usage_percent", "roboRIO CPU usage", diag.cpu_usage * 100.0);
+    let can = diag.can_utilization * 100.0;
+    gauge(&mut out, "ds_can_utilization_percent", "CAN bus utilization", can);
+    gauge(&mut out, "ds_ram_free_bytes", "roboRIO free RAM", diag.ram_free);
+    gauge(&mut out, "ds_disk_free_bytes", "roboRIO free disk", diag.disk_free);
+    counter(&mut out, "ds_can_bus_off_total", "CAN bus-off events", diag.can_bus_off);
+    counter(&mut out, "ds_can_tx_full_total", "CAN transmit buffer full events", diag.can_tx_full);
+    gauge(&mut out, "ds_can_rx_error", "CAN receive error count (REC)", diag.can_rx_error);
+    gauge(&mut out, "ds_can_tx_error", "CAN transmit error count (TEC)", diag.can_tx_error);
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: impl Display) {
+    header(out, name, help, "gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: impl Display) {
+    header(out, name, help, "counter");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_metrics_are_percentages() {
+        let mut snapshot = RobotSnapshot::default();
+        snapshot.diagnostics.cpu_usage = 0.5;
+        snapshot.diagnostics.can_utilization = 0.25;
+        let text = render_metrics(&snapshot);
+        assert!(text.contains("\nds_cpu_usage_percent 50\n"), "{text}");
+        assert!(text.contains("\nds_can_utilization_percent 25\n"), "{text}");
+        assert!(text.contains("# TYPE ds_can_rx_error gauge"));
+    }
+}
@@ -1,2 +1,11 @@
+pub mod battery;
 pub mod connection;
+pub mod link;
+pub mod metrics;
+pub mod monitor;
+pub mod resolve;
+pub mod selftest;
+pub mod simulator;
+pub mod snapshot;
+pub mod timeline;
 pub mod types;
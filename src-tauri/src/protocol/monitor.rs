@@ -0,0 +1,261 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use tokio::net::UdpSocket;
+
+use super::battery::BatteryMonitor;
+use super::connection::{
+    build_outbound_packet, parse_inbound_packet, DsCommand, DsEvent, DsState,
+    RobotFeatureRequests,
+};
+use super::types::{DiagnosticData, JoystickState, RobotState};
+
+/// Id of the robot the main connection talks to
+pub const PRIMARY_ROBOT_ID: &str = "primary";
+
+/// A monitored robot is marked disconnected after this long without a reply
+const MONITOR_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A secondary robot connected alongside the primary one. It is sent its own
+/// control state, which only changes through commands addressed to its id or
+/// while it is the active robot.
+struct MonitoredRobot {
+    id: String,
+    ip: IpAddr,
+    /// Own ephemeral source port, separate from the primary send socket
+    socket: UdpSocket,
+    ds_state: DsState,
+    robot_state: RobotState,
+    diag: DiagnosticData,
+    battery: BatteryMonitor,
+    sequence: u16,
+    last_recv: Instant,
+}
+
+/// Secondary robots keyed by id, for benches with more than one robot
+#[derive(Default)]
+pub struct RobotMonitor {
+    robots: Vec<MonitoredRobot>,
+    /// Secondary robot that receives unaddressed control commands and the
+    /// joysticks; None while the primary robot is active
+    active: Option<String>,
+    /// Events raised by incoming packets, already tagged with the robot id
+    pending: Vec<DsEvent>,
+}
+
+/// Tag `event` as coming from the secondary robot `robot_id`
+fn for_robot(robot_id: &str, event: DsEvent) -> DsEvent {
+    DsEvent::ForRobot { robot_id: robot_id.to_string(), event: Box::new(event) }
+}
+
+impl RobotMonitor {
+    /// Start monitoring `ip` under `id`, replacing any robot with the same id
+    pub async fn add(&mut self, id: String, ip: IpAddr) -> std::io::Result<()> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        self.remove(&id);
+        tracing::info!("Monitoring robot {id} at {ip}");
+        self.robots.push(MonitoredRobot {
+            id,
+            ip,
+            socket,
+            ds_state: DsState::default(),
+            robot_state: RobotState::default(),
+            diag: DiagnosticData::default(),
+            battery: BatteryMonitor::default(),
+            sequence: 0,
+            last_recv: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Stop monitoring `id`. Removing the active robot makes the primary
+    /// robot active again.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.robots.len();
+        self.robots.retain(|r| r.id != id);
+        if self.active.as_deref() == Some(id) {
+            self.active = None;
+        }
+        self.robots.len() != before
+    }
+
+    /// Id of the secondary robot that is active, if any
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Make `id` the active robot (`PRIMARY_ROBOT_ID` for the primary one).
+    /// Every monitored robot is disabled on a switch; the caller disables
+    /// the primary robot.
+    pub fn set_active(&mut self, id: &str) -> Result<(), String> {
+        let active = if id == PRIMARY_ROBOT_ID {
+            None
+        } else if self.robots.iter().any(|r| r.id == id) {
+            Some(id.to_string())
+        } else {
+            return Err(format!("No robot with id {id}"));
+        };
+        if active != self.active {
+            self.disable_all();
+            self.active = active;
+        }
+        Ok(())
+    }
+
+    /// Apply a control command (enable, mode, E-Stop, reboot...) to the
+    /// monitored robot `id`
+    pub fn apply_control(&mut self, id: &str, cmd: &DsCommand) -> Result<(), String> {
+        let robot = self
+            .robots
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| format!("No robot with id {id}"))?;
+        robot.ds_state.apply_control(cmd);
+        tracing::info!("Robot {id}: {cmd:?}");
+        Ok(())
+    }
+
+    /// Latch E-Stop on every monitored robot
+    pub fn estop_all(&mut self) {
+        for robot in &mut self.robots {
+            robot.ds_state.apply_control(&DsCommand::EStop);
+        }
+    }
+
+    pub fn disable_all(&mut self) {
+        for robot in &mut self.robots {
+            robot.ds_state.enabled = false;
+        }
+    }
+
+    pub fn any_enabled(&self) -> bool {
+        self.robots.iter().any(|r| r.ds_state.enabled)
+    }
+
+    /// Send one control packet to every monitored robot. Only the active
+    /// robot gets `joysticks`.
+    pub async fn send_all(&mut self, joysticks: &[JoystickState]) {
+        for robot in &mut self.robots {
+            let active = self.active.as_deref() == Some(robot.id.as_str());
+            let pkt = build_outbound_packet(
+                robot.sequence,
+                &robot.ds_state,
+                if active { joysticks } else { &[] },
+                &RobotFeatureRequests::default(),
+            );
+            let dest = SocketAddr::new(robot.ip, 1110);
+            if let Err(e) = robot.socket.send_to(&pkt, dest).await {
+                tracing::trace!("Monitor send error ({}): {e}", robot.id);
+            }
+            robot.sequence = robot.sequence.wrapping_add(1);
+            robot.ds_state.request_packet_sent();
+
+            if robot.last_recv.elapsed() > MONITOR_TIMEOUT && robot.robot_state.connected {
+                tracing::info!("Monitored robot {} disconnected", robot.id);
+                robot.robot_state = RobotState::default();
+                robot.battery.reset();
+                // Same as the primary robot: re-enabling after a reboot is allowed
+                robot.ds_state.enabled = false;
+                robot.ds_state.estop = false;
+            }
+        }
+    }
+
+    /// Parse a status packet from a monitored robot. Returns false if `ip`
+    /// is not monitored, leaving the packet to the primary connection.
+    pub fn handle_packet(&mut self, ip: IpAddr, data: &[u8]) -> bool {
+        let Some(robot) = self.robots.iter_mut().find(|r| r.ip == ip) else {
+            return false;
+        };
+        if data.len() >= 7 {
+            parse_inbound_packet(data, &mut robot.robot_state, &mut robot.diag);
+            robot.last_recv = Instant::now();
+            if let Some((level, voltage)) = robot.battery.update(robot.robot_state.battery_voltage) {
+                tracing::warn!("Robot {} low battery ({level:?}): {voltage:.2}V", robot.id);
+                self.pending
+                    .push(for_robot(&robot.id, DsEvent::BatteryWarning { level, voltage }));
+            }
+        }
+        true
+    }
+
+    /// Current state and diagnostics of every monitored robot, followed by
+    /// the events raised since the last call, all tagged with their robot id
+    pub fn take_events(&mut self) -> Vec<DsEvent> {
+        let mut events = Vec::with_capacity(self.robots.len() * 2 + self.pending.len());
+        for robot in &self.robots {
+            events.push(for_robot(&robot.id, DsEvent::RobotState(robot.robot_state.clone())));
+            events.push(for_robot(&robot.id, DsEvent::Diagnostics(robot.diag.clone())));
+        }
+        events.append(&mut self.pending);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::battery::BatteryLevel;
+
+    /// Minimal roboRIO status packet with the given status byte and voltage
+    fn status_packet(status: u8, volts: u8) -> [u8; 8] {
+        [0, 1, 1, status, 0x20, volts, 0, 0]
+    }
+
+    fn robot_event<'a>(events: &'a [DsEvent], id: &str, name: &str) -> Vec<&'a DsEvent> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                DsEvent::ForRobot { robot_id, event } if robot_id == id => Some(event.as_ref()),
+                _ => None,
+            })
+            .filter(|e| {
+                matches!(
+                    (name, e),
+                    ("state", DsEvent::RobotState(_)) | ("battery", DsEvent::BatteryWarning { .. })
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn robots_keep_independent_state_and_tagged_events() {
+        let a: IpAddr = "127.0.0.2".parse().unwrap();
+        let b: IpAddr = "127.0.0.3".parse().unwrap();
+        let mut monitor = RobotMonitor::default();
+        monitor.add("a".into(), a).await.unwrap();
+        monitor.add("b".into(), b).await.unwrap();
+
+        monitor.apply_control("a", &DsCommand::Enable).unwrap();
+        assert!(monitor.robots[0].ds_state.enabled);
+        assert!(!monitor.robots[1].ds_state.enabled);
+
+        // Enabled at 12V on a, E-Stopped and sagging to 6V on b
+        assert!(monitor.handle_packet(a, &status_packet(0x04, 12)));
+        for _ in 0..25 {
+            assert!(monitor.handle_packet(b, &status_packet(0x80, 6)));
+        }
+        assert!(!monitor.handle_packet("127.0.0.4".parse().unwrap(), &status_packet(0, 12)));
+
+        let events = monitor.take_events();
+        match robot_event(&events, "a", "state")[..] {
+            [DsEvent::RobotState(state)] => {
+                assert!(state.enabled && !state.estopped);
+                assert_eq!(state.battery_voltage, 12.0);
+            }
+            ref other => panic!("unexpected events for a: {other:?}"),
+        }
+        match robot_event(&events, "b", "state")[..] {
+            [DsEvent::RobotState(state)] => assert!(state.estopped && !state.enabled),
+            ref other => panic!("unexpected events for b: {other:?}"),
+        }
+        assert!(robot_event(&events, "a", "battery").is_empty());
+        assert!(matches!(
+            robot_event(&events, "b", "battery")[..],
+            [DsEvent::BatteryWarning { level: BatteryLevel::Critical, .. }]
+        ));
+
+        // Raised events are delivered once
+        assert!(robot_event(&monitor.take_events(), "b", "battery").is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+/// Wait before looking up a hostname again after a failure
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Outcome of a background lookup: the target and its address or an error
+pub type Resolution = (String, Result<SocketAddr, String>);
+
+/// Resolve a target (IP literal or hostname such as
+/// `roborio-1234-frc.local`) to a socket address, preferring IPv4
+pub async fn resolve_target(target: &str, port: u16) -> Result<SocketAddr, String> {
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, port));
+    }
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((target, port))
+        .await
+        .map_err(|e| format!("Could not resolve {target}: {e}"))?
+        .collect();
+    addrs
+        .iter()
+        .find(|a| a.is_ipv4())
+        .or(addrs.first())
+        .copied()
+        .ok_or_else(|| format!("{target} did not resolve to any address"))
+}
+
+/// Caches the UDP destination for the protocol loop. Hostnames are looked up
+/// in the background so the send tick never waits on DNS; until a lookup
+/// succeeds (or after it fails), packets keep going to the previous address.
+#[derive(Debug, Default)]
+pub struct TargetResolver {
+    cached: Option<(String, SocketAddr)>,
+    /// Address packets last went to
+    last_dest: Option<SocketAddr>,
+    /// Hostname being looked up
+    pending: Option<String>,
+    /// Failed hostname and when to try it again
+    failed: Option<(String, Instant)>,
+}
+
+impl TargetResolver {
+    /// Where to send packets for `target`, starting a lookup on `tx` if it
+    /// is a hostname without a cached address
+    pub fn dest(
+        &mut self,
+        target: &str,
+        port: u16,
+        now: Instant,
+        tx: &mpsc::Sender<Resolution>,
+    ) -> Option<SocketAddr> {
+        if let Ok(ip) = target.parse::<IpAddr>() {
+            self.last_dest = Some(SocketAddr::new(ip, port));
+            return self.last_dest;
+        }
+        if let Some((host, addr)) = &self.cached {
+            if host == target {
+                self.last_dest = Some(*addr);
+                return self.last_dest;
+            }
+        }
+        let retry_wait = matches!(&self.failed, Some((host, at)) if host == target && now < *at);
+        if self.pending.as_deref() != Some(target) && !retry_wait {
+            self.pending = Some(target.to_string());
+            let tx = tx.clone();
+            let host = target.to_string();
+            tokio::spawn(async move {
+                let result = resolve_target(&host, port).await;
+                let _ = tx.send((host, result)).await;
+            });
+        }
+        self.last_dest
+    }
+
+    /// Record a finished lookup. Returns the error the first time a
+    /// hostname fails, so the caller warns once rather than every retry.
+    pub fn on_resolved(&mut self, (target, result): Resolution, now: Instant) -> Option<String> {
+        if self.pending.as_deref() == Some(target.as_str()) {
+            self.pending = None;
+        }
+        match result {
+            Ok(addr) => {
+                tracing::info!("Resolved {target} to {addr}");
+                self.failed = None;
+                self.cached = Some((target, addr));
+                None
+            }
+            Err(e) => {
+                let first = !matches!(&self.failed, Some((host, _)) if *host == target);
+                self.failed = Some((target, now + RETRY_DELAY));
+                first.then_some(e)
+            }
+        }
+    }
+
+    /// Look hostnames up again, e.g. after the robot disconnects (its
+    /// address may have changed). The old address is used meanwhile.
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hostnames_resolve_in_the_background() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let now = Instant::now();
+        let mut resolver = TargetResolver::default();
+        let ip: SocketAddr = "10.12.34.2:1110".parse().unwrap();
+        assert_eq!(resolver.dest("10.12.34.2", 1110, now, &tx), Some(ip));
+
+        // Packets keep going to the last address until the lookup finishes
+        assert_eq!(resolver.dest("localhost", 1110, now, &tx), Some(ip));
+        let resolution = rx.recv().await.unwrap();
+        assert_eq!(resolution.1, Ok("127.0.0.1:1110".parse().unwrap()));
+        assert_eq!(resolver.on_resolved(resolution, now), None);
+        let local = resolver.dest("localhost", 1110, now, &tx).unwrap();
+        assert!(local.ip().is_loopback() && local.is_ipv4());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn a_failing_hostname_is_reported_once() {
+        let now = Instant::now();
+        let mut resolver = TargetResolver::default();
+        let failure = || ("robot.invalid".to_string(), Err("not found".to_string()));
+        assert_eq!(resolver.on_resolved(failure(), now), Some("not found".into()));
+        assert_eq!(resolver.on_resolved(failure(), now + RETRY_DELAY), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+use super::connection::{
+    build_outbound_packet, parse_inbound_packet, DsState, RobotFeatureRequests,
+};
+use super::types::{DiagnosticData, RobotState};
+
+/// Control packets exchanged with the loopback robot
+const SELF_TEST_PACKETS: u16 = 3;
+/// Wait for each reply before failing
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+/// Battery voltage the loopback robot reports (12.5V)
+const LOOPBACK_BATTERY: [u8; 2] = [12, 128];
+
+/// Result of `run_self_test`
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// Control packets round-tripped through a loopback robot and parsed
+    pub udp_ok: bool,
+    /// A local TCP console connection could be opened
+    pub tcp_ok: bool,
+    pub gamepad_count: usize,
+    /// One line per check, for display
+    pub details: Vec<String>,
+}
+
+/// Reply a roboRIO would send to `request`: same sequence, echoed control
+/// state, robot code running
+fn loopback_reply(request: &[u8]) -> Option<Vec<u8>> {
+    if request.len() < 6 {
+        return None;
+    }
+    let status = request[3] & 0x87; // E-Stop, enabled, mode
+    Some(vec![
+        request[0],
+        request[1],
+        request[2],
+        status,
+        0x20,
+        LOOPBACK_BATTERY[0],
+        LOOPBACK_BATTERY[1],
+        0x00,
+    ])
+}
+
+/// Answer control packets on `socket` until the DS side is done
+async fn loopback_robot(socket: UdpSocket) {
+    let mut buf = [0u8; 1500];
+    for _ in 0..SELF_TEST_PACKETS {
+        let Ok(Ok((len, from))) =
+            tokio::time::timeout(REPLY_TIMEOUT, socket.recv_from(&mut buf)).await
+        else {
+            return;
+        };
+        if let Some(reply) = loopback_reply(&buf[..len]) {
+            let _ = socket.send_to(&reply, from).await;
+        }
+    }
+}
+
+/// Send control packets to a loopback robot and check each reply parses back
+/// to the state that was sent
+async fn check_udp(details: &mut Vec<String>) -> Result<(), String> {
+    let robot = UdpSocket::bind("127.0.0.1:0").await.map_err(|e| format!("bind robot: {e}"))?;
+    let robot_addr: SocketAddr = robot.local_addr().map_err(|e| e.to_string())?;
+    let ds = UdpSocket::bind("127.0.0.1:0").await.map_err(|e| format!("bind DS: {e}"))?;
+    let responder = tokio::spawn(loopback_robot(robot));
+
+    let ds_state = DsState {
+        enabled: true,
+        ..DsState::default()
+    };
+    let mut buf = [0u8; 1500];
+    let result = async {
+        for seq in 0..SELF_TEST_PACKETS {
+            let pkt = build_outbound_packet(seq, &ds_state, &[], &RobotFeatureRequests::default());
+            ds.send_to(&pkt, robot_addr).await.map_err(|e| format!("send: {e}"))?;
+            let (len, _) = tokio::time::timeout(REPLY_TIMEOUT, ds.recv_from(&mut buf))
+                .await
+                .map_err(|_| format!("no reply to packet {seq}"))?
+                .map_err(|e| format!("receive: {e}"))?;
+
+            let mut robot_state = RobotState::default();
+            let mut diag = DiagnosticData::default();
+            parse_inbound_packet(&buf[..len], &mut robot_state, &mut diag);
+            if robot_state.sequence_number != seq {
+                return Err(format!(
+                    "reply sequence {} does not match {seq}",
+                    robot_state.sequence_number
+                ));
+            }
+            let echoed = robot_state.enabled && robot_state.mode == ds_state.mode;
+            if !echoed || !robot_state.code_running {
+                return Err(format!("reply {seq} parsed to the wrong state"));
+            }
+        }
+        Ok(())
+    }
+    .await;
+    responder.abort();
+
+    if result.is_ok() {
+        details.push(format!(
+            "UDP: {SELF_TEST_PACKETS} control packets round-tripped through a loopback robot"
+        ));
+    }
+    result
+}
+
+/// Open a TCP connection to a local console listener
+async fn check_tcp(details: &mut Vec<String>) -> Result<(), String> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.map_err(|e| format!("bind: {e}"))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let (connected, accepted) = tokio::time::timeout(REPLY_TIMEOUT, async {
+        tokio::join!(TcpStream::connect(addr), listener.accept())
+    })
+    .await
+    .map_err(|_| "connection timed out".to_string())?;
+    connected.map_err(|e| format!("connect: {e}"))?;
+    accepted.map_err(|e| format!("accept: {e}"))?;
+    details.push("TCP: console connection opened on loopback".to_string());
+    Ok(())
+}
+
+/// Check the DS can build, send, receive and parse protocol packets and open
+/// a console connection, all against 127.0.0.1 — no robot needed
+pub async fn run_self_test(gamepad_count: usize) -> SelfTestReport {
+    let mut details = Vec::new();
+    let udp = check_udp(&mut details).await;
+    if let Err(e) = &udp {
+        details.push(format!("UDP failed: {e}"));
+    }
+    let tcp = check_tcp(&mut details).await;
+    if let Err(e) = &tcp {
+        details.push(format!("TCP failed: {e}"));
+    }
+    details.push(format!("Gamepads connected: {gamepad_count}"));
+    SelfTestReport {
+        udp_ok: udp.is_ok(),
+        tcp_ok: tcp.is_ok(),
+        gamepad_count,
+        details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn self_test_passes_on_loopback() {
+        let report = run_self_test(2).await;
+        assert!(report.udp_ok && report.tcp_ok, "{:?}", report.details);
+        assert_eq!(report.gamepad_count, 2);
+        assert_eq!(report.details.len(), 3);
+    }
+
+    #[test]
+    fn loopback_reply_echoes_sequence_and_control_state() {
+        let state = DsState { enabled: true, estop: true, ..DsState::default() };
+        let request = build_outbound_packet(0x0102, &state, &[], &RobotFeatureRequests::default());
+        let reply = loopback_reply(&request).unwrap();
+        assert_eq!(reply[..4], [0x01, 0x02, request[2], request[3] & 0x87]);
+        assert!(loopback_reply(&request[..5]).is_none());
+    }
+}
@@ -0,0 +1,169 @@
+use super::connection::{DsEvent, DsState};
+use super::types::{ConsoleMessage, DiagnosticData, PowerData, RobotState};
+
+/// Prefix on every simulated console line so it can't pass for robot output
+pub const SIM_CONSOLE_PREFIX: &str = "[SIM]";
+
+/// Fully charged simulated battery
+const FULL_BATTERY: f32 = 12.8;
+/// Below this the simulated battery is "swapped" for a full one
+const SWAP_BATTERY: f32 = 11.0;
+/// Drain in volts per second while disabled / enabled
+const IDLE_DRAIN: f32 = 0.001;
+const ENABLED_DRAIN: f32 = 0.01;
+/// Voltage sag under load while enabled
+const ENABLED_SAG: f32 = 0.4;
+/// Seconds between fake console lines and power data updates
+const CONSOLE_PERIOD: f64 = 2.0;
+const POWER_PERIOD: f64 = 5.0;
+/// Enabled seconds per simulated 6V rail fault (a stalled servo)
+const RAIL_FAULT_PERIOD: f64 = 120.0;
+
+const CONSOLE_LINES: [&str; 4] = [
+    "Simulated robot code running",
+    "Drivetrain: odometry updated",
+    "Vision: no targets",
+    "Loop time nominal",
+];
+
+/// Fake robot for UI development and demos without hardware. The protocol
+/// loop steps it instead of talking UDP; its state is marked `simulated`.
+#[derive(Debug)]
+pub struct SimulatedRobot {
+    started: f64,
+    last_step: f64,
+    /// Open-circuit voltage; the reported voltage sags below it when enabled
+    charge: f32,
+    sequence: u16,
+    last_console: f64,
+    last_power: f64,
+    console_index: usize,
+    /// Total time spent enabled, which drives the simulated rail faults
+    enabled_secs: f64,
+}
+
+impl SimulatedRobot {
+    /// `now` is a Unix timestamp in seconds
+    pub fn new(now: f64) -> Self {
+        Self {
+            started: now,
+            last_step: now,
+            charge: FULL_BATTERY,
+            sequence: 0,
+            last_console: now,
+            last_power: f64::NEG_INFINITY,
+            console_index: 0,
+            enabled_secs: 0.0,
+        }
+    }
+
+    /// Advance to `now`, echoing the DS control state into `robot_state` and
+    /// wiggling the diagnostics. Returns console and power events that fell
+    /// due.
+    pub fn step(
+        &mut self,
+        ds: &DsState,
+        robot_state: &mut RobotState,
+        diag: &mut DiagnosticData,
+        now: f64,
+    ) -> Vec<DsEvent> {
+        let dt = (now - self.last_step).max(0.0) as f32;
+        self.last_step = now;
+        let t = now - self.started;
+        let enabled = ds.enabled && !ds.estop;
+        if enabled {
+            self.enabled_secs += f64::from(dt);
+        }
+
+        self.charge -= dt * if enabled { ENABLED_DRAIN } else { IDLE_DRAIN };
+        if self.charge < SWAP_BATTERY {
+            self.charge = FULL_BATTERY;
+        }
+        let voltage = self.charge - if enabled { ENABLED_SAG } else { 0.0 };
+        self.sequence = self.sequence.wrapping_add(1);
+
+        *robot_state = RobotState {
+            connected: true,
+            code_running: true,
+            enabled,
+            estopped: ds.estop,
+            mode: ds.mode,
+            battery_voltage: voltage,
+            sequence_number: self.sequence,
+            simulated: true,
+            ..RobotState::default()
+        };
+
+        // Fractions (0..1), as parsed from a real robot
+        diag.cpu_usage = 0.35 + 0.10 * (t * 0.7).sin() as f32 + if enabled { 0.15 } else { 0.0 };
+        diag.can_utilization = 0.20 + 0.05 * (t * 0.3).sin() as f32;
+        diag.ram_free = 120_000_000;
+        diag.disk_free = 400_000_000;
+        diag.record_voltage(now, voltage);
+
+        let mut events = Vec::new();
+        if now - self.last_console >= CONSOLE_PERIOD {
+            self.last_console = now;
+            let line = CONSOLE_LINES[self.console_index % CONSOLE_LINES.len()];
+            self.console_index += 1;
+            events.push(DsEvent::Console(ConsoleMessage {
+                timestamp: now,
+                message: format!("{SIM_CONSOLE_PREFIX} {line}"),
+                is_error: false,
+                is_warning: false,
+                sequence: self.console_index as u16,
+                repeat_count: 1,
+                error: None,
+            }));
+        }
+        if now - self.last_power >= POWER_PERIOD {
+            self.last_power = now;
+            events.push(DsEvent::PowerData(self.power_data()));
+        }
+        events
+    }
+
+    /// Fault counters of a healthy robot: the one comms disable from before
+    /// the DS connected, and an occasional 6V rail fault while enabled
+    fn power_data(&self) -> PowerData {
+        PowerData {
+            disable_count_comms: 1,
+            rail_faults_6v: (self.enabled_secs / RAIL_FAULT_PERIOD) as u16,
+            ..PowerData::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_are_fractions() {
+        let mut sim = SimulatedRobot::new(0.0);
+        let ds = DsState { enabled: true, ..DsState::default() };
+        let (mut state, mut diag) = (RobotState::default(), DiagnosticData::default());
+        for i in 1..=100 {
+            sim.step(&ds, &mut state, &mut diag, f64::from(i) * 0.1);
+            assert!((0.0..=1.0).contains(&diag.cpu_usage), "cpu {}", diag.cpu_usage);
+            assert!((0.0..=1.0).contains(&diag.can_utilization), "can {}", diag.can_utilization);
+        }
+    }
+
+    #[test]
+    fn simulated_robot_reports_connected_with_a_charged_battery() {
+        let mut sim = SimulatedRobot::new(0.0);
+        let (mut state, mut diag) = (RobotState::default(), DiagnosticData::default());
+        let mut ds = DsState::default();
+        let events = sim.step(&ds, &mut state, &mut diag, 0.1);
+        assert!(state.connected && state.code_running && state.simulated);
+        assert!(!state.enabled);
+        assert!((SWAP_BATTERY..=FULL_BATTERY).contains(&state.battery_voltage));
+        assert!(events.iter().any(|e| matches!(e, DsEvent::PowerData(_))));
+
+        ds.enabled = true;
+        sim.step(&ds, &mut state, &mut diag, 0.2);
+        assert!(state.enabled);
+        assert!(state.battery_voltage > 0.0 && state.battery_voltage < FULL_BATTERY - 0.3);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Seconds of telemetry kept for brownout snapshots
+pub const SNAPSHOT_WINDOW: f64 = 10.0;
+
+/// One telemetry reading, taken for every status packet received
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetrySample {
+    /// Unix timestamp (seconds)
+    pub timestamp: f64,
+    pub battery_voltage: f32,
+    pub brownout: bool,
+    pub enabled: bool,
+    pub cpu_usage: f32,
+    pub can_utilization: f32,
+}
+
+/// Rolling window of recent telemetry
+#[derive(Debug, Default)]
+pub struct TelemetryHistory {
+    samples: VecDeque<TelemetrySample>,
+}
+
+impl TelemetryHistory {
+    pub fn push(&mut self, sample: TelemetrySample) {
+        let newest = sample.timestamp;
+        self.samples.push_back(sample);
+        while self
+            .samples
+            .front()
+            .is_some_and(|s| newest - s.timestamp > SNAPSHOT_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn samples(&self) -> Vec<TelemetrySample> {
+        self.samples.iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BrownoutSnapshot<'a> {
+    /// Unix timestamp of the brownout onset
+    timestamp: f64,
+    /// Session brownout count including this one
+    brownout_count: u32,
+    samples: &'a [TelemetrySample],
+}
+
+/// `brownout-{unix millis}.json`
+pub fn snapshot_file_name(timestamp: f64) -> String {
+    format!("brownout-{}.json", (timestamp * 1000.0) as u64)
+}
+
+/// Write the telemetry leading up to a brownout into `dir`. Blocking.
+pub fn write_snapshot(
+    dir: &Path,
+    timestamp: f64,
+    brownout_count: u32,
+    samples: &[TelemetrySample],
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(snapshot_file_name(timestamp));
+    let snapshot = BrownoutSnapshot { timestamp, brownout_count, samples };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::connection::parse_inbound_packet;
+    use crate::protocol::types::{DiagnosticData, RobotState};
+
+    #[test]
+    fn brownout_edge_writes_the_preceding_history() {
+        let mut state = RobotState::default();
+        let mut diag = DiagnosticData::default();
+        let mut history = TelemetryHistory::default();
+        let dir = std::env::temp_dir().join(format!("ds-snapshot-test-{}", std::process::id()));
+
+        let mut written = Vec::new();
+        // 15 packets a second apart; the brownout bit comes on at the last one
+        for i in 0..15u8 {
+            let status = if i == 14 { 0x10 } else { 0 };
+            let before = diag.brownout_count;
+            let packet = [0, i, 1, status, 0x20, 12 - i / 2, 0, 0];
+            parse_inbound_packet(&packet, &mut state, &mut diag);
+            let timestamp = 1000.0 + f64::from(i);
+            history.push(TelemetrySample {
+                timestamp,
+                battery_voltage: state.battery_voltage,
+                brownout: state.brownout,
+                enabled: state.enabled,
+                cpu_usage: diag.cpu_usage,
+                can_utilization: diag.can_utilization,
+            });
+            if diag.brownout_count > before {
+                let samples = history.samples();
+                written.push(write_snapshot(&dir, timestamp, diag.brownout_count, &samples));
+            }
+        }
+
+        let [Ok(path)] = &written[..] else {
+            panic!("expected one snapshot, got {written:?}");
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(path.file_name().unwrap(), "brownout-1014000.json");
+        assert_eq!(json["brownout_count"], 1);
+        let samples = json["samples"].as_array().unwrap();
+        // Only the last SNAPSHOT_WINDOW seconds are kept, ending at the onset
+        assert_eq!(samples.len(), 11);
+        assert_eq!(samples[0]["timestamp"], 1004.0);
+        assert_eq!(samples[10]["brownout"], true);
+        assert_eq!(samples[10]["battery_voltage"], 5.0);
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::Mode;
+
+/// Maximum number of entries kept in the session timeline
+pub const TIMELINE_CAPACITY: usize = 2000;
+
+/// Why the protocol loop considers the robot disconnected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisconnectReason {
+    /// The radio did not answer its TCP probe
+    RadioUnreachable,
+    /// The radio answers but the roboRIO does not
+    RobotUnreachable,
+    /// The roboRIO is reachable but stopped sending UDP status packets
+    NoResponse,
+}
+
+impl DisconnectReason {
+    /// Pick the most specific reason from the cached reachability checks
+    pub fn classify(radio_reachable: bool, robot_reachable: bool) -> Self {
+        if robot_reachable {
+            DisconnectReason::NoResponse
+        } else if radio_reachable {
+            DisconnectReason::RobotUnreachable
+        } else {
+            DisconnectReason::RadioUnreachable
+        }
+    }
+}
+
+/// A single DS-side transition worth reviewing after a match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum TimelineEvent {
+    Enabled,
+    Disabled,
+    EStop,
+    ModeChanged { mode: Mode },
+    CommsConnected { target_ip: String },
+    CommsLost { target_ip: String, reason: DisconnectReason },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Unix timestamp (seconds)
+    pub timestamp: f64,
+    pub event: TimelineEvent,
+}
+
+/// Bounded, in-order record of control and comms transitions for this session
+#[derive(Debug, Default)]
+pub struct SessionTimeline {
+    entries: VecDeque<TimelineEntry>,
+}
+
+impl SessionTimeline {
+    pub fn record(&mut self, timestamp: f64, event: TimelineEvent) {
+        if self.entries.len() >= TIMELINE_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TimelineEntry { timestamp, event });
+    }
+
+    pub fn entries(&self) -> Vec<TimelineEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_then_disconnect_is_recorded_in_order() {
+        let mut timeline = SessionTimeline::default();
+        let target_ip = "10.12.34.2".to_string();
+        timeline.record(100.0, TimelineEvent::CommsConnected { target_ip: target_ip.clone() });
+        let reason = DisconnectReason::classify(true, false);
+        timeline.record(160.5, TimelineEvent::CommsLost { target_ip: target_ip.clone(), reason });
+
+        assert_eq!(
+            timeline.entries(),
+            vec![
+                TimelineEntry {
+                    timestamp: 100.0,
+                    event: TimelineEvent::CommsConnected { target_ip: target_ip.clone() },
+                },
+                TimelineEntry {
+                    timestamp: 160.5,
+                    event: TimelineEvent::CommsLost {
+                        target_ip,
+                        reason: DisconnectReason::RobotUnreachable,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn disconnect_reason_picks_the_most_specific_cause() {
+        assert_eq!(DisconnectReason::classify(false, false), DisconnectReason::RadioUnreachable);
+        assert_eq!(DisconnectReason::classify(true, false), DisconnectReason::RobotUnreachable);
+        assert_eq!(DisconnectReason::classify(true, true), DisconnectReason::NoResponse);
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_at_capacity() {
+        let mut timeline = SessionTimeline::default();
+        for i in 0..=TIMELINE_CAPACITY {
+            timeline.record(i as f64, TimelineEvent::Enabled);
+        }
+        let entries = timeline.entries();
+        assert_eq!(entries.len(), TIMELINE_CAPACITY);
+        assert_eq!(entries[0].timestamp, 1.0);
+    }
+}
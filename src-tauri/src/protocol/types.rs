@@ -1,5 +1,9 @@
+use std::collections::VecDeque;
+
 use serde::{Deserialize, Serialize};
 
+use super::link::LinkQuality;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Mode {
     Teleoperated,
@@ -36,6 +40,19 @@ pub enum Alliance {
 }
 
 impl Alliance {
+    /// Inverse of `to_byte`
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Alliance::Red1),
+            1 => Some(Alliance::Red2),
+            2 => Some(Alliance::Red3),
+            3 => Some(Alliance::Blue1),
+            4 => Some(Alliance::Blue2),
+            5 => Some(Alliance::Blue3),
+            _ => None,
+        }
+    }
+
     pub fn to_byte(self) -> u8 {
         match self {
             Alliance::Red1 => 0,
@@ -46,6 +63,50 @@ impl Alliance {
             Alliance::Blue3 => 5,
         }
     }
+
+    /// `"red"` or `"blue"`
+    pub fn color(&self) -> &'static str {
+        match self {
+            Alliance::Red1 | Alliance::Red2 | Alliance::Red3 => "red",
+            Alliance::Blue1 | Alliance::Blue2 | Alliance::Blue3 => "blue",
+        }
+    }
+
+    /// Driver station position within the alliance, 1-3
+    pub fn position(&self) -> u8 {
+        match self {
+            Alliance::Red1 | Alliance::Blue1 => 1,
+            Alliance::Red2 | Alliance::Blue2 => 2,
+            Alliance::Red3 | Alliance::Blue3 => 3,
+        }
+    }
+}
+
+/// Alliance station sent to the robot, pre-split for theming the UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllianceInfo {
+    pub station: Alliance,
+    pub color: String,
+    pub position: u8,
+    /// A temporary override is active instead of the saved alliance
+    pub overridden: bool,
+}
+
+impl AllianceInfo {
+    pub fn new(station: Alliance, overridden: bool) -> Self {
+        Self {
+            station,
+            color: station.color().to_string(),
+            position: station.position(),
+            overridden,
+        }
+    }
+}
+
+impl Default for AllianceInfo {
+    fn default() -> Self {
+        Self::new(Alliance::default(), false)
+    }
 }
 
 impl Default for Alliance {
@@ -65,6 +126,13 @@ pub struct RobotState {
     pub brownout: bool,
     pub fms_connected: bool,
     pub sequence_number: u16,
+    /// Match time remaining in seconds, None when no timing source is active.
+    /// The robot's status packet carries no match time (it flows from the
+    /// FMS to the robot), so off FMS this comes from the DS practice match
+    /// timer while enabled.
+    pub match_time: Option<f32>,
+    /// Produced by the built-in robot simulator, not a real robot
+    pub simulated: bool,
 }
 
 impl Default for RobotState {
@@ -79,6 +147,8 @@ impl Default for RobotState {
             brownout: false,
             fms_connected: false,
             sequence_number: 0,
+            match_time: None,
+            simulated: false,
         }
     }
 }
@@ -87,10 +157,12 @@ impl Default for RobotState {
 pub struct JoystickState {
     pub axes: Vec<f32>,
     pub buttons: Vec<bool>,
+    /// POV angles in WPILib index order (POV 0 first), -1 when centered
     pub povs: Vec<i16>,
 }
 
 impl Default for JoystickState {
+    /// A standard gamepad: 6 axes, 16 buttons and one POV
     fn default() -> Self {
         Self {
             axes: vec![0.0; 6],
@@ -100,6 +172,88 @@ impl Default for JoystickState {
     }
 }
 
+impl JoystickState {
+    /// Number of POVs sent to robot code for this joystick
+    pub fn pov_count(&self) -> usize {
+        self.povs.len()
+    }
+
+    /// Same layout with every input at rest (axes 0, buttons released, POVs
+    /// centered)
+    pub fn at_rest(&self) -> Self {
+        Self {
+            axes: vec![0.0; self.axes.len()],
+            buttons: vec![false; self.buttons.len()],
+            povs: vec![-1; self.povs.len()],
+        }
+    }
+}
+
+/// Axis/button/POV counts reported to robot code for one joystick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoystickLayout {
+    pub axes: u8,
+    pub buttons: u8,
+    pub povs: u8,
+}
+
+/// WPILib limits per joystick
+pub const MAX_JOYSTICK_AXES: u8 = 12;
+pub const MAX_JOYSTICK_POVS: u8 = 4;
+
+impl Default for JoystickLayout {
+    /// Matches `JoystickState::default`
+    fn default() -> Self {
+        Self { axes: 6, buttons: 16, povs: 1 }
+    }
+}
+
+impl JoystickLayout {
+    /// Resize `state` to this layout, keeping values that still fit
+    pub fn apply(&self, state: &mut JoystickState) {
+        state.axes.resize(self.axes as usize, 0.0);
+        state.buttons.resize(self.buttons as usize, false);
+        state.povs.resize(self.povs as usize, -1);
+    }
+}
+
+/// WPILib `HIDType` values reported in joystick descriptors
+pub const HID_TYPE_UNKNOWN: u8 = 0xFF;
+pub const HID_TYPE_XINPUT_GAMEPAD: u8 = 1;
+pub const HID_TYPE_HID_GAMEPAD: u8 = 21;
+
+/// Describes one joystick slot to robot code (`getJoystickIsXbox`,
+/// `getJoystickName`, `getJoystickType`, axis/button/POV counts)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoystickDescriptor {
+    pub name: String,
+    pub is_xbox: bool,
+    pub hid_type: u8,
+    pub layout: JoystickLayout,
+}
+
+impl Default for JoystickDescriptor {
+    /// An empty slot: unknown type, no inputs
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            is_xbox: false,
+            hid_type: HID_TYPE_UNKNOWN,
+            layout: JoystickLayout { axes: 0, buttons: 0, povs: 0 },
+        }
+    }
+}
+
+/// Latest robot state and diagnostics, for on-demand reads
+#[derive(Debug, Clone, Default)]
+pub struct RobotSnapshot {
+    pub robot_state: RobotState,
+    pub diagnostics: DiagnosticData,
+    pub alliance: AllianceInfo,
+    /// Latest 2Hz link summary; None before the first one
+    pub link: Option<LinkQuality>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiagnosticData {
     pub cpu_usage: f32,
@@ -112,6 +266,114 @@ pub struct DiagnosticData {
     pub can_tx_full: u32,
     pub can_rx_error: u32,
     pub can_tx_error: u32,
+    /// Number of brownout onsets (false→true transitions) this session
+    pub brownout_count: u32,
+    /// Unix timestamps (seconds) of the most recent brownout onsets, oldest first
+    pub brownout_history: VecDeque<f64>,
+    /// Lowest battery voltage reported since the robot connected
+    pub min_voltage: Option<f32>,
+    /// Short-term battery voltage direction
+    pub battery_trend: BatteryTrend,
+    /// Recent (timestamp, voltage) readings used for `battery_trend`
+    #[serde(skip)]
+    voltage_samples: VecDeque<(f64, f32)>,
+}
+
+/// Window of voltage readings used to classify the battery trend (seconds)
+pub const BATTERY_TREND_WINDOW: f64 = 5.0;
+/// Slopes smaller than this (volts per second) count as stable
+pub const BATTERY_TREND_DEADBAND: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BatteryTrend {
+    Rising,
+    Falling,
+    #[default]
+    Stable,
+}
+
+impl BatteryTrend {
+    /// Classify from the least-squares slope of (timestamp, voltage) samples
+    pub fn classify<'a>(samples: impl IntoIterator<Item = &'a (f64, f32)>) -> Self {
+        let points: Vec<(f64, f64)> = samples.into_iter().map(|&(t, v)| (t, v as f64)).collect();
+        if points.len() < 2 {
+            return BatteryTrend::Stable;
+        }
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_v = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (t, v) in &points {
+            cov += (t - mean_t) * (v - mean_v);
+            var += (t - mean_t) * (t - mean_t);
+        }
+        if var <= 0.0 {
+            return BatteryTrend::Stable;
+        }
+        let slope = cov / var;
+        if slope > BATTERY_TREND_DEADBAND {
+            BatteryTrend::Rising
+        } else if slope < -BATTERY_TREND_DEADBAND {
+            BatteryTrend::Falling
+        } else {
+            BatteryTrend::Stable
+        }
+    }
+}
+
+/// Number of brownout onsets kept in `DiagnosticData::brownout_history`
+pub const BROWNOUT_HISTORY_LEN: usize = 16;
+
+impl DiagnosticData {
+    /// Count a brownout onset and push it into the bounded history
+    pub fn record_brownout(&mut self, timestamp: f64) {
+        self.brownout_count += 1;
+        if self.brownout_history.len() >= BROWNOUT_HISTORY_LEN {
+            self.brownout_history.pop_front();
+        }
+        self.brownout_history.push_back(timestamp);
+    }
+
+    /// Track the minimum battery voltage and the recent trend, ignoring
+    /// zero (no reading)
+    pub fn record_voltage(&mut self, timestamp: f64, voltage: f32) {
+        if voltage <= 0.0 {
+            return;
+        }
+        if self.min_voltage.is_none_or(|min| voltage < min) {
+            self.min_voltage = Some(voltage);
+        }
+
+        self.voltage_samples.push_back((timestamp, voltage));
+        while self
+            .voltage_samples
+            .front()
+            .is_some_and(|&(t, _)| timestamp - t > BATTERY_TREND_WINDOW)
+        {
+            self.voltage_samples.pop_front();
+        }
+        self.battery_trend = BatteryTrend::classify(&self.voltage_samples);
+    }
+
+    /// Forget per-connection voltage tracking
+    pub fn reset_voltage(&mut self) {
+        self.min_voltage = None;
+        self.voltage_samples.clear();
+        self.battery_trend = BatteryTrend::Stable;
+    }
+
+    /// Zero the counters that accumulate over a session (CAN errors,
+    /// brownouts, minimum voltage). Live values such as CPU and CAN
+    /// utilization are kept; the next packet refreshes everything it carries.
+    pub fn reset_counters(&mut self) {
+        self.can_bus_off = 0;
+        self.can_tx_full = 0;
+        self.can_rx_error = 0;
+        self.can_tx_error = 0;
+        self.brownout_count = 0;
+        self.brownout_history.clear();
+        self.reset_voltage();
+    }
 }
 
 impl Default for DiagnosticData {
@@ -125,6 +387,11 @@ impl Default for DiagnosticData {
             can_tx_full: 0,
             can_rx_error: 0,
             can_tx_error: 0,
+            brownout_count: 0,
+            brownout_history: VecDeque::new(),
+            min_voltage: None,
+            battery_trend: BatteryTrend::Stable,
+            voltage_samples: VecDeque::new(),
         }
     }
 }
@@ -150,29 +417,68 @@ impl Default for PowerData {
     }
 }
 
+/// Coarse robot reachability, combining the TCP probe with UDP comms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RobotStatus {
+    /// Nothing answers at the robot address
+    Unreachable,
+    /// roboRIO is powered on (TCP probe succeeds) but no UDP comms yet
+    PoweredOn,
+    /// UDP comms established but robot code is not running
+    NoCode,
+    /// UDP comms established and robot code is running
+    CodeRunning,
+}
+
+impl RobotStatus {
+    /// Map the probe result and protocol-loop state to a status
+    pub fn from_probe(probe_ok: bool, connected: bool, code_running: bool) -> Self {
+        match (connected, code_running, probe_ok) {
+            (true, true, _) => RobotStatus::CodeRunning,
+            (true, false, _) => RobotStatus::NoCode,
+            (false, _, true) => RobotStatus::PoweredOn,
+            (false, _, false) => RobotStatus::Unreachable,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionStatus {
     pub enet_link: bool,
+    /// Some non-loopback interface is up, possibly IPv6-only
+    pub any_link: bool,
     pub enet_ip: Option<String>,
     pub robot_radio: bool,
     pub robot: bool,
     pub robot_ip: Option<String>,
+    /// Address the DS is currently sending to (USB, team, or explicit)
+    pub target_ip: String,
+    /// Simulation mode is on (targeting localhost regardless of team)
+    pub simulation: bool,
+    pub robot_status: RobotStatus,
     pub fms: bool,
     pub wifi: bool,
     pub usb: bool,
+    /// The robot's NetworkTables (NT4) port accepts connections
+    pub nt_connected: bool,
 }
 
 impl Default for ConnectionStatus {
     fn default() -> Self {
         Self {
             enet_link: false,
+            any_link: false,
             enet_ip: None,
             robot_radio: false,
             robot: false,
             robot_ip: None,
+            target_ip: String::new(),
+            simulation: false,
+            robot_status: RobotStatus::Unreachable,
             fms: false,
             wifi: false,
             usb: false,
+            nt_connected: false,
         }
     }
 }
@@ -184,6 +490,21 @@ pub struct ConsoleMessage {
     pub is_error: bool,
     pub is_warning: bool,
     pub sequence: u16,
+    /// Number of identical consecutive messages collapsed into this one
+    pub repeat_count: u32,
+    /// Structured fields of a robot error/warning report (tag 0x0B); None
+    /// for standard output
+    #[serde(default)]
+    pub error: Option<ConsoleError>,
+}
+
+/// The parts of a robot error/warning report that `message` combines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsoleError {
+    pub error_code: i32,
+    pub details: String,
+    pub location: String,
+    pub call_stack: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -192,3 +513,251 @@ pub struct VersionInfo {
     pub wpilib_version: String,
     pub rio_version: String,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// One-shot request sent to the roboRIO in the control packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RobotRequest {
+    RebootRio,
+    RestartCode,
+}
+
+/// Stable taxonomy of alert conditions so the frontend can map each kind to
+/// a specific sound/visual deterministically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertKind {
+    Brownout,
+    CommsLost,
+    LowBattery,
+    EStop,
+    CodeStopped,
+    StuckEnabled,
+    /// Frontend heartbeat stopped while enabled; the DS disabled the robot
+    Watchdog,
+    /// Driver station PC CPU temperature is above the configured threshold
+    PcOverheat,
+    /// The target hostname could not be resolved; the previous address is kept
+    TargetUnresolved,
+}
+
+impl AlertKind {
+    pub fn severity(self) -> AlertSeverity {
+        match self {
+            AlertKind::EStop | AlertKind::StuckEnabled => AlertSeverity::Critical,
+            AlertKind::Brownout
+            | AlertKind::CommsLost
+            | AlertKind::LowBattery
+            | AlertKind::CodeStopped
+            | AlertKind::Watchdog
+            | AlertKind::PcOverheat
+            | AlertKind::TargetUnresolved => AlertSeverity::Warning,
+        }
+    }
+}
+
+/// A condition the driver should be told about immediately
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: AlertSeverity,
+    pub message: String,
+    /// Unix timestamp (seconds)
+    pub timestamp: f64,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, message: impl Into<String>, timestamp: f64) -> Self {
+        Self {
+            kind,
+            severity: kind.severity(),
+            message: message.into(),
+            timestamp,
+        }
+    }
+}
+
+/// Radio/comms status event reported by the roboRIO on TCP tag 0x00
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RadioEvent {
+    pub message: String,
+    pub severity: AlertSeverity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PacketDirection {
+    Outbound,
+    Inbound,
+}
+
+/// One UDP tag found in a packet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedTag {
+    pub id: u8,
+    /// Tag payload length, excluding the size and id bytes
+    pub len: usize,
+}
+
+/// Raw UDP packet captured for protocol debugging
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacketCapture {
+    pub direction: PacketDirection,
+    /// Space-separated uppercase hex
+    pub bytes: String,
+    /// Packet sequence number (bytes 0-1)
+    pub seq: u16,
+    /// Tags found in an inbound packet (empty for outbound)
+    pub tags: Vec<CapturedTag>,
+}
+
+/// Network-layer snapshot for troubleshooting: UDP sockets owned by the
+/// protocol loop and the TCP console connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SocketStatus {
+    pub udp_send_bound: bool,
+    pub udp_send_addr: Option<String>,
+    pub udp_recv_bound: bool,
+    pub udp_recv_addr: Option<String>,
+    pub console_connected: bool,
+    pub console_remote_addr: Option<String>,
+    /// Console messages dropped because downstream consumers fell behind
+    pub console_dropped: u64,
+    /// Comm version byte currently sent in outbound packets
+    pub comm_version: u8,
+    /// Time without a robot reply before it is marked disconnected
+    pub disconnect_timeout_ms: u64,
+}
+
+impl SocketStatus {
+    /// Record the result of binding a UDP socket
+    pub fn set_udp(&mut self, recv: bool, local_addr: Option<String>) {
+        let bound = local_addr.is_some();
+        if recv {
+            self.udp_recv_bound = bound;
+            self.udp_recv_addr = local_addr;
+        } else {
+            self.udp_send_bound = bound;
+            self.udp_send_addr = local_addr;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_result_maps_to_robot_status() {
+        assert_eq!(RobotStatus::from_probe(false, false, false), RobotStatus::Unreachable);
+        assert_eq!(RobotStatus::from_probe(true, false, false), RobotStatus::PoweredOn);
+        // UDP comms win over the probe, which is skipped while connected
+        assert_eq!(RobotStatus::from_probe(false, true, false), RobotStatus::NoCode);
+        assert_eq!(RobotStatus::from_probe(true, true, false), RobotStatus::NoCode);
+        assert_eq!(RobotStatus::from_probe(false, true, true), RobotStatus::CodeRunning);
+    }
+
+    #[test]
+    fn alerts_take_their_severity_from_the_kind() {
+        let critical = [AlertKind::EStop, AlertKind::StuckEnabled];
+        let warnings = [
+            AlertKind::Brownout,
+            AlertKind::CommsLost,
+            AlertKind::LowBattery,
+            AlertKind::CodeStopped,
+            AlertKind::Watchdog,
+            AlertKind::PcOverheat,
+            AlertKind::TargetUnresolved,
+        ];
+        for kind in critical {
+            let alert = Alert::new(kind, "test", 1.0);
+            assert_eq!((alert.kind, alert.severity), (kind, AlertSeverity::Critical));
+        }
+        for kind in warnings {
+            let alert = Alert::new(kind, "test", 1.0);
+            assert_eq!((alert.kind, alert.severity), (kind, AlertSeverity::Warning));
+        }
+    }
+
+    #[test]
+    fn socket_status_reflects_bound_and_failed_sockets() {
+        let mut status = SocketStatus::default();
+        status.set_udp(true, Some("0.0.0.0:1150".into()));
+        status.set_udp(false, None);
+        assert!(status.udp_recv_bound);
+        assert_eq!(status.udp_recv_addr.as_deref(), Some("0.0.0.0:1150"));
+        assert!(!status.udp_send_bound);
+        assert_eq!(status.udp_send_addr, None);
+
+        // A later failed rebind clears the earlier address
+        status.set_udp(true, None);
+        assert!(!status.udp_recv_bound);
+        assert_eq!(status.udp_recv_addr, None);
+    }
+
+    /// Diagnostics after a 4s voltage series at 10 samples per second
+    fn trend_of(volts_per_second: f32) -> BatteryTrend {
+        let mut diag = DiagnosticData::default();
+        for i in 0..40 {
+            let t = i as f32 / 10.0;
+            diag.record_voltage(1000.0 + t as f64, 12.0 + volts_per_second * t);
+        }
+        diag.battery_trend
+    }
+
+    #[test]
+    fn battery_trend_follows_the_voltage_slope() {
+        assert_eq!(trend_of(0.1), BatteryTrend::Rising);
+        assert_eq!(trend_of(-0.1), BatteryTrend::Falling);
+        assert_eq!(trend_of(0.0), BatteryTrend::Stable);
+        // Inside the deadband
+        assert_eq!(trend_of(0.01), BatteryTrend::Stable);
+    }
+
+    #[test]
+    fn alliance_color_and_position_for_every_station() {
+        let stations = [
+            (Alliance::Red1, "red", 1),
+            (Alliance::Red2, "red", 2),
+            (Alliance::Red3, "red", 3),
+            (Alliance::Blue1, "blue", 1),
+            (Alliance::Blue2, "blue", 2),
+            (Alliance::Blue3, "blue", 3),
+        ];
+        for (station, color, position) in stations {
+            let info = AllianceInfo::new(station, false);
+            assert_eq!((info.color.as_str(), info.position), (color, position), "{station:?}");
+            assert_eq!(Alliance::from_byte(station.to_byte()), Some(station));
+        }
+        assert_eq!(Alliance::from_byte(6), None);
+    }
+
+    #[test]
+    fn reset_counters_keeps_live_values() {
+        let mut diag = DiagnosticData {
+            cpu_usage: 0.4,
+            can_utilization: 0.3,
+            can_bus_off: 2,
+            can_tx_full: 5,
+            can_rx_error: 9,
+            can_tx_error: 1,
+            ..DiagnosticData::default()
+        };
+        diag.record_brownout(10.0);
+        diag.record_voltage(10.0, 6.5);
+        diag.record_voltage(11.0, 12.0);
+
+        diag.reset_counters();
+        let can = [diag.can_bus_off, diag.can_tx_full, diag.can_rx_error, diag.can_tx_error];
+        assert_eq!(can, [0; 4]);
+        assert_eq!(diag.brownout_count, 0);
+        assert!(diag.brownout_history.is_empty());
+        assert_eq!(diag.min_voltage, None);
+        assert_eq!(diag.battery_trend, BatteryTrend::Stable);
+        assert_eq!((diag.cpu_usage, diag.can_utilization), (0.4, 0.3));
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::fs;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::log_writer::enforce_retention;
+
+/// A recording stops growing at this size (100 MB); later events are dropped
+pub const MAX_RECORDING_BYTES: u64 = 100 * 1024 * 1024;
+/// Number of session recordings kept in the log directory
+pub const MAX_RECORDINGS: usize = 10;
+
+/// Frontend events written to the session recording. Gamepad and PC system
+/// info are local to this machine and not worth replaying.
+pub const RECORDED_EVENTS: &[&str] = &[
+    "robot-state",
+    "robot-event",
+    "active-robot-changed",
+    "diagnostics",
+    "console-message",
+    "connection-status",
+    "power-data",
+    "version-info",
+    "robot-code-started",
+    "robot-code-stopped",
+    "alert",
+    "battery-warning",
+    "alliance-changed",
+    "estop-confirmed",
+    "estop-unconfirmed",
+    "link-quality",
+    "joysticks-enabled",
+    "robot-request",
+    "radio-event",
+];
+
+/// One line of a session recording (NDJSON): a frontend event as it was emitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayRecord {
+    /// Unix timestamp (seconds) when the event was emitted
+    pub timestamp: f64,
+    /// Tauri event name, e.g. "console-message"
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+/// File name for a session recording: `session-{secs}.ndjson`
+pub fn recording_file_name(secs: u64) -> String {
+    format!("session-{secs}.ndjson")
+}
+
+/// Read a session recording. Blank lines are skipped; a malformed line is an
+/// error naming its line number.
+pub fn parse_recording<R: BufRead>(reader: R) -> Result<Vec<ReplayRecord>, String> {
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| format!("Line {}: {e}", i + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Delay of each record from the start of the replay. Out-of-order
+/// timestamps are clamped so the replay never goes back in time.
+pub fn replay_offsets(records: &[ReplayRecord]) -> Vec<Duration> {
+    let Some(first) = records.first() else {
+        return Vec::new();
+    };
+    let mut latest = 0.0_f64;
+    records
+        .iter()
+        .map(|r| {
+            latest = latest.max(r.timestamp - first.timestamp);
+            Duration::from_secs_f64(latest)
+        })
+        .collect()
+}
+
+async fn open_recording(log_dir: &std::path::Path) -> Option<BufWriter<fs::File>> {
+    if let Err(e) = fs::create_dir_all(log_dir).await {
+        tracing::error!("Failed to create log directory: {e}");
+        return None;
+    }
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = log_dir.join(recording_file_name(secs));
+    match fs::OpenOptions::new().create(true).append(true).open(&path).await {
+        Ok(f) => {
+            tracing::info!("Recording session to {}", path.display());
+            enforce_retention(log_dir, "session-", ".ndjson", MAX_RECORDINGS).await;
+            Some(BufWriter::new(f))
+        }
+        Err(e) => {
+            tracing::error!("Failed to open session recording {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Appends recorded events to an NDJSON file in `log_dir` until the channel
+/// closes. The file is created with the first event, so nothing is written
+/// unless recording is turned on, and stops growing at `MAX_RECORDING_BYTES`.
+pub async fn session_recorder(mut record_rx: mpsc::Receiver<ReplayRecord>, log_dir: PathBuf) {
+    let mut writer: Option<BufWriter<fs::File>> = None;
+    let mut written: u64 = 0;
+    while let Some(record) = record_rx.recv().await {
+        if written >= MAX_RECORDING_BYTES {
+            continue;
+        }
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            continue;
+        };
+        line.push('\n');
+        if writer.is_none() {
+            writer = open_recording(&log_dir).await;
+        }
+        let Some(w) = writer.as_mut() else {
+            return;
+        };
+        if let Err(e) = w.write_all(line.as_bytes()).await {
+            tracing::warn!("Failed to write session recording: {e}");
+            break;
+        }
+        written += line.len() as u64;
+        if written >= MAX_RECORDING_BYTES {
+            tracing::warn!("Session recording reached {MAX_RECORDING_BYTES} bytes, stopping");
+        }
+        // Flush in batches: only once the queue is drained
+        if record_rx.is_empty() || written >= MAX_RECORDING_BYTES {
+            let _ = w.flush().await;
+        }
+    }
+    if let Some(w) = writer.as_mut() {
+        let _ = w.flush().await;
+    }
+}
+
+/// Re-emits recorded events to the frontend at their original relative timing.
+/// `replaying` is cleared when the replay finishes or is stopped, which lets
+/// live events through to the frontend again.
+pub async fn replay_session(
+    app: AppHandle,
+    records: Vec<ReplayRecord>,
+    mut stop_rx: oneshot::Receiver<()>,
+    replaying: Arc<AtomicBool>,
+) {
+    let start = tokio::time::Instant::now();
+    let offsets = replay_offsets(&records);
+    let mut stopped = false;
+
+    for (record, offset) in records.into_iter().zip(offsets) {
+        tokio::select! {
+            _ = tokio::time::sleep_until(start + offset) => {
+                let _ = app.emit(&record.event, record.payload);
+            }
+            _ = &mut stop_rx => {
+                stopped = true;
+                break;
+            }
+        }
+    }
+
+    replaying.store(false, Ordering::Relaxed);
+    let _ = app.emit("replay-finished", stopped);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: f64, event: &str) -> ReplayRecord {
+        ReplayRecord {
+            timestamp,
+            event: event.to_string(),
+            payload: serde_json::json!({ "message": event }),
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_events_replay_with_their_relative_timing() {
+        let dir = std::env::temp_dir().join(format!("ds-replay-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (tx, rx) = mpsc::channel(4);
+        tx.send(record(1000.25, "robot-state")).await.unwrap();
+        tx.send(record(1001.75, "console-message")).await.unwrap();
+        drop(tx);
+        session_recorder(rx, dir.clone()).await;
+
+        let file = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        let records = parse_recording(std::io::BufReader::new(std::fs::File::open(file).unwrap()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let records = records.unwrap();
+        assert_eq!(
+            records,
+            vec![record(1000.25, "robot-state"), record(1001.75, "console-message")]
+        );
+        assert_eq!(replay_offsets(&records), vec![Duration::ZERO, Duration::from_millis(1500)]);
+    }
+
+    #[test]
+    fn replay_never_goes_back_in_time() {
+        let records = [record(10.0, "a"), record(12.0, "b"), record(11.0, "c")];
+        assert_eq!(
+            replay_offsets(&records),
+            vec![Duration::ZERO, Duration::from_secs(2), Duration::from_secs(2)]
+        );
+    }
+}
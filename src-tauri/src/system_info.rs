@@ -1,20 +1,79 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
 use crate::protocol::connection::DsEvent;
+use crate::protocol::types::{Alert, AlertKind};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfoData {
     pub pc_battery_percent: Option<f32>,
+    /// Mean of `per_core`
     pub pc_cpu_usage: f32,
     pub pc_charging: bool,
+    /// Usage of each logical CPU (percent)
+    pub per_core: Vec<f32>,
+    /// Bytes
+    pub memory_total: u64,
+    /// Bytes
+    pub memory_available: u64,
+    /// Hottest CPU sensor (°C); None where the platform exposes no sensors
+    pub cpu_temp_c: Option<f32>,
+}
+
+/// Default CPU temperature above which the driver is warned
+pub const DEFAULT_CPU_TEMP_THRESHOLD: f32 = 90.0;
+
+/// Cooling needed below the threshold before the warning can fire again
+const CPU_TEMP_HYSTERESIS: f32 = 5.0;
+
+/// Whether a sensor label looks like a CPU package/core/die sensor
+fn is_cpu_sensor(label: &str) -> bool {
+    let label = label.to_lowercase();
+    ["cpu", "package", "core", "tctl", "tdie", "coretemp", "k10temp"]
+        .iter()
+        .any(|k| label.contains(k))
+}
+
+/// Hottest CPU reading among `components`, if any sensor reports one
+fn read_cpu_temp(components: &sysinfo::Components) -> Option<f32> {
+    components
+        .iter()
+        .filter(|c| is_cpu_sensor(c.label()))
+        .filter_map(|c| c.temperature())
+        .filter(|t| t.is_finite() && *t > 0.0)
+        .reduce(f32::max)
+}
+
+/// Samples kept for the sparkline (one minute at 1Hz)
+pub const SYSTEM_INFO_HISTORY_LEN: usize = 60;
+
+/// Most recent system info samples, oldest first
+pub type SystemInfoHistory = Arc<RwLock<VecDeque<SystemInfoData>>>;
+
+/// Mean of the per-core usages (0 when there are none)
+pub fn average_usage(per_core: &[f32]) -> f32 {
+    if per_core.is_empty() {
+        0.0
+    } else {
+        per_core.iter().sum::<f32>() / per_core.len() as f32
+    }
 }
 
-/// Polls host PC battery and CPU at ~1Hz, emitting SystemInfo events
-pub async fn system_info_loop(event_tx: mpsc::Sender<DsEvent>) {
-    use sysinfo::System;
+/// Polls host PC battery, CPU and memory at ~1Hz, emitting SystemInfo events
+pub async fn system_info_loop(
+    event_tx: mpsc::Sender<DsEvent>,
+    history: SystemInfoHistory,
+    temp_threshold: Arc<RwLock<f32>>,
+) {
+    use sysinfo::{Components, System};
 
     let mut sys = System::new();
+    let mut components = Components::new_with_refreshed_list();
+    let mut overheat_alerted = false;
     // Initial CPU refresh (sysinfo needs two measurements for accurate CPU)
     sys.refresh_cpu_usage();
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
@@ -22,21 +81,55 @@ pub async fn system_info_loop(event_tx: mpsc::Sender<DsEvent>) {
     loop {
         // CPU
         sys.refresh_cpu_usage();
-        let cpu_avg = if sys.cpus().is_empty() {
-            0.0
-        } else {
-            sys.cpus().iter().map(|c| c.cpu_usage()).sum::<f32>() / sys.cpus().len() as f32
-        };
+        let per_core: Vec<f32> = sys.cpus().iter().map(|c| c.cpu_usage()).collect();
+        components.refresh(false);
+        let cpu_temp = read_cpu_temp(&components);
+
+        // Memory
+        sys.refresh_memory();
 
         // Battery
         let (battery_pct, charging) = read_battery();
 
         let data = SystemInfoData {
             pc_battery_percent: battery_pct,
-            pc_cpu_usage: cpu_avg,
+            pc_cpu_usage: average_usage(&per_core),
             pc_charging: charging,
+            per_core,
+            memory_total: sys.total_memory(),
+            memory_available: sys.available_memory(),
+            cpu_temp_c: cpu_temp,
         };
 
+        // Warn once per overheating episode
+        if let Some(temp) = cpu_temp {
+            let threshold = *temp_threshold.read();
+            if temp > threshold && !overheat_alerted {
+                overheat_alerted = true;
+                tracing::warn!("PC CPU temperature {temp:.0}°C exceeds {threshold:.0}°C");
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                let alert = Alert::new(
+                    AlertKind::PcOverheat,
+                    format!("Driver station PC is hot ({temp:.0}°C), it may throttle"),
+                    timestamp,
+                );
+                let _ = event_tx.send(DsEvent::Alert(alert)).await;
+            } else if temp < threshold - CPU_TEMP_HYSTERESIS {
+                overheat_alerted = false;
+            }
+        }
+
+        {
+            let mut history = history.write();
+            if history.len() >= SYSTEM_INFO_HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(data.clone());
+        }
+
         if event_tx.send(DsEvent::SystemInfo(data)).await.is_err() {
             break;
         }
@@ -63,3 +156,22 @@ fn read_battery() -> (Option<f32>, bool) {
         (None, false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_usage_is_mean_of_cores() {
+        assert_eq!(average_usage(&[]), 0.0);
+        assert_eq!(average_usage(&[42.0]), 42.0);
+        assert!((average_usage(&[10.0, 20.0, 30.0, 100.0]) - 40.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cpu_sensor_labels() {
+        assert!(is_cpu_sensor("coretemp Package id 0"));
+        assert!(is_cpu_sensor("k10temp Tctl"));
+        assert!(!is_cpu_sensor("nvme Composite"));
+    }
+}
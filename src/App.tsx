@@ -62,6 +62,16 @@ export default function App() {
     }
   }, []);
 
+  // Keep the backend enable watchdog fed at all times. Whether any robot is
+  // enabled is decided by the DS, not by the robot's status echo, so the
+  // heartbeat can't depend on it; a frozen UI stops it and every robot is
+  // disabled automatically
+  useEffect(() => {
+    safeInvoke("heartbeat");
+    const interval = setInterval(() => safeInvoke("heartbeat"), 500);
+    return () => clearInterval(interval);
+  }, []);
+
   // Track enabled time
   useEffect(() => {
     if (state.enabled) {
@@ -122,12 +132,17 @@ export default function App() {
     switch (e.code) {
       case "Enter":
       case "NumpadEnter":
+        // Enter only disables, as on the official DS. Mapping it to E-Stop
+        // would turn the usual "stop the robot" key into a latch that needs
+        // a roboRIO reboot to clear.
         e.preventDefault();
-        safeInvoke("disable_robot");
+        if (!e.repeat) safeInvoke("disable_robot");
         break;
       case "Space":
+        // E-Stop latches in the backend, so auto-repeat is only skipped to
+        // avoid flooding the command channel
         e.preventDefault();
-        safeInvoke("estop_robot");
+        if (!e.repeat) safeInvoke("key_estop");
         break;
     }
   }, [compactMode, snapCompact, restoreFromCompact]);
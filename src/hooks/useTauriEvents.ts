@@ -16,6 +16,7 @@ export function useTauriEvents() {
   const setConnectionStatus = useRobotStore((s) => s.setConnectionStatus);
   const setVersionInfo = useRobotStore((s) => s.setVersionInfo);
   const addEntry = useLogStore((s) => s.addEntry);
+  const clearLog = useLogStore((s) => s.clear);
   const setGamepads = useGamepadStore((s) => s.setGamepads);
   const setSystemInfo = useSystemStore((s) => s.setSystemInfo);
   const setPowerData = usePowerStore((s) => s.setPowerData);
@@ -37,6 +38,10 @@ export function useTauriEvents() {
       addEntry(event.payload);
     }).then((u) => unlisten.push(u));
 
+    listen("console-cleared", () => {
+      clearLog();
+    }).then((u) => unlisten.push(u));
+
     listen<ConnectionStatus>("connection-status", (event) => {
       setConnectionStatus(event.payload);
     }).then((u) => unlisten.push(u));
@@ -67,5 +72,5 @@ export function useTauriEvents() {
     return () => {
       unlisten.forEach((u) => u());
     };
-  }, [setRobotState, setDiagnostics, setConnectionStatus, addEntry, setGamepads, setSystemInfo, setPowerData, setVersionInfo]);
+  }, [setRobotState, setDiagnostics, setConnectionStatus, addEntry, clearLog, setGamepads, setSystemInfo, setPowerData, setVersionInfo]);
 }
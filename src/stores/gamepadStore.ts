@@ -1,5 +1,11 @@
 import { create } from "zustand";
 
+export type GamepadBattery =
+  | { state: "Wired" }
+  | { state: "Charged" }
+  | { state: "Charging"; percent: number }
+  | { state: "Discharging"; percent: number };
+
 export interface GamepadInfo {
   id: number;
   name: string;
@@ -8,6 +14,10 @@ export interface GamepadInfo {
   buttons: boolean[];
   povs: number[];
   locked: boolean;
+  /** null when the controller doesn't report power state */
+  battery: GamepadBattery | null;
+  /** Physical button index → output button index (remapped buttons only) */
+  button_remap: Record<string, number>;
 }
 
 interface GamepadStore {
@@ -1,11 +1,21 @@
 import { create } from "zustand";
 
+/** Structured fields of a robot error/warning report */
+export interface LogEntryError {
+  error_code: number;
+  details: string;
+  location: string;
+  call_stack: string;
+}
+
 export interface LogEntry {
   timestamp: number;
   message: string;
   is_error: boolean;
   is_warning: boolean;
   sequence: number;
+  repeat_count: number;
+  error: LogEntryError | null;
 }
 
 interface LogStore {